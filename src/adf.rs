@@ -1,4 +1,4 @@
-use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use pulldown_cmark::{BlockQuoteKind, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
 use serde_json::{json, Value};
 
 /// Extract plain text from Atlassian Document Format (ADF) JSON.
@@ -6,16 +6,17 @@ pub fn to_plain_text(node: &Value) -> String {
     if node.is_null() {
         return String::new();
     }
+    // Data Center (API v2) returns rich text as wiki markup strings
+    if let Some(text) = node.as_str() {
+        return text.to_string();
+    }
 
     if let Some(text) = node.get("text").and_then(|t| t.as_str()) {
         return text.to_string();
     }
 
     if let Some(content) = node.get("content").and_then(|c| c.as_array()) {
-        let parts: Vec<String> = content
-            .iter()
-            .map(|child| to_plain_text(child))
-            .collect::<Vec<_>>();
+        let parts: Vec<String> = content.iter().map(to_plain_text).collect::<Vec<_>>();
         let node_type = node.get("type").and_then(|t| t.as_str()).unwrap_or("");
         return match node_type {
             "paragraph" | "heading" => format!("{}\n", parts.join("")),
@@ -33,6 +34,10 @@ pub fn get_node_text(node: &Value) -> String {
     if node.is_null() {
         return String::new();
     }
+    // Data Center (API v2) returns rich text as wiki markup strings
+    if let Some(text) = node.as_str() {
+        return text.to_string();
+    }
 
     if let Some(text) = node.get("text").and_then(|t| t.as_str()) {
         return text.to_string();
@@ -41,7 +46,7 @@ pub fn get_node_text(node: &Value) -> String {
     if let Some(content) = node.get("content").and_then(|c| c.as_array()) {
         return content
             .iter()
-            .map(|child| get_node_text(child))
+            .map(get_node_text)
             .collect::<Vec<_>>()
             .join("");
     }
@@ -49,18 +54,474 @@ pub fn get_node_text(node: &Value) -> String {
     String::new()
 }
 
-/// Create a simple ADF JSON structure (single paragraph) from a string.
-pub fn from_plain_text(text: &str) -> Value {
+/// Render Atlassian Document Format (ADF) JSON as CommonMark with GitHub extensions
+/// (tables, strikethrough, task lists).
+pub fn to_markdown(node: &Value) -> String {
+    if node.is_null() {
+        return String::new();
+    }
+    if let Some(text) = node.as_str() {
+        return text.trim_end().to_string();
+    }
+    let rendered = if node["type"] == "doc" {
+        render_blocks(children(node))
+    } else {
+        render_block(node)
+    };
+    let trimmed = rendered.trim_end();
+    if trimmed.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", trimmed)
+    }
+}
+
+/// Describe what `to_markdown` cannot represent in a document, i.e. what is lost when the
+/// Markdown is converted back (e.g. "mentions", "text colors"). Empty when nothing is.
+pub fn markdown_losses(node: &Value) -> Vec<&'static str> {
+    let mut losses = Vec::new();
+    collect_losses(node, &mut losses);
+    losses
+}
+
+fn collect_losses(node: &Value, losses: &mut Vec<&'static str>) {
+    let mut add = |loss: &'static str| {
+        if !losses.contains(&loss) {
+            losses.push(loss);
+        }
+    };
+    let merged = |name: &str| attr(node, name).as_u64().is_some_and(|n| n > 1);
+    match node["type"].as_str().unwrap_or("") {
+        "mention" => add("mentions"),
+        "media" | "mediaInline" if attr(node, "type") != "external" => add("attachments"),
+        "status" => add("status lozenges"),
+        "emoji" => add("emoji"),
+        "date" => add("dates"),
+        "inlineCard" | "blockCard" | "embedCard" => add("smart links"),
+        "expand" | "nestedExpand" => add("expands"),
+        "panel"
+            if !matches!(
+                attr(node, "panelType").as_str(),
+                Some("info" | "note" | "success" | "warning" | "error")
+            ) =>
+        {
+            add("custom panels")
+        }
+        "decisionList" => add("decisions"),
+        "extension" | "bodiedExtension" | "inlineExtension" => add("macros"),
+        "layoutSection" => add("layouts"),
+        "table" if attr(node, "isNumberColumnEnabled") == true => add("table layout"),
+        "tableCell" | "tableHeader"
+            if !attr(node, "colwidth").is_null()
+                || !attr(node, "background").is_null()
+                || merged("colspan")
+                || merged("rowspan") =>
+        {
+            add("table layout")
+        }
+        _ => {}
+    }
+    for mark in node["marks"].as_array().map(Vec::as_slice).unwrap_or(&[]) {
+        match mark["type"].as_str().unwrap_or("") {
+            "textColor" | "backgroundColor" => add("text colors"),
+            "underline" => add("underline"),
+            "subsup" => add("subscript and superscript"),
+            "alignment" | "indentation" => add("alignment"),
+            "annotation" => add("inline comments"),
+            _ => {}
+        }
+    }
+    for child in children(node) {
+        collect_losses(child, losses);
+    }
+}
+
+fn children(node: &Value) -> &[Value] {
+    node.get("content")
+        .and_then(|c| c.as_array())
+        .map(|a| a.as_slice())
+        .unwrap_or(&[])
+}
+
+fn attr<'a>(node: &'a Value, name: &str) -> &'a Value {
+    &node["attrs"][name]
+}
+
+fn render_blocks(nodes: &[Value]) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        let block = render_block(node);
+        if block.is_empty() {
+            continue;
+        }
+        if !out.is_empty() {
+            out.push_str("\n\n");
+        }
+        out.push_str(&block);
+    }
+    out
+}
+
+/// Prefix the first line with `first` and every following non-empty line with `rest`.
+fn indent_lines(text: &str, first: &str, rest: &str) -> String {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 {
+                format!("{}{}", first, line)
+            } else if line.is_empty() {
+                String::new()
+            } else {
+                format!("{}{}", rest, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_block(node: &Value) -> String {
+    let node_type = node["type"].as_str().unwrap_or("");
+    match node_type {
+        "paragraph" => escape_line_starts(&render_inlines(children(node))),
+        "heading" => {
+            let level = attr(node, "level").as_u64().unwrap_or(1).clamp(1, 6) as usize;
+            format!("{} {}", "#".repeat(level), render_inlines(children(node)))
+        }
+        "bulletList" => render_list(node, None),
+        "orderedList" => {
+            let start = attr(node, "order").as_u64().unwrap_or(1);
+            render_list(node, Some(start))
+        }
+        "taskList" | "decisionList" => children(node)
+            .iter()
+            .map(render_block)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        "taskItem" | "decisionItem" => {
+            let done = matches!(attr(node, "state").as_str(), Some("DONE") | Some("DECIDED"));
+            let body = render_inlines(children(node));
+            indent_lines(&body, if done { "- [x] " } else { "- [ ] " }, "      ")
+        }
+        "codeBlock" => {
+            let code = get_node_text(node);
+            let language = attr(node, "language").as_str().unwrap_or("");
+            let mut fence = "```".to_string();
+            while code.contains(&fence) {
+                fence.push('`');
+            }
+            format!(
+                "{}{}\n{}\n{}",
+                fence,
+                language,
+                code.trim_end_matches('\n'),
+                fence
+            )
+        }
+        "blockquote" => quote(&render_blocks(children(node))),
+        "panel" => {
+            let alert = match attr(node, "panelType").as_str().unwrap_or("info") {
+                "success" => "TIP",
+                "warning" => "WARNING",
+                "error" => "CAUTION",
+                "note" => "NOTE",
+                _ => "IMPORTANT",
+            };
+            quote(&format!("[!{}]\n{}", alert, render_blocks(children(node))))
+        }
+        "rule" => "---".to_string(),
+        "table" => render_table(node),
+        "mediaSingle" | "mediaGroup" => children(node)
+            .iter()
+            .map(render_media)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        "expand" | "nestedExpand" => {
+            let title = attr(node, "title").as_str().unwrap_or("");
+            let body = render_blocks(children(node));
+            if title.is_empty() {
+                body
+            } else {
+                format!("**{}**\n\n{}", escape_markdown(title), body)
+            }
+        }
+        "blockCard" | "embedCard" => {
+            let url = attr(node, "url").as_str().unwrap_or("");
+            format!("<{}>", url)
+        }
+        _ => {
+            // Unknown block: fall back to its children, or treat it as inline content.
+            if node.get("text").is_some() {
+                render_inlines(std::slice::from_ref(node))
+            } else {
+                render_blocks(children(node))
+            }
+        }
+    }
+}
+
+fn quote(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                ">".to_string()
+            } else {
+                format!("> {}", line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_list(node: &Value, start: Option<u64>) -> String {
+    children(node)
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let marker = match start {
+                Some(n) => format!("{}. ", n + i as u64),
+                None => "- ".to_string(),
+            };
+            let padding = " ".repeat(marker.len());
+            indent_lines(&render_list_item(item), &marker, &padding)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// List item children are separated by a single newline before nested lists (keeping the
+/// list tight) and by a blank line between paragraphs.
+fn render_list_item(item: &Value) -> String {
+    let mut out = String::new();
+    for child in children(item) {
+        let block = render_block(child);
+        if block.is_empty() {
+            continue;
+        }
+        if !out.is_empty() {
+            let child_type = child["type"].as_str().unwrap_or("");
+            if matches!(child_type, "bulletList" | "orderedList" | "taskList") {
+                out.push('\n');
+            } else {
+                out.push_str("\n\n");
+            }
+        }
+        out.push_str(&block);
+    }
+    out
+}
+
+fn render_table(node: &Value) -> String {
+    let rows: Vec<Vec<String>> = children(node)
+        .iter()
+        .map(|row| children(row).iter().map(render_cell).collect())
+        .collect();
+
+    let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    if columns == 0 {
+        return String::new();
+    }
+
+    let format_row = |cells: &[String]| {
+        let mut padded: Vec<String> = cells.to_vec();
+        padded.resize(columns, String::new());
+        format!("| {} |", padded.join(" | "))
+    };
+
+    // GFM tables always need a header row; use an empty one if the first row has no headers.
+    let first_is_header = children(node)
+        .first()
+        .map(|row| children(row).iter().all(|c| c["type"] == "tableHeader"))
+        .unwrap_or(false);
+
+    let mut lines = Vec::new();
+    let body = if first_is_header {
+        lines.push(format_row(&rows[0]));
+        &rows[1..]
+    } else {
+        lines.push(format_row(&[]));
+        &rows[..]
+    };
+    lines.push(format!("|{}", " --- |".repeat(columns)));
+    for row in body {
+        lines.push(format_row(row));
+    }
+    lines.join("\n")
+}
+
+/// Render a table cell on one line. Line breaks become `<br>`; the backslash that marks a
+/// hard break elsewhere is dropped, since `<br>` already is one.
+fn render_cell(cell: &Value) -> String {
+    let text = children(cell)
+        .iter()
+        .map(render_block)
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    text.lines()
+        .map(|line| {
+            // Literal backslashes are escaped in pairs, so an odd count ends in a hard break
+            let trailing = line.len() - line.trim_end_matches('\\').len();
+            if trailing % 2 == 1 {
+                &line[..line.len() - 1]
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("<br>")
+        .replace('|', "\\|")
+}
+
+fn render_media(node: &Value) -> String {
+    let alt = attr(node, "alt").as_str().unwrap_or("");
+    match attr(node, "type").as_str() {
+        Some("external") => format!(
+            "![{}]({})",
+            escape_markdown(alt),
+            attr(node, "url").as_str().unwrap_or("")
+        ),
+        _ => {
+            let label = if alt.is_empty() {
+                attr(node, "id").as_str().unwrap_or("file")
+            } else {
+                alt
+            };
+            format!("[attachment: {}]", escape_markdown(label))
+        }
+    }
+}
+
+fn render_inlines(nodes: &[Value]) -> String {
+    nodes.iter().map(render_inline).collect::<Vec<_>>().join("")
+}
+
+fn render_inline(node: &Value) -> String {
+    match node["type"].as_str().unwrap_or("") {
+        "text" => render_text(node),
+        "hardBreak" => "\\\n".to_string(),
+        "mention" => {
+            let text = attr(node, "text").as_str().unwrap_or("");
+            if text.is_empty() {
+                format!("@{}", attr(node, "id").as_str().unwrap_or("unknown"))
+            } else if text.starts_with('@') {
+                text.to_string()
+            } else {
+                format!("@{}", text)
+            }
+        }
+        "emoji" => attr(node, "text")
+            .as_str()
+            .or_else(|| attr(node, "shortName").as_str())
+            .unwrap_or("")
+            .to_string(),
+        "inlineCard" => format!("<{}>", attr(node, "url").as_str().unwrap_or("")),
+        "status" => format!("`{}`", attr(node, "text").as_str().unwrap_or("")),
+        "date" => attr(node, "timestamp")
+            .as_str()
+            .and_then(|t| t.parse::<i64>().ok())
+            .or_else(|| attr(node, "timestamp").as_i64())
+            .map(crate::dates::format_date)
+            .unwrap_or_default(),
+        "media" | "mediaInline" => render_media(node),
+        _ => render_inlines(children(node)),
+    }
+}
+
+fn render_text(node: &Value) -> String {
+    let text = node["text"].as_str().unwrap_or("");
+    let marks = node["marks"].as_array().cloned().unwrap_or_default();
+
+    if marks.iter().any(|m| m["type"] == "code") {
+        let mut ticks = "`".to_string();
+        while text.contains(&ticks) {
+            ticks.push('`');
+        }
+        let code = if text.starts_with('`') || text.ends_with('`') {
+            format!(" {} ", text)
+        } else {
+            text.to_string()
+        };
+        let rendered = format!("{}{}{}", ticks, code, ticks);
+        return match marks.iter().find(|m| m["type"] == "link") {
+            Some(link) => format!(
+                "[{}]({})",
+                rendered,
+                link["attrs"]["href"].as_str().unwrap_or("")
+            ),
+            None => rendered,
+        };
+    }
+
+    let mut out = escape_markdown(text);
+    for mark in &marks {
+        out = match mark["type"].as_str().unwrap_or("") {
+            "strong" => format!("**{}**", out),
+            "em" => format!("*{}*", out),
+            "strike" => format!("~~{}~~", out),
+            "link" => format!(
+                "[{}]({})",
+                out,
+                mark["attrs"]["href"].as_str().unwrap_or("")
+            ),
+            _ => out,
+        };
+    }
+    out
+}
+
+/// Escape characters that would otherwise be interpreted as inline Markdown syntax.
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '~' | '<') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Escape characters at the start of each line that would otherwise begin a block: headings,
+/// quotes, list items, rules and setext underlines.
+fn escape_line_starts(text: &str) -> String {
+    text.split('\n')
+        .map(|line| {
+            let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            let marker = if digits > 0 {
+                line[digits..].starts_with(['.', ')']).then_some(digits)
+            } else {
+                line.starts_with(['#', '>', '-', '+', '=']).then_some(0)
+            };
+            match marker {
+                Some(i) => format!("{}\\{}", &line[..i], &line[i..]),
+                None => line.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Create an ADF document from user-supplied text: Markdown by default, or literal text when
+/// `plain` is set (line breaks are kept as hard breaks).
+pub fn from_text(text: &str, plain: bool) -> Value {
+    let content = if plain {
+        let mut inline = Vec::new();
+        for (i, line) in text.trim_end_matches('\n').lines().enumerate() {
+            if i > 0 {
+                inline.push(json!({ "type": "hardBreak" }));
+            }
+            if !line.is_empty() {
+                inline.push(json!({ "type": "text", "text": line }));
+            }
+        }
+        vec![json!({ "type": "paragraph", "content": inline })]
+    } else {
+        from_markdown(text)
+    };
     json!({
         "type": "doc",
         "version": 1,
-        "content": [{
-            "type": "paragraph",
-            "content": [{
-                "type": "text",
-                "text": text
-            }]
-        }]
+        "content": content
     })
 }
 
@@ -131,97 +592,421 @@ pub fn from_plain_text_with_attachment(text: &str, media_id: &str) -> Value {
 }
 
 /// Convert Markdown to ADF nodes.
+///
+/// Understands CommonMark plus the GitHub extensions: tables, strikethrough, task lists and
+/// alerts (`> [!NOTE]`, which become panels). Images become external `mediaSingle` nodes.
 pub fn from_markdown(markdown: &str) -> Vec<Value> {
-    let parser = Parser::new(markdown);
-    let mut doc_nodes = Vec::new();
-    let mut current_block: Option<Value> = None;
-    let mut current_block_content = Vec::new();
-    let mut list_stack: Vec<(Value, Vec<Value>)> = Vec::new();
-    let mut marks: Vec<Value> = Vec::new();
-
-    for event in parser {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_GFM;
+    let mut builder = AdfBuilder::default();
+    for event in Parser::new_ext(markdown, options) {
+        builder.event(event);
+    }
+    builder.finish()
+}
+
+/// A node under construction, together with the children collected so far.
+struct Frame {
+    node: Value,
+    content: Vec<Value>,
+    /// Paragraph opened to hold the inline content of a tight list item or table cell.
+    implicit: bool,
+}
+
+/// Builds ADF block nodes from a stream of pulldown-cmark events.
+#[derive(Default)]
+struct AdfBuilder {
+    doc: Vec<Value>,
+    stack: Vec<Frame>,
+    marks: Vec<Value>,
+    in_table_head: bool,
+    /// URL and alt text of the image currently being parsed.
+    image: Option<(String, String)>,
+}
+
+impl AdfBuilder {
+    fn event(&mut self, event: Event) {
         match event {
-            Event::Start(tag) => match tag {
-                Tag::Paragraph => {
-                    current_block = Some(json!({ "type": "paragraph" }));
-                }
-                Tag::Heading { level, .. } => {
-                    current_block =
-                        Some(json!({ "type": "heading", "attrs": { "level": level as u8 } }));
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag_end) => self.end(tag_end),
+            Event::Text(text) => self.text(&text),
+            Event::Code(code) => {
+                // ADF only allows the code mark to be combined with a link.
+                let marks: Vec<Value> = self
+                    .marks
+                    .iter()
+                    .filter(|m| m["type"] == "link")
+                    .cloned()
+                    .chain(std::iter::once(json!({ "type": "code" })))
+                    .collect();
+                self.push_inline(
+                    json!({ "type": "text", "text": code.to_string(), "marks": marks }),
+                );
+            }
+            Event::Html(html) | Event::InlineHtml(html) => self.text(&html),
+            Event::SoftBreak => self.text(" "),
+            Event::HardBreak => self.push_inline(json!({ "type": "hardBreak" })),
+            Event::Rule => self.push_block(json!({ "type": "rule" })),
+            Event::TaskListMarker(checked) => self.mark_task(checked),
+            Event::FootnoteReference(label) => self.text(&format!("[^{}]", label)),
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.open(json!({ "type": "paragraph" })),
+            Tag::Heading { level, .. } => {
+                self.open(json!({ "type": "heading", "attrs": { "level": level as u8 } }))
+            }
+            Tag::BlockQuote(None) => self.open(json!({ "type": "blockquote" })),
+            Tag::BlockQuote(Some(kind)) => {
+                let panel_type = match kind {
+                    BlockQuoteKind::Note => "note",
+                    BlockQuoteKind::Tip => "success",
+                    BlockQuoteKind::Important => "info",
+                    BlockQuoteKind::Warning => "warning",
+                    BlockQuoteKind::Caution => "error",
+                };
+                self.open(json!({ "type": "panel", "attrs": { "panelType": panel_type } }))
+            }
+            Tag::CodeBlock(kind) => {
+                let mut node = json!({ "type": "codeBlock" });
+                if let CodeBlockKind::Fenced(info) = kind {
+                    if let Some(language) = info.split_whitespace().next() {
+                        node["attrs"] = json!({ "language": language });
+                    }
                 }
-                Tag::List(ordered) => {
-                    let list_type = if ordered.is_some() {
-                        "orderedList"
-                    } else {
-                        "bulletList"
-                    };
-                    list_stack.push((json!({ "type": list_type }), Vec::new()));
+                self.open(node)
+            }
+            Tag::List(Some(start)) if start != 1 => {
+                self.open(json!({ "type": "orderedList", "attrs": { "order": start } }))
+            }
+            Tag::List(Some(_)) => self.open(json!({ "type": "orderedList" })),
+            Tag::List(None) => self.open(json!({ "type": "bulletList" })),
+            Tag::Item => self.open(json!({ "type": "listItem" })),
+            Tag::Table(_) => self.open(json!({ "type": "table" })),
+            Tag::TableHead => {
+                self.in_table_head = true;
+                self.open(json!({ "type": "tableRow" }))
+            }
+            Tag::TableRow => self.open(json!({ "type": "tableRow" })),
+            Tag::TableCell => {
+                let cell_type = if self.in_table_head {
+                    "tableHeader"
+                } else {
+                    "tableCell"
+                };
+                self.open(json!({ "type": cell_type }))
+            }
+            Tag::Emphasis => self.marks.push(json!({ "type": "em" })),
+            Tag::Strong => self.marks.push(json!({ "type": "strong" })),
+            Tag::Strikethrough => self.marks.push(json!({ "type": "strike" })),
+            Tag::Link { dest_url, .. } => self
+                .marks
+                .push(json!({ "type": "link", "attrs": { "href": dest_url.to_string() } })),
+            Tag::Image { dest_url, .. } => self.image = Some((dest_url.to_string(), String::new())),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag_end: TagEnd) {
+        match tag_end {
+            TagEnd::Paragraph
+            | TagEnd::Heading(_)
+            | TagEnd::BlockQuote(_)
+            | TagEnd::CodeBlock
+            | TagEnd::List(_)
+            | TagEnd::Item
+            | TagEnd::Table
+            | TagEnd::TableRow
+            | TagEnd::TableCell => self.close_explicit(),
+            TagEnd::TableHead => {
+                self.close_explicit();
+                self.in_table_head = false;
+            }
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link => {
+                self.marks.pop();
+            }
+            TagEnd::Image => {
+                if let Some((url, alt)) = self.image.take() {
+                    self.push_image(url, alt);
                 }
-                Tag::Item => {
-                    current_block = Some(json!({ "type": "listItem" }));
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(mut self) -> Vec<Value> {
+        while !self.stack.is_empty() {
+            self.close();
+        }
+        self.doc
+    }
+
+    fn top_type(&self) -> &str {
+        self.stack
+            .last()
+            .and_then(|f| f.node["type"].as_str())
+            .unwrap_or("doc")
+    }
+
+    fn close_implicit(&mut self) {
+        if self.stack.last().is_some_and(|f| f.implicit) {
+            self.close();
+        }
+    }
+
+    fn open(&mut self, node: Value) {
+        self.close_implicit();
+        self.stack.push(Frame {
+            node,
+            content: Vec::new(),
+            implicit: false,
+        });
+    }
+
+    /// Close the frame opened by the matching start tag, along with any implicit paragraph.
+    fn close_explicit(&mut self) {
+        self.close_implicit();
+        self.close();
+    }
+
+    fn close(&mut self) {
+        let Some(Frame {
+            mut node, content, ..
+        }) = self.stack.pop()
+        else {
+            return;
+        };
+
+        match node["type"].as_str().unwrap_or("") {
+            "paragraph" => {
+                for block in split_paragraph(node, content) {
+                    self.push_block(block);
                 }
-                Tag::Strong => marks.push(json!({ "type": "strong" })),
-                Tag::Emphasis => marks.push(json!({ "type": "em" })),
-                Tag::Link { dest_url, .. } => {
-                    marks.push(json!({ "type": "link", "attrs": { "href": dest_url.to_string() } }))
+                return;
+            }
+            "codeBlock" => {
+                let code: String = content.iter().filter_map(|t| t["text"].as_str()).collect();
+                let code = code.trim_end_matches('\n');
+                if !code.is_empty() {
+                    node["content"] = json!([{ "type": "text", "text": code }]);
                 }
-                _ => {}
-            },
-            Event::End(tag_end) => match tag_end {
-                TagEnd::Paragraph | TagEnd::Heading(_) => {
-                    if let Some(mut block) = current_block.take() {
-                        block["content"] = json!(current_block_content);
-                        current_block_content = Vec::new();
-                        if list_stack.is_empty() {
-                            doc_nodes.push(block);
-                        } else {
-                            list_stack.last_mut().unwrap().1.push(block);
-                        }
-                    }
+            }
+            "taskItem" => {
+                let (inline, nested) = flatten_task_item(content);
+                node["content"] = json!(inline);
+                self.push_block(node);
+                for list in nested {
+                    self.push_block(list);
                 }
-                TagEnd::List(_) => {
-                    if let Some((mut list, content)) = list_stack.pop() {
-                        list["content"] = json!(content);
-                        if list_stack.is_empty() {
-                            doc_nodes.push(list);
+                return;
+            }
+            "taskList" => {
+                // Plain items in a list that also contains task items become open tasks.
+                let items: Vec<Value> = content
+                    .into_iter()
+                    .flat_map(|item| {
+                        if item["type"] == "listItem" {
+                            let (inline, nested) = flatten_task_item(
+                                item["content"].as_array().cloned().unwrap_or_default(),
+                            );
+                            let task = json!({
+                                "type": "taskItem",
+                                "attrs": { "localId": local_id(), "state": "TODO" },
+                                "content": inline
+                            });
+                            std::iter::once(task).chain(nested).collect::<Vec<_>>()
                         } else {
-                            list_stack.last_mut().unwrap().1.push(list);
+                            vec![item]
                         }
-                    }
+                    })
+                    .collect();
+                node["content"] = json!(items);
+            }
+            "listItem" | "tableCell" | "tableHeader" if content.is_empty() => {
+                node["content"] = json!([{ "type": "paragraph", "content": [] }]);
+            }
+            _ => node["content"] = json!(content),
+        }
+        self.push_block(node);
+    }
+
+    fn push_block(&mut self, node: Value) {
+        self.close_implicit();
+        match self.stack.last_mut() {
+            Some(frame) => frame.content.push(node),
+            None => self.doc.push(node),
+        }
+    }
+
+    /// Add inline content, opening a paragraph first where ADF requires block content.
+    fn push_inline(&mut self, node: Value) {
+        if matches!(
+            self.top_type(),
+            "doc" | "listItem" | "tableCell" | "tableHeader" | "blockquote" | "panel"
+        ) {
+            self.stack.push(Frame {
+                node: json!({ "type": "paragraph" }),
+                content: Vec::new(),
+                implicit: true,
+            });
+        }
+        if let Some(frame) = self.stack.last_mut() {
+            frame.content.push(node);
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if let Some((_, alt)) = self.image.as_mut() {
+            alt.push_str(text);
+            return;
+        }
+        if self.top_type() == "codeBlock" {
+            if let Some(frame) = self.stack.last_mut() {
+                frame.content.push(json!({ "type": "text", "text": text }));
+            }
+            return;
+        }
+        let mut node = json!({ "type": "text", "text": text });
+        if !self.marks.is_empty() {
+            node["marks"] = json!(self.marks);
+        }
+        // The parser splits text at escapes and entities; keep runs with the same marks whole
+        if let Some(last) = self.stack.last_mut().and_then(|f| f.content.last_mut()) {
+            if last["type"] == "text" && last.get("marks") == node.get("marks") {
+                let joined = format!("{}{}", last["text"].as_str().unwrap_or(""), text);
+                last["text"] = json!(joined);
+                return;
+            }
+        }
+        self.push_inline(node);
+    }
+
+    /// Images are block nodes in ADF. Inside paragraphs they are split out when the paragraph
+    /// closes; elsewhere (headings, task items) they degrade to a link.
+    fn push_image(&mut self, url: String, alt: String) {
+        let mut media = json!({ "type": "external", "url": url });
+        if !alt.is_empty() {
+            media["alt"] = json!(alt);
+        }
+        let single = json!({
+            "type": "mediaSingle",
+            "attrs": { "layout": "center" },
+            "content": [{ "type": "media", "attrs": media }]
+        });
+        if matches!(self.top_type(), "heading" | "taskItem") {
+            let text = if alt.is_empty() { url.clone() } else { alt };
+            let mut marks = self.marks.clone();
+            marks.push(json!({ "type": "link", "attrs": { "href": url } }));
+            self.push_inline(json!({ "type": "text", "text": text, "marks": marks }));
+        } else {
+            self.push_inline(single);
+        }
+    }
+
+    /// Turn the enclosing list item into a task item and its list into a task list.
+    fn mark_task(&mut self, checked: bool) {
+        let Some(index) = self
+            .stack
+            .iter()
+            .rposition(|f| f.node["type"] == "listItem")
+        else {
+            return;
+        };
+        self.stack[index].node = json!({
+            "type": "taskItem",
+            "attrs": {
+                "localId": local_id(),
+                "state": if checked { "DONE" } else { "TODO" }
+            }
+        });
+        if index > 0 && self.stack[index - 1].node["type"] != "taskList" {
+            self.stack[index - 1].node = json!({
+                "type": "taskList",
+                "attrs": { "localId": local_id() }
+            });
+        }
+    }
+}
+
+/// Split a paragraph around any images it contains, dropping runs that are only whitespace.
+fn split_paragraph(node: Value, content: Vec<Value>) -> Vec<Value> {
+    if !content.iter().any(|n| n["type"] == "mediaSingle") {
+        let mut paragraph = node;
+        paragraph["content"] = json!(content);
+        return vec![paragraph];
+    }
+
+    let mut blocks = Vec::new();
+    let mut runs = content.split(|n| n["type"] == "mediaSingle");
+    let images = content.iter().filter(|n| n["type"] == "mediaSingle");
+    let push_run = |blocks: &mut Vec<Value>, run: &[Value]| {
+        let blank = run
+            .iter()
+            .all(|n| n["type"] == "text" && n["text"].as_str().unwrap_or("").trim().is_empty());
+        if !blank {
+            let mut paragraph = node.clone();
+            paragraph["content"] = json!(run);
+            blocks.push(paragraph);
+        }
+    };
+    for image in images {
+        if let Some(run) = runs.next() {
+            push_run(&mut blocks, run);
+        }
+        blocks.push(image.clone());
+    }
+    for run in runs {
+        push_run(&mut blocks, run);
+    }
+    blocks
+}
+
+/// Task items hold inline content only: paragraphs are joined with hard breaks, nested task
+/// lists are returned separately (they become siblings in the parent list), and any other
+/// block is reduced to its text.
+fn flatten_task_item(content: Vec<Value>) -> (Vec<Value>, Vec<Value>) {
+    let mut inline = Vec::new();
+    let mut nested = Vec::new();
+    for child in content {
+        match child["type"].as_str().unwrap_or("") {
+            "taskList" => nested.push(child),
+            "paragraph" => {
+                if !inline.is_empty() {
+                    inline.push(json!({ "type": "hardBreak" }));
                 }
-                TagEnd::Item => {
-                    if let Some(mut item) = current_block.take() {
-                        item["content"] = json!(current_block_content);
-                        current_block_content = Vec::new();
-                        list_stack.last_mut().unwrap().1.push(item);
+                inline.extend(child["content"].as_array().cloned().unwrap_or_default());
+            }
+            _ => {
+                let text = get_node_text(&child);
+                if !text.is_empty() {
+                    if !inline.is_empty() {
+                        inline.push(json!({ "type": "hardBreak" }));
                     }
+                    inline.push(json!({ "type": "text", "text": text }));
                 }
-                TagEnd::Strong | TagEnd::Emphasis | TagEnd::Link => {
-                    marks.pop();
-                }
-                _ => {}
-            },
-            Event::Text(text) => {
-                let mut node = json!({
-                    "type": "text",
-                    "text": text.to_string()
-                });
-                if !marks.is_empty() {
-                    node["marks"] = json!(marks);
-                }
-                current_block_content.push(node);
-            }
-            Event::SoftBreak => {
-                current_block_content.push(json!({ "type": "text", "text": " " }));
             }
-            Event::HardBreak => {
-                current_block_content.push(json!({ "type": "hardBreak" }));
-            }
-            _ => {}
         }
     }
+    (inline, nested)
+}
 
-    doc_nodes
+/// Random identifier for nodes that require a `localId` (task lists and items).
+fn local_id() -> String {
+    let bytes: [u8; 16] = rand::random();
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
 }
 
 /// Append nodes to the end of the ADF document.
@@ -410,4 +1195,206 @@ mod tests {
         assert!(find_anchor_index(&doc, "heading:notfound").is_err());
         assert!(find_anchor_index(&doc, "heading:").is_err()); // Ambiguous if query is empty and multiple headings exist
     }
+
+    #[test]
+    fn test_to_markdown_blocks() {
+        let doc = json!({
+            "type": "doc",
+            "version": 1,
+            "content": [
+                { "type": "heading", "attrs": { "level": 2 }, "content": [{ "type": "text", "text": "Setup" }] },
+                { "type": "paragraph", "content": [
+                    { "type": "text", "text": "Run " },
+                    { "type": "text", "text": "make", "marks": [{ "type": "code" }] },
+                    { "type": "text", "text": " then see " },
+                    { "type": "text", "text": "docs", "marks": [{ "type": "link", "attrs": { "href": "https://example.com" } }] },
+                    { "type": "text", "text": " or ask " },
+                    { "type": "mention", "attrs": { "id": "abc", "text": "@Jane" } }
+                ]},
+                { "type": "bulletList", "content": [
+                    { "type": "listItem", "content": [
+                        { "type": "paragraph", "content": [{ "type": "text", "text": "one", "marks": [{ "type": "strong" }] }] },
+                        { "type": "orderedList", "attrs": { "order": 3 }, "content": [
+                            { "type": "listItem", "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": "nested" }] }] }
+                        ]}
+                    ]},
+                    { "type": "listItem", "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": "two" }] }] }
+                ]},
+                { "type": "codeBlock", "attrs": { "language": "rust" }, "content": [{ "type": "text", "text": "fn main() {}" }] },
+                { "type": "panel", "attrs": { "panelType": "warning" }, "content": [
+                    { "type": "paragraph", "content": [{ "type": "text", "text": "Careful" }] }
+                ]},
+                { "type": "rule" }
+            ]
+        });
+
+        let expected = "## Setup\n\n\
+            Run `make` then see [docs](https://example.com) or ask @Jane\n\n\
+            - **one**\n  3. nested\n- two\n\n\
+            ```rust\nfn main() {}\n```\n\n\
+            > [!WARNING]\n> Careful\n\n\
+            ---\n";
+        assert_eq!(to_markdown(&doc), expected);
+    }
+
+    #[test]
+    fn test_to_markdown_table_and_inline_nodes() {
+        let doc = json!({
+            "type": "doc",
+            "content": [
+                { "type": "table", "content": [
+                    { "type": "tableRow", "content": [
+                        { "type": "tableHeader", "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": "Name" }] }] },
+                        { "type": "tableHeader", "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": "State" }] }] }
+                    ]},
+                    { "type": "tableRow", "content": [
+                        { "type": "tableCell", "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": "a|b" }] }] },
+                        { "type": "tableCell", "content": [{ "type": "paragraph", "content": [
+                            { "type": "status", "attrs": { "text": "DONE" } },
+                            { "type": "text", "text": " " },
+                            { "type": "emoji", "attrs": { "shortName": ":tada:", "text": "🎉" } }
+                        ]}] }
+                    ]}
+                ]},
+                { "type": "paragraph", "content": [
+                    { "type": "date", "attrs": { "timestamp": "1700000000000" } },
+                    { "type": "text", "text": " " },
+                    { "type": "inlineCard", "attrs": { "url": "https://example.com/x" } }
+                ]},
+                { "type": "mediaSingle", "content": [
+                    { "type": "media", "attrs": { "type": "external", "url": "https://example.com/a.png", "alt": "diagram" } }
+                ]}
+            ]
+        });
+
+        let expected = "| Name | State |\n| --- | --- |\n| a\\|b | `DONE` 🎉 |\n\n\
+            2023-11-14 <https://example.com/x>\n\n\
+            ![diagram](https://example.com/a.png)\n";
+        assert_eq!(to_markdown(&doc), expected);
+    }
+
+    fn round_trip(md: &str) -> String {
+        to_markdown(&json!({ "type": "doc", "version": 1, "content": from_markdown(md) }))
+    }
+
+    #[test]
+    fn test_from_markdown_round_trip() {
+        let md = "## Release notes\n\n\
+            Run `make`, skip ~~old~~ steps and read *all* of **this**.\\\n\
+            Second line with [docs](https://example.com)\n\n\
+            > Quoted\n\n\
+            1. first\n\
+            2. second\n   - nested\n\n\
+            | Name | State |\n| --- | --- |\n| a | `b` |\n\n\
+            5. five\n\n\
+            ```rust\nfn main() {}\n```\n\n\
+            ![diagram](https://example.com/a.png)\n\n\
+            ---\n\n\
+            > [!WARNING]\n> Careful\n";
+        assert_eq!(round_trip(md), md);
+
+        let tasks = "- [x] done\n- [ ] todo\n";
+        assert_eq!(round_trip(tasks), tasks);
+    }
+
+    #[test]
+    fn test_markdown_losses() {
+        let doc = json!({ "type": "doc", "content": [
+            { "type": "paragraph", "content": [
+                { "type": "text", "text": "plain " },
+                { "type": "text", "text": "bold", "marks": [{ "type": "strong" }] },
+                { "type": "mention", "attrs": { "id": "abc", "text": "@Jane" } },
+                { "type": "text", "text": "red", "marks": [{ "type": "textColor", "attrs": { "color": "#ff0000" } }] }
+            ]},
+            { "type": "panel", "attrs": { "panelType": "warning" }, "content": [] },
+            { "type": "mediaSingle", "content": [{ "type": "media", "attrs": { "type": "file", "id": "1" } }] }
+        ]});
+        assert_eq!(
+            markdown_losses(&doc),
+            ["mentions", "text colors", "attachments"]
+        );
+        assert!(markdown_losses(
+            &json!({ "type": "doc", "content": from_markdown("**a** [b](https://x)") })
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_to_markdown_escapes_block_markers() {
+        let paragraph = |lines: &[&str]| {
+            let mut content = Vec::new();
+            for (i, line) in lines.iter().enumerate() {
+                if i > 0 {
+                    content.push(json!({ "type": "hardBreak" }));
+                }
+                content.push(json!({ "type": "text", "text": line }));
+            }
+            json!({ "type": "paragraph", "content": content })
+        };
+        let lines = [
+            "# not a heading",
+            "- not a list",
+            "2. nor this",
+            "> nor a quote",
+        ];
+        let doc = json!({ "type": "doc", "content": [paragraph(&lines)] });
+        let md = to_markdown(&doc);
+        assert_eq!(
+            md,
+            "\\# not a heading\\\n\\- not a list\\\n2\\. nor this\\\n\\> nor a quote\n"
+        );
+        assert_eq!(from_markdown(&md), vec![paragraph(&lines)]);
+
+        let table = json!({ "type": "doc", "content": [{ "type": "table", "content": [
+            { "type": "tableRow", "content": [
+                { "type": "tableCell", "content": [paragraph(&["one", "two\\"])] }
+            ]}
+        ]}]});
+        assert_eq!(to_markdown(&table), "|  |\n| --- |\n| one<br>two\\\\ |\n");
+    }
+
+    #[test]
+    fn test_from_markdown_structure() {
+        let nodes = from_markdown(
+            "- item\n  - nested\n\n* [ ] task\n\ntext ![img](https://x/i.png) more\n\n```\ncode\n```\n",
+        );
+
+        // Tight list items wrap their text in a paragraph and keep nested lists as siblings
+        assert_eq!(nodes[0]["type"], "bulletList");
+        let item = &nodes[0]["content"][0];
+        assert_eq!(item["content"][0]["type"], "paragraph");
+        assert_eq!(item["content"][0]["content"][0]["text"], "item");
+        assert_eq!(item["content"][1]["type"], "bulletList");
+
+        assert_eq!(nodes[1]["type"], "taskList");
+        assert!(nodes[1]["attrs"]["localId"].is_string());
+        assert_eq!(nodes[1]["content"][0]["type"], "taskItem");
+        assert_eq!(nodes[1]["content"][0]["attrs"]["state"], "TODO");
+        assert_eq!(nodes[1]["content"][0]["content"][0]["text"], "task");
+
+        // Images are split out of the paragraph into a mediaSingle block
+        assert_eq!(nodes[2]["type"], "paragraph");
+        assert_eq!(nodes[3]["type"], "mediaSingle");
+        assert_eq!(nodes[3]["content"][0]["attrs"]["url"], "https://x/i.png");
+        assert_eq!(nodes[3]["content"][0]["attrs"]["alt"], "img");
+        assert_eq!(nodes[4]["type"], "paragraph");
+
+        assert_eq!(nodes[5]["type"], "codeBlock");
+        assert!(nodes[5].get("attrs").is_none());
+        assert_eq!(nodes[5]["content"][0]["text"], "code");
+    }
+
+    #[test]
+    fn test_from_text() {
+        let doc = from_text("Steps:\n\n- one\n- two\n", false);
+        assert_eq!(doc["type"], "doc");
+        assert_eq!(doc["content"][0]["type"], "paragraph");
+        assert_eq!(doc["content"][1]["type"], "bulletList");
+
+        let doc = from_text("at foo()\n  at *bar*()\n", true);
+        let inline = doc["content"][0]["content"].as_array().unwrap();
+        assert_eq!(inline.len(), 3);
+        assert_eq!(inline[1]["type"], "hardBreak");
+        assert_eq!(inline[2]["text"], "  at *bar*()");
+    }
 }
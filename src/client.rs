@@ -1,24 +1,43 @@
 use crate::adf;
-use crate::config::Config;
+use crate::config::{AuthMode, Config, Deployment};
+use crate::error::{ApiError, Error};
+use crate::markup;
+use crate::oauth::{self, OAuthSettings, Session};
+use crate::progress::Progress;
+use crate::retry::{self, RetryPolicy};
 use base64::{engine::general_purpose, Engine as _};
 use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::path::Path;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
 
 /// API types supported by the Atlassian client.
 pub enum AtlassianApi {
     Jira,
     Confluence,
     ConfluenceV1,
+    /// Jira Software boards and sprints.
+    Agile,
 }
 
-/// Client for interacting with Atlassian Cloud REST APIs (Jira and Confluence).
+/// Client for interacting with Atlassian REST APIs (Jira and Confluence), on Cloud or Data Center.
 pub struct AtlassianClient {
     client: reqwest::Client,
     config: Config,
     field_cache: std::sync::Mutex<Option<FieldLookup>>,
+    oauth: Option<OAuthState>,
+}
+
+/// The OAuth session of a client in `oauth` auth mode.
+struct OAuthState {
+    settings: OAuthSettings,
+    session: std::sync::Mutex<Option<Session>>,
+    /// Serializes refreshes, so concurrent requests that hit an expired token refresh it once.
+    refresh_lock: tokio::sync::Mutex<()>,
 }
 
 /// Metadata lookup table for Jira fields.
@@ -28,15 +47,52 @@ pub struct FieldLookup {
     pub id_to_name: HashMap<String, String>,
     /// Map of lowercase human-readable name to Jira internal field ID.
     pub name_to_id: HashMap<String, String>,
+    /// Map of Jira internal field ID to its `schema` object from `/field`.
+    pub schemas: HashMap<String, Value>,
+}
+
+impl FieldLookup {
+    /// Resolve a field given by ID or (case-insensitive) name to its ID.
+    ///
+    /// Several custom fields may share a name; that is an error listing their IDs.
+    pub fn resolve(&self, name: &str) -> Result<Option<&str>, Error> {
+        if let Some((id, _)) = self.id_to_name.get_key_value(name) {
+            return Ok(Some(id));
+        }
+        let lower = name.to_lowercase();
+        let mut ids: Vec<&str> = self
+            .id_to_name
+            .iter()
+            .filter(|(_, field)| field.to_lowercase() == lower)
+            .map(|(id, _)| id.as_str())
+            .collect();
+        if ids.len() > 1 {
+            ids.sort_unstable();
+            return Err(format!(
+                "Field name '{}' matches several fields ({}); pass the field ID instead",
+                name,
+                ids.join(", ")
+            )
+            .into());
+        }
+        Ok(ids.pop())
+    }
 }
 
 impl AtlassianClient {
     /// Create a new AtlassianClient with the provided configuration.
     pub fn new(config: Config) -> Self {
+        // A missing or unreadable session surfaces as "not logged in" on the first request
+        let oauth = config.oauth.clone().map(|settings| OAuthState {
+            session: std::sync::Mutex::new(Session::load(&settings.store).ok().flatten()),
+            settings,
+            refresh_lock: tokio::sync::Mutex::new(()),
+        });
         Self {
             client: reqwest::Client::new(),
             config,
             field_cache: std::sync::Mutex::new(None),
+            oauth,
         }
     }
 
@@ -44,10 +100,221 @@ impl AtlassianClient {
         &self.config
     }
 
+    /// Whether the site is a self-hosted Data Center (or Server) instance.
+    pub fn is_data_center(&self) -> bool {
+        self.config.deployment == Deployment::DataCenter
+    }
+
     /// Get current user information (Jira).
-    pub async fn myself(&self) -> Result<Value, String> {
-        self.request(AtlassianApi::Jira, reqwest::Method::GET, "/myself", None)
+    pub async fn myself(&self) -> Result<Value, Error> {
+        let user = self
+            .request(AtlassianApi::Jira, reqwest::Method::GET, "/myself", None)
+            .await?;
+        Ok(self.normalize_user(user))
+    }
+
+    /// Base path for each API flavor, relative to the site URL.
+    ///
+    /// Data Center has neither Jira API v3 nor Confluence API v2, and serves Confluence
+    /// without the `/wiki` context.
+    fn api_prefix(&self, api: &AtlassianApi) -> &'static str {
+        match (self.config.deployment, api) {
+            (Deployment::Cloud, AtlassianApi::Jira) => "/rest/api/3",
+            (Deployment::Cloud, AtlassianApi::Confluence) => "/wiki/api/v2",
+            (Deployment::Cloud, AtlassianApi::ConfluenceV1) => "/wiki/rest/api",
+            (Deployment::DataCenter, AtlassianApi::Jira) => "/rest/api/2",
+            (Deployment::DataCenter, AtlassianApi::Confluence | AtlassianApi::ConfluenceV1) => {
+                "/rest/api"
+            }
+            (_, AtlassianApi::Agile) => "/rest/agile/1.0",
+        }
+    }
+
+    /// Site serving an API: `confluence_site` for Confluence when configured, else `site`.
+    /// OAuth requests go through the API gateway instead, addressed by cloud ID.
+    fn base_url(&self, api: &AtlassianApi) -> String {
+        if let Some(state) = &self.oauth {
+            if let Some(session) = state.session.lock().unwrap().as_ref() {
+                let product = match api {
+                    AtlassianApi::Confluence | AtlassianApi::ConfluenceV1 => "confluence",
+                    AtlassianApi::Jira | AtlassianApi::Agile => "jira",
+                };
+                return format!(
+                    "{}/ex/{}/{}",
+                    state.settings.api_url.trim_end_matches('/'),
+                    product,
+                    session.cloud_id
+                );
+            }
+        }
+        match (api, &self.config.confluence_site) {
+            (AtlassianApi::Confluence | AtlassianApi::ConfluenceV1, Some(site)) => site.clone(),
+            _ => self.config.site.clone(),
+        }
+    }
+
+    fn url(&self, api: &AtlassianApi, path: &str) -> String {
+        format!(
+            "{}{}{}",
+            self.base_url(api).trim_end_matches('/'),
+            self.api_prefix(api),
+            path
+        )
+    }
+
+    /// Build the auth header shared by every request: Basic (email and API token), or
+    /// Bearer with a personal access token or the current OAuth access token.
+    fn auth_headers(&self) -> Result<HeaderMap, Error> {
+        let mut headers = HeaderMap::new();
+        let value = match self.config.auth_mode {
+            AuthMode::Basic => {
+                let auth = format!("{}:{}", self.config.user, self.config.token);
+                format!("Basic {}", general_purpose::STANDARD.encode(auth))
+            }
+            AuthMode::Bearer => format!("Bearer {}", self.config.token),
+            AuthMode::OAuth => {
+                let session = self.oauth.as_ref().and_then(|s| {
+                    s.session
+                        .lock()
+                        .unwrap()
+                        .as_ref()
+                        .map(|s| s.access_token.clone())
+                });
+                let token = session.ok_or("Not logged in. Run `jiri auth login` first")?;
+                format!("Bearer {}", token)
+            }
+        };
+        let value = HeaderValue::from_str(&value).map_err(|_| "The token is not a valid header")?;
+        headers.insert(AUTHORIZATION, value);
+        Ok(headers)
+    }
+
+    /// Refresh the OAuth access token. With `stale`, only if that header is still the
+    /// current one (a concurrent request may have refreshed already); without, only if
+    /// the token has expired. The rotated tokens are written back to the session store.
+    async fn refresh_oauth(&self, stale: Option<&HeaderValue>) -> Result<(), Error> {
+        let Some(state) = &self.oauth else {
+            return Ok(());
+        };
+        let _guard = state.refresh_lock.lock().await;
+        let current = state.session.lock().unwrap().clone();
+        let mut session = current.ok_or("Not logged in. Run `jiri auth login` first")?;
+        let needed = match stale {
+            Some(stale) => {
+                stale.to_str().ok() == Some(format!("Bearer {}", session.access_token).as_str())
+            }
+            None => session.is_expired(),
+        };
+        if !needed {
+            return Ok(());
+        }
+
+        let refresh_token = session.refresh_token.clone().ok_or(
+            "The OAuth session has expired and has no refresh token. Run `jiri auth login` again",
+        )?;
+        if std::env::var("JIRI_VERBOSE").is_ok() {
+            eprintln!("DEBUG: Refreshing the OAuth access token");
+        }
+        let tokens = oauth::refresh(&self.client, &state.settings, &refresh_token)
             .await
+            .map_err(|e| {
+                format!(
+                    "Failed to refresh the OAuth session ({}). Run `jiri auth login` again",
+                    e
+                )
+            })?;
+        session.update(tokens);
+        session.save(&state.settings.store)?;
+        *state.session.lock().unwrap() = Some(session);
+        Ok(())
+    }
+
+    /// Data Center users have a `name` instead of an `accountId`. Copy it across so callers
+    /// can treat both alike; `to_server_body` maps it back on the way out.
+    fn normalize_user(&self, mut user: Value) -> Value {
+        if self.is_data_center() && user["accountId"].is_null() {
+            if let Some(name) = user.get("name").cloned() {
+                user["accountId"] = name;
+            }
+        }
+        user
+    }
+
+    /// Send a request, retrying 429/503 responses according to the configured `RetryPolicy`.
+    ///
+    /// `build` is called once per attempt because request bodies cannot always be reused;
+    /// the auth header is added here. With OAuth, an expired access token is refreshed before
+    /// sending and a 401 is retried once after refreshing.
+    /// The last response is returned as-is once retries are exhausted, so callers keep
+    /// handling non-success statuses themselves.
+    async fn send_with_retry<F>(
+        &self,
+        method: &reqwest::Method,
+        url: &str,
+        build: F,
+    ) -> Result<reqwest::Response, Error>
+    where
+        F: Fn() -> Result<reqwest::RequestBuilder, Error>,
+    {
+        let policy = &self.config.retry;
+        let mut attempt = 0;
+        let mut waited = Duration::ZERO;
+        let mut refreshed = false;
+        self.refresh_oauth(None).await?;
+
+        loop {
+            let auth = self.auth_headers()?;
+            let used = auth.get(AUTHORIZATION).cloned();
+            let response = build()?
+                .headers(auth)
+                .send()
+                .await
+                .map_err(|e| Error::transport(method, url, e))?;
+
+            if std::env::var("JIRI_VERBOSE").is_ok() {
+                eprintln!("DEBUG: Response: {}", response.status());
+            }
+
+            let status = response.status();
+            if status == reqwest::StatusCode::UNAUTHORIZED && self.oauth.is_some() && !refreshed {
+                refreshed = true;
+                self.refresh_oauth(used.as_ref()).await?;
+                continue;
+            }
+            if !RetryPolicy::is_retryable(status) {
+                return Ok(response);
+            }
+
+            let retry_after = retry::retry_after(response.headers());
+            match policy.next_delay(attempt, retry_after, waited) {
+                Some(delay) => {
+                    if std::env::var("JIRI_VERBOSE").is_ok() {
+                        eprintln!(
+                            "DEBUG: {} received, retrying in {:?} (retry {}/{})",
+                            status,
+                            delay,
+                            attempt + 1,
+                            policy.max_retries
+                        );
+                    }
+                    tokio::time::sleep(delay).await;
+                    waited += delay;
+                    attempt += 1;
+                }
+                None => return Ok(response),
+            }
+        }
+    }
+
+    /// Turn a non-success response into an `ApiError`.
+    async fn error_for_status(
+        method: &reqwest::Method,
+        url: &str,
+        response: reqwest::Response,
+    ) -> Error {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        ApiError::new(status, method.clone(), url.to_string(), text).into()
     }
 
     /// Perform a generic authenticated request to the Atlassian API.
@@ -57,15 +324,13 @@ impl AtlassianClient {
         method: reqwest::Method,
         path: &str,
         body: Option<Value>,
-    ) -> Result<Value, String> {
-        let prefix = match api {
-            AtlassianApi::Jira => "/rest/api/3",
-            AtlassianApi::Confluence => "/wiki/api/v2",
-            AtlassianApi::ConfluenceV1 => "/wiki/rest/api",
+    ) -> Result<Value, Error> {
+        let url = self.url(&api, path);
+        let body = match api {
+            AtlassianApi::Jira if self.is_data_center() => body.map(to_server_body),
+            _ => body,
         };
 
-        let url = format!("{}{}{}", self.config.site, prefix, path);
-
         if std::env::var("JIRI_VERBOSE").is_ok() {
             eprintln!("DEBUG: {} {}", method, url);
             if let Some(ref b) = body {
@@ -77,45 +342,40 @@ impl AtlassianClient {
         }
 
         let mut headers = HeaderMap::new();
-
-        let auth = format!("{}:{}", self.config.user, self.config.token);
-        let encoded_auth = general_purpose::STANDARD.encode(auth);
-
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Basic {}", encoded_auth)).unwrap(),
-        );
         headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
 
         if body.is_some() {
             headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
         }
 
-        let mut request_builder = self.client.request(method, &url).headers(headers);
-        if let Some(b) = body {
-            request_builder = request_builder.json(&b);
-        }
-
-        let response = request_builder.send().await.map_err(|e| e.to_string())?;
-
-        if std::env::var("JIRI_VERBOSE").is_ok() {
-            eprintln!("DEBUG: Response: {}", response.status());
-        }
+        let response = self
+            .send_with_retry(&method, &url, || {
+                let mut request_builder = self
+                    .client
+                    .request(method.clone(), &url)
+                    .headers(headers.clone());
+                if let Some(ref b) = body {
+                    request_builder = request_builder.json(b);
+                }
+                Ok(request_builder)
+            })
+            .await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("Atlassian request failed ({}): {}", status, text));
+            return Err(Self::error_for_status(&method, &url, response).await);
         }
 
-        if response.status() == reqwest::StatusCode::NO_CONTENT {
+        // Some endpoints (e.g. 201 from POST /issueLink) succeed with an empty body.
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| Error::transport(&method, &url, e))?;
+        if bytes.iter().all(|b| b.is_ascii_whitespace()) {
             return Ok(Value::Null);
         }
 
-        let json: Value = response.json().await.map_err(|e| e.to_string())?;
+        let json: Value =
+            serde_json::from_slice(&bytes).map_err(|e| Error::transport(&method, &url, e))?;
         if std::env::var("JIRI_VERBOSE").is_ok() {
             eprintln!(
                 "DEBUG: JSON: {}",
@@ -125,91 +385,118 @@ impl AtlassianClient {
         Ok(json)
     }
 
-    /// Perform a multipart upload request to the Atlassian API.
+    /// Perform a multipart upload of one or more files to the Atlassian API.
+    ///
+    /// File bodies are streamed from disk rather than read into memory, and a progress bar
+    /// is shown on stderr when it is a terminal.
     async fn request_multipart(
         &self,
         api: AtlassianApi,
         path: &str,
-        file_path: &str,
+        files: &[PathBuf],
         comment: Option<String>,
-    ) -> Result<Value, String> {
-        let prefix = match api {
-            AtlassianApi::Jira => "/rest/api/3",
-            AtlassianApi::Confluence => "/wiki/api/v2",
-            AtlassianApi::ConfluenceV1 => "/wiki/rest/api",
-        };
-
-        let url = format!("{}{}{}", self.config.site, prefix, path);
+    ) -> Result<Value, Error> {
+        let url = self.url(&api, path);
 
         if std::env::var("JIRI_VERBOSE").is_ok() {
             eprintln!("DEBUG: POST (multipart) {}", url);
-            eprintln!("DEBUG: File: {}", file_path);
+            for file in files {
+                eprintln!("DEBUG: File: {}", file.display());
+            }
         }
 
-        let file_name = Path::new(file_path)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .ok_or_else(|| format!("Invalid file path: {}", file_path))?
-            .to_string();
-
-        let file_content = tokio::fs::read(file_path)
-            .await
-            .map_err(|e| format!("Failed to read file {}: {}", file_path, e))?;
-
-        let mime = mime_guess::from_path(file_path)
-            .first_raw()
-            .unwrap_or("application/octet-stream");
-
-        let part = reqwest::multipart::Part::bytes(file_content)
-            .file_name(file_name)
-            .mime_str(mime)
-            .map_err(|e| e.to_string())?;
-
-        let mut form = reqwest::multipart::Form::new().part("file", part);
-
-        // Add comment only for Confluence v2 attachments
-        if let (AtlassianApi::Confluence, Some(c)) = (&api, comment) {
-            form = form.text("comment", c);
+        let mut uploads = Vec::new();
+        for file in files {
+            let file_name = file
+                .file_name()
+                .and_then(|n| n.to_str())
+                .ok_or_else(|| format!("Invalid file path: {}", file.display()))?
+                .to_string();
+            let metadata = tokio::fs::metadata(file).await.map_err(|e| {
+                Error::Other(format!("Failed to read file {}: {}", file.display(), e))
+            })?;
+            if !metadata.is_file() {
+                return Err(format!("Not a file: {}", file.display()).into());
+            }
+            let mime = mime_guess::from_path(file)
+                .first_raw()
+                .unwrap_or("application/octet-stream");
+            uploads.push((file.clone(), file_name, metadata.len(), mime));
         }
 
-        let mut headers = HeaderMap::new();
-        let auth = format!("{}:{}", self.config.user, self.config.token);
-        let encoded_auth = general_purpose::STANDARD.encode(auth);
+        // Add comment only for Confluence attachments
+        let comment = match api {
+            AtlassianApi::Confluence | AtlassianApi::ConfluenceV1 => comment,
+            _ => None,
+        };
 
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Basic {}", encoded_auth)).unwrap(),
-        );
+        let mut headers = HeaderMap::new();
         headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
         headers.insert("X-Atlassian-Token", HeaderValue::from_static("no-check"));
 
-        let response = self.client
-            .post(&url)
-            .headers(headers)
-            .multipart(form)
-            .send()
-            .await
-            .map_err(|e| e.to_string())?;
+        let total = uploads.iter().map(|(_, _, len, _)| len).sum();
+        let label = match uploads.as_slice() {
+            [(_, name, _, _)] => format!("Uploading {}", name),
+            _ => format!("Uploading {} files", uploads.len()),
+        };
+        let progress = Arc::new(Progress::new(label, total));
+
+        // A multipart form is consumed when sent, so it is rebuilt (and the files reopened)
+        // for every attempt.
+        let method = reqwest::Method::POST;
+        let response = self
+            .send_with_retry(&method, &url, || {
+                progress.reset();
+                let mut form = reqwest::multipart::Form::new();
+                for (file, file_name, len, mime) in &uploads {
+                    let handle = std::fs::File::open(file).map_err(|e| {
+                        Error::Other(format!("Failed to read file {}: {}", file.display(), e))
+                    })?;
+                    let body = reqwest::Body::wrap_stream(file_stream(
+                        tokio::fs::File::from_std(handle),
+                        progress.clone(),
+                    ));
+                    let part = reqwest::multipart::Part::stream_with_length(body, *len)
+                        .file_name(file_name.clone())
+                        .mime_str(mime)
+                        .map_err(|e| Error::Other(e.to_string()))?;
+                    form = form.part("file", part);
+                }
+                if let Some(ref c) = comment {
+                    form = form.text("comment", c.clone());
+                }
 
-        if std::env::var("JIRI_VERBOSE").is_ok() {
-            eprintln!("DEBUG: Response: {}", response.status());
-        }
+                Ok(self
+                    .client
+                    .post(&url)
+                    .headers(headers.clone())
+                    .multipart(form))
+            })
+            .await;
+        progress.finish();
+        let response = response?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("Atlassian request failed ({}): {}", status, text));
+            return Err(Self::error_for_status(&method, &url, response).await);
         }
 
-        let json: Value = response.json().await.map_err(|e| e.to_string())?;
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::transport(&method, &url, e))?;
         Ok(json)
     }
 
     /// List all projects visible to the user, fetching every page.
-    pub async fn projects_all(&self) -> Result<Vec<Value>, String> {
+    pub async fn projects_all(&self) -> Result<Vec<Value>, Error> {
+        if self.is_data_center() {
+            // Data Center has no paginated project search; `/project` returns them all.
+            let data = self
+                .request(AtlassianApi::Jira, reqwest::Method::GET, "/project", None)
+                .await?;
+            return Ok(data.as_array().cloned().unwrap_or_default());
+        }
+
         let page_size = 100;
         let mut start_at = 0;
         let mut projects = Vec::new();
@@ -238,14 +525,97 @@ impl AtlassianClient {
         Ok(projects)
     }
 
+    /// List the issue types that can be created in a project (createmeta), fetching every page.
+    pub async fn create_meta_issue_types(&self, project_key: &str) -> Result<Vec<Value>, Error> {
+        let path = format!("/issue/createmeta/{}/issuetypes", project_key);
+        self.create_meta_pages(&path, "issueTypes").await
+    }
+
+    /// List the fields (with `required`, `schema` and `allowedValues`) for creating an issue of
+    /// the given type in a project, fetching every page.
+    pub async fn create_meta_fields(
+        &self,
+        project_key: &str,
+        issue_type_id: &str,
+    ) -> Result<Vec<Value>, Error> {
+        let path = format!(
+            "/issue/createmeta/{}/issuetypes/{}",
+            project_key, issue_type_id
+        );
+        self.create_meta_pages(&path, "fields").await
+    }
+
+    async fn create_meta_pages(&self, path: &str, key: &str) -> Result<Vec<Value>, Error> {
+        let page_size = 50;
+        let mut start_at = 0;
+        let mut items = Vec::new();
+
+        loop {
+            let page_path = format!("{}?startAt={}&maxResults={}", path, start_at, page_size);
+            let data = self
+                .request(AtlassianApi::Jira, reqwest::Method::GET, &page_path, None)
+                .await?;
+
+            // Older responses use "values" instead of "issueTypes"/"fields".
+            let page = data[key]
+                .as_array()
+                .or_else(|| data["values"].as_array())
+                .cloned()
+                .unwrap_or_default();
+            let returned = page.len() as i64;
+            items.extend(page);
+
+            let total = data["total"].as_i64().unwrap_or(items.len() as i64);
+            start_at += returned;
+
+            if returned == 0 || start_at >= total {
+                break;
+            }
+        }
+
+        Ok(items)
+    }
+
     /// Perform a JQL search.
+    ///
+    /// Data Center pages by offset rather than token, so there the offset is passed through
+    /// `nextPageToken` to keep callers deployment-agnostic.
     pub async fn search(
         &self,
         jql: &str,
         fields: Vec<String>,
         max_results: i64,
         next_page_token: Option<String>,
-    ) -> Result<Value, String> {
+    ) -> Result<Value, Error> {
+        if self.is_data_center() {
+            let start_at: i64 = next_page_token
+                .as_deref()
+                .map(|t| t.parse())
+                .transpose()
+                .map_err(|_| "Invalid page token")?
+                .unwrap_or(0);
+            let body = serde_json::json!({
+                "jql": jql,
+                "fields": fields,
+                "maxResults": max_results,
+                "startAt": start_at,
+            });
+            let mut data = self
+                .request(
+                    AtlassianApi::Jira,
+                    reqwest::Method::POST,
+                    "/search",
+                    Some(body),
+                )
+                .await?;
+            let returned = data["issues"].as_array().map_or(0, |a| a.len()) as i64;
+            let total = data["total"].as_i64().unwrap_or(0);
+            if returned > 0 && start_at + returned < total {
+                data["nextPageToken"] = Value::String((start_at + returned).to_string());
+            }
+            return Ok(data);
+        }
+
         let mut body = serde_json::json!({
             "jql": jql,
             "fields": fields,
@@ -273,7 +643,7 @@ impl AtlassianClient {
         jql: &str,
         fields: Vec<String>,
         limit: i64,
-    ) -> Result<(Vec<Value>, bool), String> {
+    ) -> Result<(Vec<Value>, bool), Error> {
         let page_size = 100;
         let mut issues = Vec::new();
         let mut next_page_token: Option<String> = None;
@@ -306,7 +676,7 @@ impl AtlassianClient {
     }
 
     /// Fetch field definitions and build a lookup table. Caches the result.
-    pub async fn field_lookup(&self) -> Result<FieldLookup, String> {
+    pub async fn field_lookup(&self) -> Result<FieldLookup, Error> {
         {
             let cache = self.field_cache.lock().unwrap();
             if let Some(ref lookup) = *cache {
@@ -319,12 +689,16 @@ impl AtlassianClient {
             .await?;
         let mut id_to_name = HashMap::new();
         let mut name_to_id = HashMap::new();
+        let mut schemas = HashMap::new();
 
         if let Some(fields) = data.as_array() {
             for f in fields {
                 let id = f["id"].as_str().unwrap_or_default().to_string();
                 let name = f["name"].as_str().unwrap_or_default().to_string();
                 if !id.is_empty() && !name.is_empty() {
+                    if f["schema"].is_object() {
+                        schemas.insert(id.clone(), f["schema"].clone());
+                    }
                     id_to_name.insert(id.clone(), name.clone());
                     name_to_id.insert(name.to_lowercase(), id);
                 }
@@ -334,6 +708,7 @@ impl AtlassianClient {
         let lookup = FieldLookup {
             id_to_name,
             name_to_id,
+            schemas,
         };
         let mut cache = self.field_cache.lock().unwrap();
         *cache = Some(lookup.clone());
@@ -341,36 +716,179 @@ impl AtlassianClient {
     }
 
     /// Get a single issue by key.
-    pub async fn get_issue(&self, key: &str) -> Result<Value, String> {
+    pub async fn get_issue(&self, key: &str) -> Result<Value, Error> {
         let path = format!("/issue/{}", key);
         self.request(AtlassianApi::Jira, reqwest::Method::GET, &path, None)
             .await
     }
 
-    /// List available transitions for an issue.
-    pub async fn get_transitions(&self, key: &str) -> Result<Value, String> {
-        let path = format!("/issue/{}/transitions", key);
+    /// List the change history of an issue, oldest first, fetching every page.
+    pub async fn get_changelog(&self, key: &str) -> Result<Vec<Value>, Error> {
+        if self.is_data_center() {
+            // Data Center has no changelog endpoint; the history comes with the issue.
+            let path = format!("/issue/{}?expand=changelog&fields=created", key);
+            let issue = self
+                .request(AtlassianApi::Jira, reqwest::Method::GET, &path, None)
+                .await?;
+            return Ok(issue["changelog"]["histories"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default());
+        }
+
+        let page_size = 100;
+        let mut start_at = 0;
+        let mut histories = Vec::new();
+
+        loop {
+            let path = format!(
+                "/issue/{}/changelog?startAt={}&maxResults={}",
+                key, start_at, page_size
+            );
+            let data = self
+                .request(AtlassianApi::Jira, reqwest::Method::GET, &path, None)
+                .await?;
+
+            let page = data["values"].as_array().cloned().unwrap_or_default();
+            let returned = page.len() as i64;
+            histories.extend(page);
+
+            let total = data["total"].as_i64().unwrap_or(histories.len() as i64);
+            start_at += returned;
+
+            if returned == 0 || start_at >= total {
+                break;
+            }
+        }
+
+        Ok(histories)
+    }
+
+    /// List available transitions for an issue, including the fields on each transition screen.
+    pub async fn get_transitions(&self, key: &str) -> Result<Value, Error> {
+        let path = format!("/issue/{}/transitions?expand=transitions.fields", key);
         self.request(AtlassianApi::Jira, reqwest::Method::GET, &path, None)
             .await
     }
 
-    /// Perform a transition on an issue.
-    pub async fn do_transition(&self, key: &str, transition_id: &str) -> Result<Value, String> {
+    /// Perform a transition on an issue, setting screen `fields` and applying `update`
+    /// operations (e.g. adding a comment) when given.
+    pub async fn do_transition(
+        &self,
+        key: &str,
+        transition_id: &str,
+        fields: serde_json::Map<String, Value>,
+        update: Option<Value>,
+    ) -> Result<Value, Error> {
         let path = format!("/issue/{}/transitions", key);
-        let body = serde_json::json!({
+        let mut body = serde_json::json!({
             "transition": { "id": transition_id }
         });
+        if !fields.is_empty() {
+            body["fields"] = Value::Object(fields);
+        }
+        if let Some(update) = update {
+            body["update"] = update;
+        }
         self.request(AtlassianApi::Jira, reqwest::Method::POST, &path, Some(body))
             .await
     }
 
-    /// Add a comment to an issue.
-    pub async fn add_comment(&self, key: &str, body_text: &str) -> Result<Value, String> {
+    /// The workflow scheme used by a project (requires project or Jira admin permission).
+    pub async fn project_workflow_scheme(&self, project_id: &str) -> Result<Value, Error> {
+        let path = format!("/workflowscheme/project?projectId={}", project_id);
+        let data = self
+            .request(AtlassianApi::Jira, reqwest::Method::GET, &path, None)
+            .await?;
+        Ok(data["values"][0]["workflowScheme"].clone())
+    }
+
+    /// A workflow with its statuses and transitions (requires Jira admin permission).
+    pub async fn get_workflow(&self, name: &str) -> Result<Value, Error> {
+        let path = format!(
+            "/workflow/search?workflowName={}&expand=transitions,statuses",
+            urlencoding::encode(name)
+        );
+        let data = self
+            .request(AtlassianApi::Jira, reqwest::Method::GET, &path, None)
+            .await?;
+        data["values"]
+            .get(0)
+            .cloned()
+            .ok_or_else(|| format!("Workflow '{}' not found", name).into())
+    }
+
+    /// Add a comment to an issue. `comment` holds the ADF `body` and optionally `visibility`
+    /// and `properties`.
+    pub async fn add_comment(&self, key: &str, comment: Value) -> Result<Value, Error> {
         let path = format!("/issue/{}/comment", key);
-        let body = serde_json::json!({
-            "body": adf::from_plain_text(body_text)
-        });
-        self.request(AtlassianApi::Jira, reqwest::Method::POST, &path, Some(body))
+        self.request(
+            AtlassianApi::Jira,
+            reqwest::Method::POST,
+            &path,
+            Some(comment),
+        )
+        .await
+    }
+
+    /// List all comments of an issue, oldest first, fetching every page.
+    pub async fn get_comments(&self, key: &str) -> Result<Vec<Value>, Error> {
+        let page_size = 100;
+        let mut start_at = 0;
+        let mut comments = Vec::new();
+
+        loop {
+            let path = format!(
+                "/issue/{}/comment?startAt={}&maxResults={}&orderBy=created&expand=properties",
+                key, start_at, page_size
+            );
+            let data = self
+                .request(AtlassianApi::Jira, reqwest::Method::GET, &path, None)
+                .await?;
+
+            let page = data["comments"].as_array().cloned().unwrap_or_default();
+            let returned = page.len() as i64;
+            comments.extend(page);
+
+            let total = data["total"].as_i64().unwrap_or(comments.len() as i64);
+            start_at += returned;
+
+            if returned == 0 || start_at >= total {
+                break;
+            }
+        }
+
+        Ok(comments)
+    }
+
+    /// Get a single comment.
+    pub async fn get_comment(&self, key: &str, comment_id: &str) -> Result<Value, Error> {
+        let path = format!("/issue/{}/comment/{}", key, comment_id);
+        self.request(AtlassianApi::Jira, reqwest::Method::GET, &path, None)
+            .await
+    }
+
+    /// Replace a comment's body (and visibility/properties, when given).
+    pub async fn update_comment(
+        &self,
+        key: &str,
+        comment_id: &str,
+        comment: Value,
+    ) -> Result<Value, Error> {
+        let path = format!("/issue/{}/comment/{}", key, comment_id);
+        self.request(
+            AtlassianApi::Jira,
+            reqwest::Method::PUT,
+            &path,
+            Some(comment),
+        )
+        .await
+    }
+
+    /// Delete a comment.
+    pub async fn delete_comment(&self, key: &str, comment_id: &str) -> Result<Value, Error> {
+        let path = format!("/issue/{}/comment/{}", key, comment_id);
+        self.request(AtlassianApi::Jira, reqwest::Method::DELETE, &path, None)
             .await
     }
 
@@ -380,7 +898,7 @@ impl AtlassianClient {
         key: &str,
         body_text: &str,
         url: &str,
-    ) -> Result<Value, String> {
+    ) -> Result<Value, Error> {
         let path = format!("/issue/{}/comment", key);
         let body = serde_json::json!({
             "body": adf::from_plain_text_with_external_media(body_text, url)
@@ -395,7 +913,7 @@ impl AtlassianClient {
         key: &str,
         body_text: &str,
         media_id: &str,
-    ) -> Result<Value, String> {
+    ) -> Result<Value, Error> {
         let path = format!("/issue/{}/comment", key);
         let body = serde_json::json!({
             "body": adf::from_plain_text_with_attachment(body_text, media_id)
@@ -406,29 +924,23 @@ impl AtlassianClient {
 
     /// Retrieve the Media Services UUID for a given numeric attachment ID.
     /// This follows the redirect of the attachment content URL.
-    pub async fn get_attachment_media_id(&self, attachment_id: &str) -> Result<String, String> {
-        let url = format!("{}/rest/api/3/attachment/content/{}", self.config.site, attachment_id);
-
-        let mut headers = HeaderMap::new();
-        let auth = format!("{}:{}", self.config.user, self.config.token);
-        let encoded_auth = general_purpose::STANDARD.encode(auth);
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Basic {}", encoded_auth)).unwrap(),
+    pub async fn get_attachment_media_id(&self, attachment_id: &str) -> Result<String, Error> {
+        let url = self.url(
+            &AtlassianApi::Jira,
+            &format!("/attachment/content/{}", attachment_id),
         );
 
         // We use a separate client that doesn't automatically follow redirects so we can see the Location header.
         let no_redirect_client = reqwest::Client::builder()
             .redirect(reqwest::redirect::Policy::none())
             .build()
-            .map_err(|e| e.to_string())?;
+            .map_err(|e| Error::Other(e.to_string()))?;
 
-        let response = no_redirect_client
-            .get(&url)
-            .headers(headers)
-            .send()
-            .await
-            .map_err(|e| e.to_string())?;
+        let response = self
+            .send_with_retry(&reqwest::Method::GET, &url, || {
+                Ok(no_redirect_client.get(&url))
+            })
+            .await?;
 
         let location = response
             .headers()
@@ -439,39 +951,112 @@ impl AtlassianClient {
         // The URL typically looks like https://.../file/<UUID>/binary?...
         let parts: Vec<&str> = location.split("/file/").collect();
         if parts.len() < 2 {
-            return Err(format!("Could not find Media UUID in redirect URL: {}", location));
+            return Err(format!("Could not find Media UUID in redirect URL: {}", location).into());
         }
-        let uuid_part = parts[1].split('/').next().ok_or("Malformed Media UUID path")?;
-        
+        let uuid_part = parts[1]
+            .split('/')
+            .next()
+            .ok_or("Malformed Media UUID path")?;
+
         Ok(uuid_part.to_string())
     }
 
-    /// Add an attachment to a Jira issue.
-    pub async fn attach_to_issue(&self, key: &str, file_path: &str) -> Result<Value, String> {
+    /// Add one or more attachments to a Jira issue in a single request.
+    pub async fn attach_to_issue(&self, key: &str, files: &[PathBuf]) -> Result<Value, Error> {
         let path = format!("/issue/{}/attachments", key);
-        self.request_multipart(AtlassianApi::Jira, &path, file_path, None)
+        self.request_multipart(AtlassianApi::Jira, &path, files, None)
+            .await
+    }
+
+    /// List the attachments of an issue.
+    pub async fn get_issue_attachments(&self, key: &str) -> Result<Vec<Value>, Error> {
+        let path = format!("/issue/{}?fields=attachment", key);
+        let issue = self
+            .request(AtlassianApi::Jira, reqwest::Method::GET, &path, None)
+            .await?;
+        Ok(issue["fields"]["attachment"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    /// Delete an issue attachment by ID.
+    pub async fn delete_issue_attachment(&self, attachment_id: &str) -> Result<Value, Error> {
+        let path = format!("/attachment/{}", attachment_id);
+        self.request(AtlassianApi::Jira, reqwest::Method::DELETE, &path, None)
             .await
     }
 
-    /// Create a new issue in the specified project.
+    /// Stream an authenticated download (e.g. an attachment's content URL) to `dest`.
+    ///
+    /// The body is written to `<dest>.part` and renamed once complete, so an interrupted
+    /// download never leaves a truncated file under the real name. Returns the bytes written.
+    pub async fn download(&self, url: &str, dest: &Path) -> Result<u64, Error> {
+        use tokio::io::AsyncWriteExt;
+
+        if std::env::var("JIRI_VERBOSE").is_ok() {
+            eprintln!("DEBUG: GET (download) {}", url);
+        }
+
+        let method = reqwest::Method::GET;
+        let mut response = self
+            .send_with_retry(&method, url, || Ok(self.client.get(url)))
+            .await?;
+        if !response.status().is_success() {
+            return Err(Self::error_for_status(&method, url, response).await);
+        }
+
+        let mut partial = dest.as_os_str().to_owned();
+        partial.push(".part");
+        let partial = std::path::PathBuf::from(partial);
+        let io_error =
+            |e: std::io::Error| Error::Other(format!("Failed to write {}: {}", dest.display(), e));
+
+        let mut file = tokio::fs::File::create(&partial).await.map_err(io_error)?;
+        let mut written = 0;
+        let result: Result<(), Error> = async {
+            while let Some(chunk) = response
+                .chunk()
+                .await
+                .map_err(|e| Error::transport(&method, url, e))?
+            {
+                file.write_all(&chunk).await.map_err(io_error)?;
+                written += chunk.len() as u64;
+            }
+            file.flush().await.map_err(io_error)
+        }
+        .await;
+        drop(file);
+
+        if let Err(e) = result {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(e);
+        }
+        tokio::fs::rename(&partial, dest).await.map_err(io_error)?;
+        Ok(written)
+    }
+
+    /// Create a new issue in the specified project. `description` is an ADF document.
     pub async fn create_issue(
         &self,
         project_key: &str,
         summary: &str,
-        issue_type: &str,
-        description: Option<&str>,
-    ) -> Result<Value, String> {
+        issue_type_id: &str,
+        description: Option<Value>,
+        extra_fields: serde_json::Map<String, Value>,
+    ) -> Result<Value, Error> {
         let mut fields = serde_json::json!({
             "project": { "key": project_key },
             "summary": summary,
-            "issuetype": { "name": issue_type },
+            "issuetype": { "id": issue_type_id },
         });
+        fields.as_object_mut().unwrap().extend(extra_fields);
 
         if let Some(desc) = description {
             fields
                 .as_object_mut()
                 .unwrap()
-                .insert("description".to_string(), adf::from_plain_text(desc));
+                .insert("description".to_string(), desc);
         }
 
         let body = serde_json::json!({ "fields": fields });
@@ -484,21 +1069,266 @@ impl AtlassianClient {
         .await
     }
 
+    /// List all issue link types (e.g. Blocks, Relates, Duplicate, Cloners).
+    pub async fn issue_link_types(&self) -> Result<Vec<Value>, Error> {
+        let data = self
+            .request(
+                AtlassianApi::Jira,
+                reqwest::Method::GET,
+                "/issueLinkType",
+                None,
+            )
+            .await?;
+        Ok(data["issueLinkTypes"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    /// Get the links of an issue (the `issuelinks` field).
+    pub async fn get_issue_links(&self, key: &str) -> Result<Vec<Value>, Error> {
+        let path = format!("/issue/{}?fields=issuelinks", key);
+        let issue = self
+            .request(AtlassianApi::Jira, reqwest::Method::GET, &path, None)
+            .await?;
+        Ok(issue["fields"]["issuelinks"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    /// Create a link of the given type. Jira shows it as "`inward_key` <outward description>
+    /// `outward_key`", e.g. "PROJ-1 blocks PROJ-2".
+    pub async fn create_issue_link(
+        &self,
+        link_type: &str,
+        inward_key: &str,
+        outward_key: &str,
+    ) -> Result<Value, Error> {
+        let body = serde_json::json!({
+            "type": { "name": link_type },
+            "inwardIssue": { "key": inward_key },
+            "outwardIssue": { "key": outward_key },
+        });
+        self.request(
+            AtlassianApi::Jira,
+            reqwest::Method::POST,
+            "/issueLink",
+            Some(body),
+        )
+        .await
+    }
+
+    /// Delete an issue link by ID.
+    pub async fn delete_issue_link(&self, link_id: &str) -> Result<Value, Error> {
+        let path = format!("/issueLink/{}", link_id);
+        self.request(AtlassianApi::Jira, reqwest::Method::DELETE, &path, None)
+            .await
+    }
+
+    /// List all worklogs of an issue, fetching every page.
+    pub async fn get_worklogs(&self, key: &str) -> Result<Vec<Value>, Error> {
+        let page_size = 1000;
+        let mut start_at = 0;
+        let mut worklogs = Vec::new();
+
+        loop {
+            let path = format!(
+                "/issue/{}/worklog?startAt={}&maxResults={}",
+                key, start_at, page_size
+            );
+            let data = self
+                .request(AtlassianApi::Jira, reqwest::Method::GET, &path, None)
+                .await?;
+
+            let page = data["worklogs"].as_array().cloned().unwrap_or_default();
+            let returned = page.len() as i64;
+            worklogs.extend(page.into_iter().map(|mut w| {
+                w["author"] = self.normalize_user(w["author"].take());
+                w
+            }));
+
+            let total = data["total"].as_i64().unwrap_or(worklogs.len() as i64);
+            start_at += returned;
+
+            if returned == 0 || start_at >= total {
+                break;
+            }
+        }
+
+        Ok(worklogs)
+    }
+
+    /// Log work on an issue. `worklog` holds `timeSpentSeconds`, `started` and an optional ADF
+    /// `comment`; the remaining estimate is adjusted automatically.
+    pub async fn add_worklog(&self, key: &str, worklog: Value) -> Result<Value, Error> {
+        let path = format!("/issue/{}/worklog?adjustEstimate=auto", key);
+        self.request(
+            AtlassianApi::Jira,
+            reqwest::Method::POST,
+            &path,
+            Some(worklog),
+        )
+        .await
+    }
+
+    /// Update fields of an existing worklog.
+    pub async fn update_worklog(
+        &self,
+        key: &str,
+        worklog_id: &str,
+        worklog: Value,
+    ) -> Result<Value, Error> {
+        let path = format!("/issue/{}/worklog/{}?adjustEstimate=auto", key, worklog_id);
+        self.request(
+            AtlassianApi::Jira,
+            reqwest::Method::PUT,
+            &path,
+            Some(worklog),
+        )
+        .await
+    }
+
+    /// Delete a worklog.
+    pub async fn delete_worklog(&self, key: &str, worklog_id: &str) -> Result<Value, Error> {
+        let path = format!("/issue/{}/worklog/{}?adjustEstimate=auto", key, worklog_id);
+        self.request(AtlassianApi::Jira, reqwest::Method::DELETE, &path, None)
+            .await
+    }
+
+    /// Fetch every page of an Agile API collection (`values`/`issues` with `isLast`/`total`).
+    async fn agile_pages(&self, path: &str, key: &str) -> Result<Vec<Value>, Error> {
+        let page_size = 50;
+        let mut start_at = 0;
+        let mut items = Vec::new();
+        let separator = if path.contains('?') { '&' } else { '?' };
+
+        loop {
+            let page_path = format!(
+                "{}{}startAt={}&maxResults={}",
+                path, separator, start_at, page_size
+            );
+            let data = self
+                .request(AtlassianApi::Agile, reqwest::Method::GET, &page_path, None)
+                .await?;
+
+            let page = data[key].as_array().cloned().unwrap_or_default();
+            let returned = page.len() as i64;
+            items.extend(page);
+            start_at += returned;
+
+            let is_last = data["isLast"]
+                .as_bool()
+                .unwrap_or_else(|| start_at >= data["total"].as_i64().unwrap_or(0));
+            if returned == 0 || is_last {
+                break;
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// List boards, optionally only those of a project.
+    pub async fn boards_all(&self, project_key: Option<&str>) -> Result<Vec<Value>, Error> {
+        let path = match project_key {
+            Some(key) => format!("/board?projectKeyOrId={}", urlencoding::encode(key)),
+            None => "/board".to_string(),
+        };
+        self.agile_pages(&path, "values").await
+    }
+
+    /// List the sprints of a board, optionally filtered by state (`active,future,closed`).
+    pub async fn board_sprints(
+        &self,
+        board_id: &str,
+        state: Option<&str>,
+    ) -> Result<Vec<Value>, Error> {
+        let path = match state {
+            Some(state) => format!("/board/{}/sprint?state={}", board_id, state),
+            None => format!("/board/{}/sprint", board_id),
+        };
+        self.agile_pages(&path, "values").await
+    }
+
+    /// Get a sprint by ID.
+    pub async fn get_sprint(&self, sprint_id: &str) -> Result<Value, Error> {
+        let path = format!("/sprint/{}", sprint_id);
+        self.request(AtlassianApi::Agile, reqwest::Method::GET, &path, None)
+            .await
+    }
+
+    /// List the issues in a sprint with their summary, status and assignee.
+    pub async fn sprint_issues(&self, sprint_id: &str) -> Result<Vec<Value>, Error> {
+        let path = format!(
+            "/sprint/{}/issue?fields=summary,status,assignee,issuetype",
+            sprint_id
+        );
+        self.agile_pages(&path, "issues").await
+    }
+
+    /// Move issues into a sprint (at most 50 per request, so larger sets are chunked).
+    pub async fn move_issues_to_sprint(
+        &self,
+        sprint_id: &str,
+        keys: &[String],
+    ) -> Result<(), Error> {
+        let path = format!("/sprint/{}/issue", sprint_id);
+        for chunk in keys.chunks(50) {
+            let body = serde_json::json!({ "issues": chunk });
+            self.request(
+                AtlassianApi::Agile,
+                reqwest::Method::POST,
+                &path,
+                Some(body),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Partially update a sprint, e.g. to start (`state: active`) or close it.
+    pub async fn update_sprint(&self, sprint_id: &str, changes: Value) -> Result<Value, Error> {
+        let path = format!("/sprint/{}", sprint_id);
+        self.request(
+            AtlassianApi::Agile,
+            reqwest::Method::POST,
+            &path,
+            Some(changes),
+        )
+        .await
+    }
+
     /// Search Jira users by query string.
-    pub async fn search_users(&self, query: &str) -> Result<Value, String> {
+    pub async fn search_users(&self, query: &str) -> Result<Value, Error> {
+        if self.is_data_center() {
+            let path = format!("/user/search?username={}", urlencoding::encode(query));
+            let data = self
+                .request(AtlassianApi::Jira, reqwest::Method::GET, &path, None)
+                .await?;
+            let users = data.as_array().cloned().unwrap_or_default();
+            return Ok(Value::Array(
+                users.into_iter().map(|u| self.normalize_user(u)).collect(),
+            ));
+        }
+
         let path = format!("/user/search?query={}", urlencoding::encode(query));
         self.request(AtlassianApi::Jira, reqwest::Method::GET, &path, None)
             .await
     }
 
-    /// Update fields on an existing issue.
+    /// Update an existing issue. `fields` replaces field values; `update` holds per-field
+    /// operations such as `{"labels": [{"add": "urgent"}]}` for incremental changes.
     pub async fn update_issue(
         &self,
         key: &str,
         fields: serde_json::Value,
-    ) -> Result<Value, String> {
+        update: Option<Value>,
+    ) -> Result<Value, Error> {
         let path = format!("/issue/{}", key);
-        let body = serde_json::json!({ "fields": fields });
+        let mut body = serde_json::json!({ "fields": fields });
+        if let Some(update) = update {
+            body["update"] = update;
+        }
         self.request(AtlassianApi::Jira, reqwest::Method::PUT, &path, Some(body))
             .await
     }
@@ -506,7 +1336,7 @@ impl AtlassianClient {
     // --- Confluence Methods ---
 
     /// Search for Confluence pages using CQL (v1 API).
-    pub async fn search_pages(&self, cql: &str, limit: i64) -> Result<Value, String> {
+    pub async fn search_pages(&self, cql: &str, limit: i64) -> Result<Value, Error> {
         let path = format!("/search?cql={}&limit={}", urlencoding::encode(cql), limit);
         self.request(
             AtlassianApi::ConfluenceV1,
@@ -518,16 +1348,33 @@ impl AtlassianClient {
     }
 
     /// Get a Confluence page by ID, including ADF body (v2 API).
-    pub async fn get_page(&self, id: &str) -> Result<Value, String> {
+    ///
+    /// On Data Center the page comes from the v1 content API with a storage format body
+    /// (`body.storage.value`), and `spaceId` holds the space key.
+    pub async fn get_page(&self, id: &str) -> Result<Value, Error> {
+        if self.is_data_center() {
+            let path = format!("/content/{}?expand=body.storage,version,space", id);
+            let mut page = self
+                .request(
+                    AtlassianApi::ConfluenceV1,
+                    reqwest::Method::GET,
+                    &path,
+                    None,
+                )
+                .await?;
+            page["spaceId"] = page["space"]["key"].clone();
+            return Ok(page);
+        }
+
         let path = format!("/pages/{}?body-format=atlas_doc_format", id);
         self.request(AtlassianApi::Confluence, reqwest::Method::GET, &path, None)
             .await
     }
 
     /// Resolve a Space Key to a Space ID (v2 API).
-    pub async fn get_space_id(&self, key: &str) -> Result<String, String> {
-        // If it's already numeric, return it
-        if key.chars().all(|c| c.is_ascii_digit()) {
+    pub async fn get_space_id(&self, key: &str) -> Result<String, Error> {
+        // If it's already numeric, return it. Data Center addresses spaces by key.
+        if self.is_data_center() || key.chars().all(|c| c.is_ascii_digit()) {
             return Ok(key.to_string());
         }
 
@@ -542,10 +1389,10 @@ impl AtlassianClient {
                 return s["id"]
                     .as_str()
                     .map(|s| s.to_string())
-                    .ok_or("Space has no ID".to_string());
+                    .ok_or_else(|| "Space has no ID".into());
             }
         }
-        Err(format!("Could not find space with key '{}'", key))
+        Err(format!("Could not find space with key '{}'", key).into())
     }
 
     /// Create a new Confluence page (v2 API).
@@ -555,8 +1402,34 @@ impl AtlassianClient {
         title: &str,
         parent_id: Option<&str>,
         adf_body: &Value,
-    ) -> Result<Value, String> {
-        let stringified_adf = serde_json::to_string(adf_body).map_err(|e| e.to_string())?;
+    ) -> Result<Value, Error> {
+        if self.is_data_center() {
+            let mut body = serde_json::json!({
+                "type": "page",
+                "title": title,
+                "space": { "key": space_id },
+                "body": {
+                    "storage": {
+                        "value": markup::to_storage(adf_body),
+                        "representation": "storage"
+                    }
+                }
+            });
+            if let Some(pid) = parent_id {
+                body["ancestors"] = serde_json::json!([{ "id": pid }]);
+            }
+            return self
+                .request(
+                    AtlassianApi::ConfluenceV1,
+                    reqwest::Method::POST,
+                    "/content",
+                    Some(body),
+                )
+                .await;
+        }
+
+        let stringified_adf =
+            serde_json::to_string(adf_body).map_err(|e| Error::Other(e.to_string()))?;
 
         let mut body = serde_json::json!({
             "spaceId": space_id,
@@ -592,11 +1465,19 @@ impl AtlassianClient {
         adf_body: &Value,
         version: i64,
         minor_edit: bool,
-    ) -> Result<Value, String> {
+    ) -> Result<Value, Error> {
+        if self.is_data_center() {
+            let storage = markup::to_storage(adf_body);
+            return self
+                .update_page_storage(id, title, space_id, &storage, version, minor_edit)
+                .await;
+        }
+
         let path = format!("/pages/{}", id);
 
         // Confluence v2 requirement: body.value must be a stringified JSON string
-        let stringified_adf = serde_json::to_string(adf_body).map_err(|e| e.to_string())?;
+        let stringified_adf =
+            serde_json::to_string(adf_body).map_err(|e| Error::Other(e.to_string()))?;
 
         let body = serde_json::json!({
             "id": id,
@@ -622,15 +1503,652 @@ impl AtlassianClient {
         .await
     }
 
-    /// Add an attachment to a Confluence page (v2 API) with optional comment.
+    /// Update a Confluence page with a storage format body (v1 API, used on Data Center).
+    pub async fn update_page_storage(
+        &self,
+        id: &str,
+        title: &str,
+        space_key: &str,
+        storage: &str,
+        version: i64,
+        minor_edit: bool,
+    ) -> Result<Value, Error> {
+        let path = format!("/content/{}", id);
+        let body = serde_json::json!({
+            "id": id,
+            "type": "page",
+            "title": title,
+            "space": { "key": space_key },
+            "body": {
+                "storage": {
+                    "value": storage,
+                    "representation": "storage"
+                }
+            },
+            "version": {
+                "number": version,
+                "minorEdit": minor_edit
+            }
+        });
+        self.request(
+            AtlassianApi::ConfluenceV1,
+            reqwest::Method::PUT,
+            &path,
+            Some(body),
+        )
+        .await
+    }
+
+    /// List the attachments of a Confluence page (v1 API, which includes author names and
+    /// download links), fetching every page.
+    pub async fn page_attachments(&self, page_id: &str) -> Result<Vec<Value>, Error> {
+        let page_size = 100;
+        let mut start = 0;
+        let mut attachments = Vec::new();
+
+        loop {
+            let path = format!(
+                "/content/{}/child/attachment?expand=version&start={}&limit={}",
+                page_id, start, page_size
+            );
+            let data = self
+                .request(
+                    AtlassianApi::ConfluenceV1,
+                    reqwest::Method::GET,
+                    &path,
+                    None,
+                )
+                .await?;
+
+            let page = data["results"].as_array().cloned().unwrap_or_default();
+            let returned = page.len();
+            attachments.extend(page);
+            start += returned;
+
+            if returned < page_size || data["_links"]["next"].is_null() {
+                break;
+            }
+        }
+
+        Ok(attachments)
+    }
+
+    /// Absolute URL for a Confluence `_links.download` path.
+    pub fn confluence_download_url(&self, download_path: &str) -> String {
+        let context = match self.config.deployment {
+            Deployment::Cloud => "/wiki",
+            Deployment::DataCenter => "",
+        };
+        format!(
+            "{}{}{}",
+            self.base_url(&AtlassianApi::ConfluenceV1)
+                .trim_end_matches('/'),
+            context,
+            download_path
+        )
+    }
+
+    /// Delete a Confluence attachment (moves it to the trash).
+    pub async fn delete_page_attachment(&self, attachment_id: &str) -> Result<Value, Error> {
+        if self.is_data_center() {
+            let path = format!("/content/{}", attachment_id);
+            return self
+                .request(
+                    AtlassianApi::ConfluenceV1,
+                    reqwest::Method::DELETE,
+                    &path,
+                    None,
+                )
+                .await;
+        }
+
+        let path = format!("/attachments/{}", attachment_id);
+        self.request(
+            AtlassianApi::Confluence,
+            reqwest::Method::DELETE,
+            &path,
+            None,
+        )
+        .await
+    }
+
+    /// Add an attachment to a Confluence page (v2 API, v1 on Data Center) with optional comment.
     pub async fn attach_to_page(
         &self,
         id: &str,
         file_path: &str,
         comment: Option<String>,
-    ) -> Result<Value, String> {
+    ) -> Result<Value, Error> {
+        let files = [PathBuf::from(file_path)];
+        if self.is_data_center() {
+            let path = format!("/content/{}/child/attachment", id);
+            return self
+                .request_multipart(AtlassianApi::ConfluenceV1, &path, &files, comment)
+                .await;
+        }
+
         let path = format!("/pages/{}/attachments", id);
-        self.request_multipart(AtlassianApi::Confluence, &path, file_path, comment)
+        self.request_multipart(AtlassianApi::Confluence, &path, &files, comment)
             .await
     }
 }
+
+/// Rewrite a Jira Cloud (v3) request body for the Data Center v2 API: ADF documents become
+/// wiki markup and `{"accountId": ...}` user references become `{"name": ...}`.
+fn to_server_body(body: Value) -> Value {
+    match body {
+        Value::Object(map) if map.get("type").and_then(|t| t.as_str()) == Some("doc") => {
+            Value::String(markup::to_wiki(&Value::Object(map)))
+        }
+        Value::Object(mut map) => {
+            if map.len() == 1 {
+                if let Some(id) = map.remove("accountId") {
+                    map.insert("name".to_string(), id);
+                    return Value::Object(map);
+                }
+            }
+            Value::Object(
+                map.into_iter()
+                    .map(|(k, v)| (k, to_server_body(v)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(to_server_body).collect()),
+        other => other,
+    }
+}
+
+/// Read a file as a stream of chunks, reporting each chunk to `progress`.
+fn file_stream(
+    file: tokio::fs::File,
+    progress: Arc<Progress>,
+) -> impl futures_util::Stream<Item = Result<Vec<u8>, std::io::Error>> {
+    futures_util::stream::try_unfold(file, move |mut file| {
+        let progress = progress.clone();
+        async move {
+            let mut buf = vec![0; 64 * 1024];
+            let read = file.read(&mut buf).await?;
+            if read == 0 {
+                return Ok(None);
+            }
+            buf.truncate(read);
+            progress.add(read as u64);
+            Ok(Some((buf, file)))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AuthMode, Deployment, TokenSpec};
+    use crate::error::ErrorKind;
+    use serde_json::json;
+    use wiremock::matchers::{body_json, body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn test_client(site: &str, retry: RetryPolicy) -> AtlassianClient {
+        let mut config = Config::for_site(site, AuthMode::Basic, Deployment::Cloud)
+            .with_credentials("me@example.com", TokenSpec::Plain("secret".to_string()))
+            .unwrap();
+        config.retry = retry;
+        AtlassianClient::new(config)
+    }
+
+    /// A Data Center client authenticating with a personal access token.
+    fn data_center_client(site: &str) -> AtlassianClient {
+        let mut config = Config::for_site(site, AuthMode::Bearer, Deployment::DataCenter)
+            .with_credentials("", TokenSpec::Plain("secret".to_string()))
+            .unwrap();
+        config.retry = fast_policy();
+        AtlassianClient::new(config)
+    }
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            max_total_wait: Duration::from_secs(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_rate_limit_then_succeeds() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/myself"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/myself"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "displayName": "Me" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server.uri(), fast_policy());
+        let me = client.myself().await.unwrap();
+        assert_eq!(me["displayName"], "Me");
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/myself"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(4)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server.uri(), fast_policy());
+        let err = client.myself().await.unwrap_err();
+        assert_eq!(
+            err.api().map(|e| e.status),
+            Some(reqwest::StatusCode::SERVICE_UNAVAILABLE)
+        );
+        assert_eq!(err.kind(), ErrorKind::Server);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_exceeding_total_wait_is_not_honored() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/myself"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "30"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server.uri(), fast_policy());
+        let err = client.myself().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::RateLimited);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_client_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/issue/PROJ-1"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server.uri(), fast_policy());
+        let err = client.get_issue("PROJ-1").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_retries_confluence_apis() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/api/v2/pages/42"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/api/v2/pages/42"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "id": "42" })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/rest/api/search"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/wiki/rest/api/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "results": [] })))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server.uri(), fast_policy());
+        assert_eq!(client.get_page("42").await.unwrap()["id"], "42");
+        assert!(client.search_pages("type=page", 5).await.unwrap()["results"].is_array());
+    }
+
+    #[tokio::test]
+    async fn test_uploads_multiple_files_in_one_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/3/issue/PROJ-1/attachments"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "filename": "one.txt" },
+                { "filename": "two.txt" }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = std::env::temp_dir().join(format!("jiri-multi-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let files = [dir.join("one.txt"), dir.join("two.txt")];
+        std::fs::write(&files[0], b"first file").unwrap();
+        std::fs::write(&files[1], b"second file").unwrap();
+
+        let client = test_client(&server.uri(), fast_policy());
+        let result = client.attach_to_issue("PROJ-1", &files).await;
+        let requests = server.received_requests().await.unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(result.unwrap().as_array().unwrap().len(), 2);
+        let body = String::from_utf8_lossy(&requests[0].body);
+        for expected in ["one.txt", "first file", "two.txt", "second file"] {
+            assert!(body.contains(expected), "missing {} in {}", expected, body);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_multipart_upload() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/3/issue/PROJ-1/attachments"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/3/issue/PROJ-1/attachments"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!([{ "filename": "upload.txt" }])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let file = std::env::temp_dir().join(format!("jiri-retry-{}.txt", std::process::id()));
+        std::fs::write(&file, b"hello").unwrap();
+
+        let client = test_client(&server.uri(), fast_policy());
+        let result = client
+            .attach_to_issue("PROJ-1", std::slice::from_ref(&file))
+            .await;
+        std::fs::remove_file(&file).ok();
+        assert_eq!(result.unwrap()[0]["filename"], "upload.txt");
+    }
+
+    #[tokio::test]
+    async fn test_download_streams_to_file() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/attachment/content/10001"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"log line\n".to_vec()))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/attachment/content/404"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server.uri(), fast_policy());
+        let dest = std::env::temp_dir().join(format!("jiri-download-{}.log", std::process::id()));
+        let url = format!("{}/rest/api/3/attachment/content/", server.uri());
+
+        let written = client
+            .download(&format!("{}10001", url), &dest)
+            .await
+            .unwrap();
+        assert_eq!(written, 9);
+        assert_eq!(std::fs::read(&dest).unwrap(), b"log line\n");
+        std::fs::remove_file(&dest).ok();
+
+        let err = client
+            .download(&format!("{}404", url), &dest)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert!(!dest.exists());
+    }
+
+    #[tokio::test]
+    async fn test_create_issue_link_accepts_empty_body() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/3/issueLink"))
+            .and(body_json(json!({
+                "type": { "name": "Blocks" },
+                "inwardIssue": { "key": "PROJ-1" },
+                "outwardIssue": { "key": "PROJ-2" },
+            })))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server.uri(), fast_policy());
+        let result = client.create_issue_link("Blocks", "PROJ-1", "PROJ-2").await;
+        assert_eq!(result.unwrap(), Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_update_issue_sends_fields_and_update() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/rest/api/3/issue/PROJ-1"))
+            .and(body_json(json!({
+                "fields": { "summary": "New" },
+                "update": { "labels": [{ "add": "urgent" }, { "remove": "triage" }] },
+            })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server.uri(), fast_policy());
+        let update = json!({ "labels": [{ "add": "urgent" }, { "remove": "triage" }] });
+        client
+            .update_issue("PROJ-1", json!({ "summary": "New" }), Some(update))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_field_lookup_rejects_ambiguous_names() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/3/field"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "id": "summary", "name": "Summary" },
+                { "id": "customfield_10010", "name": "Team" },
+                { "id": "customfield_10020", "name": "team" },
+            ])))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server.uri(), fast_policy());
+        let lookup = client.field_lookup().await.unwrap();
+        assert_eq!(lookup.resolve("SUMMARY").unwrap(), Some("summary"));
+        assert_eq!(
+            lookup.resolve("customfield_10020").unwrap(),
+            Some("customfield_10020")
+        );
+        assert_eq!(lookup.resolve("Sprint").unwrap(), None);
+        let err = lookup.resolve("Team").unwrap_err().to_string();
+        assert!(
+            err.contains("customfield_10010, customfield_10020"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_data_center_uses_bearer_and_v2_with_wiki_bodies() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/2/issue/PROJ-1/comment"))
+            .and(header("authorization", "Bearer secret"))
+            .and(body_json(json!({ "body": "Ship *it*" })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "id": "10" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/rest/api/2/issue/PROJ-1/assignee"))
+            .and(body_json(json!({ "name": "jdoe" })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = data_center_client(&server.uri());
+        client
+            .add_comment(
+                "PROJ-1",
+                json!({ "body": adf::from_text("Ship **it**", false) }),
+            )
+            .await
+            .unwrap();
+        client
+            .request(
+                AtlassianApi::Jira,
+                reqwest::Method::PUT,
+                "/issue/PROJ-1/assignee",
+                Some(json!({ "accountId": "jdoe" })),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_data_center_search_pages_by_offset() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/2/search"))
+            .and(body_partial_json(json!({ "startAt": 0 })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "startAt": 0, "total": 3,
+                "issues": [{ "key": "PROJ-1" }, { "key": "PROJ-2" }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/2/search"))
+            .and(body_partial_json(json!({ "startAt": 2 })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "startAt": 2, "total": 3, "issues": [{ "key": "PROJ-3" }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = data_center_client(&server.uri());
+        let (issues, more) = client
+            .search_all("project = PROJ", vec![], 10)
+            .await
+            .unwrap();
+        let keys: Vec<&str> = issues.iter().filter_map(|i| i["key"].as_str()).collect();
+        assert_eq!(keys, ["PROJ-1", "PROJ-2", "PROJ-3"]);
+        assert!(!more);
+    }
+
+    #[tokio::test]
+    async fn test_data_center_confluence_uses_content_api() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/confluence/rest/api/content/42"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "42",
+                "title": "Notes",
+                "space": { "key": "ENG" },
+                "version": { "number": 3 },
+                "body": { "storage": { "value": "<p>Hi</p>" } }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/confluence/rest/api/content/42"))
+            .and(body_partial_json(json!({
+                "space": { "key": "ENG" },
+                "body": { "storage": { "value": "<p>Hi</p><p>More</p>" } },
+                "version": { "number": 4 }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "id": "42" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut client = data_center_client("https://jira.example.com");
+        client.config.confluence_site = Some(format!("{}/confluence", server.uri()));
+        let page = client.get_page("42").await.unwrap();
+        assert_eq!(page["spaceId"], "ENG");
+        client
+            .update_page_storage("42", "Notes", "ENG", "<p>Hi</p><p>More</p>", 4, false)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_oauth_routes_through_gateway_and_refreshes_on_401() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/ex/jira/cloud-1/rest/api/3/myself"))
+            .and(header("authorization", "Bearer old"))
+            .respond_with(ResponseTemplate::new(401))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/oauth/token"))
+            .and(body_json(json!({
+                "grant_type": "refresh_token",
+                "client_id": "app",
+                "refresh_token": "refresh-1",
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "access_token": "new",
+                "refresh_token": "refresh-2",
+                "expires_in": 3600
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/ex/jira/cloud-1/rest/api/3/myself"))
+            .and(header("authorization", "Bearer new"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "accountId": "a1" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let store = std::env::temp_dir().join(format!("jiri-oauth-{}.json", std::process::id()));
+        let session = Session {
+            access_token: "old".to_string(),
+            refresh_token: Some("refresh-1".to_string()),
+            expires_at: u64::MAX,
+            cloud_id: "cloud-1".to_string(),
+            site: "https://acme.atlassian.net".to_string(),
+        };
+        session.save(&store).unwrap();
+
+        let mut config = test_client("https://acme.atlassian.net", fast_policy()).config;
+        config.auth_mode = AuthMode::OAuth;
+        config.oauth = Some(OAuthSettings {
+            client_id: "app".to_string(),
+            client_secret: None,
+            oauth_url: server.uri(),
+            api_url: server.uri(),
+            store: store.clone(),
+        });
+        let client = AtlassianClient::new(config);
+        let me = client.myself().await.unwrap();
+        assert_eq!(me["accountId"], "a1");
+
+        // The rotated tokens are persisted for the next run
+        let saved = Session::load(&store).unwrap().unwrap();
+        std::fs::remove_file(&store).unwrap();
+        assert_eq!(saved.access_token, "new");
+        assert_eq!(saved.refresh_token.as_deref(), Some("refresh-2"));
+    }
+}
@@ -1,16 +1,28 @@
 use crate::client::AtlassianClient;
+use crate::error::Error;
 use owo_colors::OwoColorize;
 
 /// Execute the assign command to set issue assignee.
-pub async fn run(client: &AtlassianClient, key: String, user: String) -> Result<(), String> {
+pub async fn run(client: &AtlassianClient, key: String, user: String) -> Result<(), Error> {
     let account_id = super::edit::resolve_account_id(client, &user).await?;
+    assign_issue(client, &key, &account_id).await?;
+
+    println!("{} {}", "Assigned issue:".green().bold(), key.cyan().bold());
+    Ok(())
+}
+
+/// Assign an issue to an already resolved account ID.
+pub(crate) async fn assign_issue(
+    client: &AtlassianClient,
+    key: &str,
+    account_id: &str,
+) -> Result<(), Error> {
     client
         .update_issue(
-            &key,
+            key,
             serde_json::json!({ "assignee": { "accountId": account_id } }),
+            None,
         )
         .await?;
-
-    println!("{} {}", "Assigned issue:".green().bold(), key.cyan().bold());
     Ok(())
 }
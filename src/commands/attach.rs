@@ -1,34 +1,60 @@
 use crate::client::AtlassianClient;
+use crate::error::Error;
 use owo_colors::OwoColorize;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tokio::time::sleep;
 
+use super::attachments::{format_size, glob_match};
+
+/// Attach one or more files (or glob patterns) to an issue in a single upload. With a
+/// message, the attachment is also embedded in a new comment (single file only).
 pub async fn run(
     client: &AtlassianClient,
     key: String,
-    file_path: String,
+    files: Vec<String>,
     message: Option<String>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Attaching {} to issue {}...", file_path, key);
-    let result = client.attach_to_issue(&key, &file_path).await?;
+) -> Result<(), Error> {
+    let paths = expand_paths(&files)?;
+    if message.is_some() && paths.len() > 1 {
+        return Err("--message can only be used when attaching a single file".into());
+    }
+
+    match paths.as_slice() {
+        [path] => println!("Attaching {} to issue {}...", path.display(), key),
+        _ => println!("Attaching {} files to issue {}...", paths.len(), key),
+    }
+    let result = client.attach_to_issue(&key, &paths).await?;
 
     let attachments = result
         .as_array()
         .ok_or("Failed to parse attachment response")?;
-    let attachment = attachments
-        .first()
-        .ok_or("No attachment returned in response")?;
-    let filename = attachment["filename"].as_str().unwrap_or("unknown");
-    let attachment_id = attachment["id"].as_str().ok_or("Attachment has no numeric ID")?;
-    let attachment_url = attachment["content"].as_str().ok_or("Attachment has no content URL")?;
-
-    println!(
-        "{} {}",
-        "Successfully attached:".green().bold(),
-        filename.bold()
-    );
+    if attachments.is_empty() {
+        return Err("No attachment returned in response".into());
+    }
+    for attachment in attachments {
+        println!(
+            "{} {} {}",
+            "Successfully attached:".green().bold(),
+            attachment["filename"].as_str().unwrap_or("unknown").bold(),
+            format!(
+                "({}, ID {})",
+                format_size(attachment["size"].as_u64().unwrap_or(0)),
+                attachment["id"].as_str().unwrap_or("?")
+            )
+            .dimmed()
+        );
+    }
 
     if let Some(msg) = message {
+        let attachment = &attachments[0];
+        let attachment_id = attachment["id"]
+            .as_str()
+            .ok_or("Attachment has no numeric ID")?;
+        let attachment_url = attachment["content"]
+            .as_str()
+            .ok_or("Attachment has no content URL")?;
+
         println!("Resolving Media ID and polling for attachment processing...");
         
         let media_id = match client.get_attachment_media_id(attachment_id).await {
@@ -52,7 +78,7 @@ pub async fn run(
                     success = true;
                     break;
                 }
-                Err(e) if e.contains("ATTACHMENT_VALIDATION_ERROR") => {
+                Err(e) if e.api().is_some_and(|api| api.mentions("ATTACHMENT_VALIDATION_ERROR")) => {
                     attempt += 1;
                     if attempt < max_retries {
                         print!(".");
@@ -83,3 +109,70 @@ pub async fn run(
 
     Ok(())
 }
+
+/// Expand arguments containing `*` or `?` in their file name against the directory they
+/// name (no recursive `**`). Other arguments are passed through as-is.
+fn expand_paths(args: &[String]) -> Result<Vec<PathBuf>, Error> {
+    let mut paths = Vec::new();
+    for arg in args {
+        let path = Path::new(arg);
+        let pattern = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        if !pattern.contains(['*', '?']) {
+            paths.push(path.to_path_buf());
+            continue;
+        }
+
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+        let mut matches: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+            .filter(|e| {
+                e.file_name()
+                    .to_str()
+                    .is_some_and(|n| glob_match(pattern, n))
+            })
+            .map(|e| dir.join(e.file_name()))
+            .collect();
+        if matches.is_empty() {
+            return Err(format!("No files match '{}'", arg).into());
+        }
+        matches.sort();
+        paths.extend(matches);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_paths() {
+        let dir = std::env::temp_dir().join(format!("jiri-attach-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["b.log", "a.log", "notes.txt"] {
+            std::fs::write(dir.join(name), b"x").unwrap();
+        }
+
+        let pattern = dir.join("*.log").to_string_lossy().to_string();
+        let plain = dir.join("notes.txt").to_string_lossy().to_string();
+        let paths = expand_paths(&[pattern, plain]).unwrap();
+        let names: Vec<_> = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, ["a.log", "b.log", "notes.txt"]);
+
+        let none = dir.join("*.png").to_string_lossy().to_string();
+        assert!(expand_paths(&[none]).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,273 @@
+use crate::client::AtlassianClient;
+use crate::dates;
+use crate::error::Error;
+use crate::formatter::Formatter;
+use owo_colors::OwoColorize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// An attachment to download: its ID, file name and absolute content URL.
+pub struct Download {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+}
+
+/// List the attachments of an issue.
+pub async fn run_list(
+    client: &AtlassianClient,
+    formatter: &Formatter,
+    key: String,
+) -> Result<(), Error> {
+    let attachments = client.get_issue_attachments(&key).await?;
+
+    let mut rows = vec![vec![
+        "ID".to_string(),
+        "NAME".to_string(),
+        "SIZE".to_string(),
+        "AUTHOR".to_string(),
+        "CREATED".to_string(),
+    ]];
+    for a in &attachments {
+        rows.push(vec![
+            a["id"].as_str().unwrap_or_default().to_string(),
+            a["filename"].as_str().unwrap_or_default().to_string(),
+            format_size(a["size"].as_u64().unwrap_or(0)),
+            a["author"]["displayName"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            dates::display_timestamp(a["created"].as_str().unwrap_or_default()),
+        ]);
+    }
+
+    println!("{}", formatter.render(rows));
+    Ok(())
+}
+
+/// Download the attachments of an issue whose name matches `pattern` (all when omitted).
+pub async fn run_download(
+    client: &AtlassianClient,
+    key: String,
+    pattern: Option<String>,
+    out_dir: Option<String>,
+    force: bool,
+) -> Result<(), Error> {
+    let attachments = client.get_issue_attachments(&key).await?;
+    let selected = select(&attachments, "filename", pattern.as_deref());
+    if selected.is_empty() {
+        return Err(no_match(&key, pattern.as_deref()).into());
+    }
+
+    let downloads = selected
+        .into_iter()
+        .map(|a| Download {
+            id: a["id"].as_str().unwrap_or_default().to_string(),
+            name: a["filename"].as_str().unwrap_or_default().to_string(),
+            url: a["content"].as_str().unwrap_or_default().to_string(),
+        })
+        .collect();
+    download_all(client, downloads, out_dir.as_deref(), force).await
+}
+
+/// Delete attachments of an issue by ID and/or name pattern.
+pub async fn run_delete(
+    client: &AtlassianClient,
+    key: String,
+    ids: Vec<String>,
+    pattern: Option<String>,
+) -> Result<(), Error> {
+    let attachments = client.get_issue_attachments(&key).await?;
+    let selected: Vec<&Value> = attachments
+        .iter()
+        .filter(|a| {
+            let id = a["id"].as_str().unwrap_or_default();
+            let name = a["filename"].as_str().unwrap_or_default();
+            ids.iter().any(|i| i == id) || pattern.as_deref().is_some_and(|p| glob_match(p, name))
+        })
+        .collect();
+    if selected.is_empty() {
+        return Err(no_match(&key, pattern.as_deref()).into());
+    }
+
+    for a in selected {
+        let id = a["id"].as_str().unwrap_or_default();
+        client.delete_issue_attachment(id).await?;
+        println!(
+            "{} {} {}",
+            "Deleted".green().bold(),
+            a["filename"].as_str().unwrap_or("?").bold(),
+            format!("({})", id).dimmed()
+        );
+    }
+    Ok(())
+}
+
+/// Attachments whose `name_field` matches the glob `pattern`, or all of them.
+pub(crate) fn select<'a>(
+    attachments: &'a [Value],
+    name_field: &str,
+    pattern: Option<&str>,
+) -> Vec<&'a Value> {
+    attachments
+        .iter()
+        .filter(|a| {
+            pattern.is_none_or(|p| glob_match(p, a[name_field].as_str().unwrap_or_default()))
+        })
+        .collect()
+}
+
+pub(crate) fn no_match(owner: &str, pattern: Option<&str>) -> String {
+    match pattern {
+        Some(pattern) => format!("No attachments on {} match '{}'", owner, pattern),
+        None => format!("{} has no attachments", owner),
+    }
+}
+
+/// Stream each attachment into `out_dir` (default: the current directory). When two
+/// attachments share a name, later ones are saved as `<id>-<name>`. Existing files are only
+/// overwritten with `force`, and are checked before anything is downloaded.
+pub(crate) async fn download_all(
+    client: &AtlassianClient,
+    downloads: Vec<Download>,
+    out_dir: Option<&str>,
+    force: bool,
+) -> Result<(), Error> {
+    let dir = PathBuf::from(out_dir.unwrap_or("."));
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let mut used = HashSet::new();
+    let mut targets = Vec::new();
+    for download in downloads {
+        let name = safe_file_name(&download.name, &download.id);
+        let name = if used.insert(name.clone()) {
+            name
+        } else {
+            format!("{}-{}", download.id, name)
+        };
+        targets.push((dir.join(&name), download));
+    }
+    if !force {
+        let existing: Vec<String> = targets
+            .iter()
+            .filter(|(dest, _)| dest.exists())
+            .map(|(dest, _)| dest.display().to_string())
+            .collect();
+        if !existing.is_empty() {
+            return Err(format!(
+                "Not overwriting existing files: {}. Pass --force to overwrite them",
+                existing.join(", ")
+            )
+            .into());
+        }
+    }
+
+    for (dest, download) in targets {
+        let bytes = client.download(&download.url, &dest).await?;
+        println!(
+            "{} {} {}",
+            "Downloaded".green().bold(),
+            dest.display().bold(),
+            format!("({})", format_size(bytes)).dimmed()
+        );
+    }
+    Ok(())
+}
+
+/// Keep only the final path component of a server-supplied name, so a name like
+/// `../../.bashrc` cannot escape the output directory.
+fn safe_file_name(name: &str, id: &str) -> String {
+    Path::new(name)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .filter(|n| !n.is_empty())
+        .map(|n| n.to_string())
+        .unwrap_or_else(|| format!("attachment-{}", id))
+}
+
+/// Case-insensitive shell-style glob match supporting `*` and `?`.
+pub(crate) fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name index it was tried at, for backtracking.
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Human-readable size, e.g. `512 B`, `1.5 KB`, `12.0 MB`.
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.log", "server.log"));
+        assert!(glob_match("*.LOG", "server.log"));
+        assert!(glob_match("server-??.log", "server-01.log"));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(glob_match("report.pdf", "Report.PDF"));
+        assert!(!glob_match("*.log", "server.log.gz"));
+        assert!(!glob_match("server-?.log", "server-01.log"));
+        assert!(!glob_match("a*b", "ac"));
+    }
+
+    #[test]
+    fn test_select_and_helpers() {
+        let attachments = vec![
+            json!({ "id": "1", "filename": "app.log" }),
+            json!({ "id": "2", "filename": "screenshot.png" }),
+        ];
+        assert_eq!(select(&attachments, "filename", None).len(), 2);
+        let logs = select(&attachments, "filename", Some("*.log"));
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0]["id"], "1");
+
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+
+        assert_eq!(safe_file_name("../../.bashrc", "7"), ".bashrc");
+        assert_eq!(safe_file_name("..", "7"), "attachment-7");
+    }
+}
@@ -0,0 +1,176 @@
+use crate::client::AtlassianClient;
+use crate::config::AuthMode;
+use crate::error::Error;
+use crate::input;
+use crate::oauth::{self, OAuthSettings, Session};
+use owo_colors::OwoColorize;
+use serde_json::Value;
+use tokio::net::TcpListener;
+
+use super::open;
+
+/// Log in with OAuth 2.0 (3LO): authorize in the browser, then store the tokens and the
+/// cloud ID of the chosen site.
+pub async fn run_login(
+    client: &AtlassianClient,
+    port: u16,
+    scopes: Option<String>,
+    no_browser: bool,
+) -> Result<(), Error> {
+    let settings = oauth_settings(client)?;
+    let listener = TcpListener::bind(("127.0.0.1", port)).await.map_err(|e| {
+        format!(
+            "Could not listen on port {} for the OAuth redirect: {}. Pick another with --port \
+             (it must match the app's callback URL)",
+            port, e
+        )
+    })?;
+
+    let http = reqwest::Client::new();
+    let scopes = scopes.as_deref().unwrap_or(oauth::DEFAULT_SCOPES);
+    let tokens = oauth::authorize(&http, settings, listener, scopes, |url| {
+        println!("{}", "Open this URL to authorize jiri:".yellow().bold());
+        println!("  {}", url);
+        if !no_browser && open::open_url(url).is_err() {
+            println!(
+                "{}",
+                "(could not open a browser; copy the URL instead)".dimmed()
+            );
+        }
+        println!("Waiting for the browser to return to jiri...");
+    })
+    .await?;
+
+    // Without auth.site, the site comes from the previous login; do not hold the user to it
+    let previous = Session::load(&settings.store)?.map(|s| s.site);
+    let configured = match &client.config().site {
+        site if previous.as_ref() == Some(site) => "",
+        site => site,
+    };
+    let sites = oauth::accessible_resources(&http, settings, &tokens.access_token).await?;
+    let site = choose_site(&sites, configured)?;
+    let mut session = Session {
+        access_token: String::new(),
+        refresh_token: None,
+        expires_at: 0,
+        cloud_id: site["id"].as_str().unwrap_or_default().to_string(),
+        site: site["url"].as_str().unwrap_or_default().to_string(),
+    };
+    session.update(tokens);
+    session.save(&settings.store)?;
+
+    println!(
+        "{} {} ({} {})",
+        "Logged in to".green().bold(),
+        session.site.cyan().bold(),
+        "cloud ID".dimmed(),
+        session.cloud_id.dimmed()
+    );
+    if session.refresh_token.is_none() {
+        println!(
+            "{} no refresh token was issued; include the offline_access scope to stay logged in",
+            "warning:".yellow().bold()
+        );
+    }
+    Ok(())
+}
+
+/// Forget the stored OAuth tokens of the active profile.
+pub async fn run_logout(client: &AtlassianClient) -> Result<(), Error> {
+    let settings = oauth_settings(client)?;
+    match std::fs::remove_file(&settings.store) {
+        Ok(()) => println!("{}", "Logged out".green().bold()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => println!("Not logged in"),
+        Err(e) => {
+            return Err(format!("Failed to remove {}: {}", settings.store.display(), e).into())
+        }
+    }
+    Ok(())
+}
+
+fn oauth_settings(client: &AtlassianClient) -> Result<&OAuthSettings, Error> {
+    match (&client.config().auth_mode, &client.config().oauth) {
+        (AuthMode::OAuth, Some(settings)) => Ok(settings),
+        _ => Err(concat!(
+            "OAuth is not configured. Set auth.mode to oauth and auth.client_id to your ",
+            "OAuth app's client ID with `jiri config set`"
+        )
+        .into()),
+    }
+}
+
+/// Pick the site to use: the configured one, the only one, or (interactively) one the
+/// user chooses.
+fn choose_site<'a>(sites: &'a [Value], configured: &str) -> Result<&'a Value, Error> {
+    fn url(site: &Value) -> &str {
+        site["url"]
+            .as_str()
+            .unwrap_or_default()
+            .trim_end_matches('/')
+    }
+    let listing = || {
+        sites
+            .iter()
+            .map(|s| format!("{} ({})", url(s), s["name"].as_str().unwrap_or("")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    if sites.is_empty() {
+        return Err("The authorization does not grant access to any site".into());
+    }
+    if !configured.is_empty() {
+        return sites
+            .iter()
+            .find(|s| url(s).eq_ignore_ascii_case(configured.trim_end_matches('/')))
+            .ok_or_else(|| {
+                format!(
+                    "The authorization does not cover {}. Sites granted: {}",
+                    configured,
+                    listing()
+                )
+                .into()
+            });
+    }
+    if let [site] = sites {
+        return Ok(site);
+    }
+    if !input::is_interactive() {
+        return Err(format!(
+            "Several sites were granted; set auth.site to one of: {}",
+            listing()
+        )
+        .into());
+    }
+
+    for (i, site) in sites.iter().enumerate() {
+        println!("  {}. {}", i + 1, url(site));
+    }
+    let answer = input::prompt_line("Site number: ")?;
+    answer
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| sites.get(i))
+        .ok_or_else(|| format!("Invalid choice '{}'", answer.trim()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_choose_site() {
+        let sites = vec![
+            json!({ "id": "1", "url": "https://a.atlassian.net", "name": "a" }),
+            json!({ "id": "2", "url": "https://b.atlassian.net", "name": "b" }),
+        ];
+        let chosen = choose_site(&sites, "https://B.atlassian.net/").unwrap();
+        assert_eq!(chosen["id"], "2");
+        assert!(choose_site(&sites, "https://c.atlassian.net").is_err());
+        assert_eq!(choose_site(&sites[..1], "").unwrap()["id"], "1");
+        assert!(choose_site(&[], "").is_err());
+    }
+}
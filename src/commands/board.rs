@@ -0,0 +1,124 @@
+use crate::client::AtlassianClient;
+use crate::error::Error;
+use crate::formatter::Formatter;
+use serde_json::Value;
+
+/// List Jira Software boards, optionally only those of a project.
+pub async fn run_list(
+    client: &AtlassianClient,
+    formatter: &Formatter,
+    project: Option<String>,
+) -> Result<(), Error> {
+    let boards = client.boards_all(project.as_deref()).await?;
+
+    let mut rows = vec![vec![
+        "ID".to_string(),
+        "NAME".to_string(),
+        "TYPE".to_string(),
+        "PROJECT".to_string(),
+    ]];
+    for b in &boards {
+        rows.push(vec![
+            board_id(b),
+            b["name"].as_str().unwrap_or_default().to_string(),
+            b["type"].as_str().unwrap_or_default().to_string(),
+            b["location"]["projectKey"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        ]);
+    }
+
+    println!("{}", formatter.render(rows));
+    Ok(())
+}
+
+fn board_id(board: &Value) -> String {
+    match &board["id"] {
+        Value::Number(n) => n.to_string(),
+        other => other.as_str().unwrap_or_default().to_string(),
+    }
+}
+
+/// Resolve `--board` (an ID or name) to a board ID. Without it, use the only board of the
+/// default project.
+pub async fn resolve_board(client: &AtlassianClient, board: Option<&str>) -> Result<String, Error> {
+    if let Some(id) = board.filter(|b| b.chars().all(|c| c.is_ascii_digit())) {
+        return Ok(id.to_string());
+    }
+
+    let boards = match board {
+        Some(name) => {
+            let boards = client.boards_all(None).await?;
+            let matches = match_boards(&boards, name);
+            if matches.is_empty() {
+                return Err(format!("No board matching '{}'", name).into());
+            }
+            matches
+        }
+        None => {
+            let project = client
+                .config()
+                .default_project
+                .clone()
+                .ok_or("No board given. Use --board or set default_project in config.")?;
+            let boards = client.boards_all(Some(&project)).await?;
+            if boards.is_empty() {
+                return Err(format!("Project {} has no boards", project).into());
+            }
+            boards
+        }
+    };
+
+    if boards.len() > 1 {
+        let names: Vec<String> = boards
+            .iter()
+            .map(|b| format!("{} ({})", b["name"].as_str().unwrap_or("?"), board_id(b)))
+            .collect();
+        return Err(format!(
+            "Several boards match; pick one with --board: {}",
+            names.join(", ")
+        )
+        .into());
+    }
+    Ok(board_id(&boards[0]))
+}
+
+/// Boards whose name matches exactly (case-insensitive), or else contains `name`.
+fn match_boards(boards: &[Value], name: &str) -> Vec<Value> {
+    let lower = name.to_lowercase();
+    let board_name = |b: &Value| b["name"].as_str().unwrap_or_default().to_lowercase();
+    let exact: Vec<Value> = boards
+        .iter()
+        .filter(|b| board_name(b) == lower)
+        .cloned()
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+    boards
+        .iter()
+        .filter(|b| board_name(b).contains(&lower))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_match_boards() {
+        let boards = vec![
+            json!({ "id": 1, "name": "Platform" }),
+            json!({ "id": 2, "name": "Platform Kanban" }),
+            json!({ "id": 3, "name": "Mobile" }),
+        ];
+        let exact = match_boards(&boards, "platform");
+        assert_eq!(exact.len(), 1);
+        assert_eq!(board_id(&exact[0]), "1");
+        assert_eq!(match_boards(&boards, "kanban").len(), 1);
+        assert!(match_boards(&boards, "web").is_empty());
+    }
+}
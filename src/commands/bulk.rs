@@ -0,0 +1,246 @@
+use crate::adf;
+use crate::client::AtlassianClient;
+use crate::error::Error;
+use crate::formatter::Formatter;
+use crate::input;
+use futures_util::stream::{self, StreamExt};
+use owo_colors::OwoColorize;
+use serde_json::Value;
+
+use super::{assign, comment, edit, transition};
+
+/// Issue keys shown in the confirmation prompt before "and N more".
+const PREVIEW_KEYS: usize = 10;
+
+/// What to do to every issue a bulk query returns.
+pub enum Action {
+    Transition {
+        status: String,
+        resolution: Option<String>,
+        comment: Option<String>,
+    },
+    Edit {
+        labels: Option<String>,
+        fields: Vec<String>,
+    },
+    Assign {
+        user: String,
+    },
+    Comment {
+        message: String,
+    },
+}
+
+impl Action {
+    fn describe(&self) -> String {
+        match self {
+            Action::Transition { status, .. } => format!("transition to '{}'", status),
+            Action::Edit { .. } => "edit".to_string(),
+            Action::Assign { user } => format!("assign to '{}'", user),
+            Action::Comment { .. } => "comment on".to_string(),
+        }
+    }
+}
+
+/// Values resolved once up front, so they are not looked up again for every issue.
+enum Prepared {
+    Transition {
+        status: String,
+        resolution: Option<String>,
+        comment: Option<String>,
+    },
+    Edit {
+        fields: serde_json::Map<String, Value>,
+        update: serde_json::Map<String, Value>,
+    },
+    Assign {
+        account_id: String,
+    },
+    Comment {
+        payload: Value,
+    },
+}
+
+/// Apply an action to every issue matching `jql`, `concurrency` issues at a time, and print
+/// a per-issue result table. Asks for confirmation unless `yes` is set.
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    client: &AtlassianClient,
+    formatter: &Formatter,
+    jql: String,
+    action: Action,
+    limit: i64,
+    concurrency: usize,
+    yes: bool,
+    plain: bool,
+) -> Result<(), Error> {
+    let fields = vec!["summary".to_string()];
+    let (issues, more_available) = client.search_all(&jql, fields, limit).await?;
+    if more_available {
+        return Err(format!(
+            "The query matches more than {} issues. Narrow the JQL or raise --limit.",
+            limit
+        )
+        .into());
+    }
+    if issues.is_empty() {
+        println!("No issues match the query.");
+        return Ok(());
+    }
+
+    let keys: Vec<&str> = issues
+        .iter()
+        .map(|i| i["key"].as_str().unwrap_or_default())
+        .collect();
+    let description = action.describe();
+    let prepared = prepare(client, action, plain).await?;
+    if !yes && !confirm(&description, &keys)? {
+        return Err("Aborted".into());
+    }
+
+    let mut results: Vec<(usize, Result<String, Error>)> = stream::iter(issues.iter().enumerate())
+        .map(|(i, issue)| {
+            let prepared = &prepared;
+            async move { (i, apply(client, issue, prepared, plain).await) }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+    results.sort_by_key(|(i, _)| *i);
+
+    let mut rows = vec![vec![
+        "KEY".to_string(),
+        "RESULT".to_string(),
+        "DETAIL".to_string(),
+    ]];
+    let mut failed = 0;
+    for (i, result) in results {
+        let (status, detail) = match result {
+            Ok(detail) => ("ok", detail),
+            Err(e) => {
+                failed += 1;
+                ("failed", e.to_string())
+            }
+        };
+        rows.push(vec![keys[i].to_string(), status.to_string(), detail]);
+    }
+    println!("{}", formatter.render(rows));
+
+    if failed > 0 {
+        return Err(format!("{} of {} issues failed", failed, keys.len()).into());
+    }
+    Ok(())
+}
+
+/// List the affected issues and ask before changing them.
+fn confirm(action: &str, keys: &[&str]) -> Result<bool, Error> {
+    if !input::is_interactive() {
+        return Err(format!(
+            "Refusing to {} {} issues without confirmation. Pass --yes to proceed.",
+            action,
+            keys.len()
+        )
+        .into());
+    }
+    let mut preview = keys
+        .iter()
+        .take(PREVIEW_KEYS)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if keys.len() > PREVIEW_KEYS {
+        preview.push_str(&format!(" and {} more", keys.len() - PREVIEW_KEYS));
+    }
+    println!(
+        "{} {} {} issues: {}",
+        "About to".yellow().bold(),
+        action,
+        keys.len(),
+        preview
+    );
+    let answer = input::prompt_line("Continue? [y/N] ")?;
+    Ok(matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+}
+
+async fn prepare(client: &AtlassianClient, action: Action, plain: bool) -> Result<Prepared, Error> {
+    Ok(match action {
+        Action::Transition {
+            status,
+            resolution,
+            comment,
+        } => Prepared::Transition {
+            status,
+            resolution,
+            comment,
+        },
+        Action::Edit { labels, fields } => {
+            if labels.is_none() && fields.is_empty() {
+                return Err("No fields provided. Use --labels or --field.".into());
+            }
+            let (mut fields, mut update) = edit::resolve_field_edits(client, &fields).await?;
+            if let Some(labels) = labels {
+                edit::label_edits(&labels, &mut fields, &mut update)?;
+            }
+            Prepared::Edit { fields, update }
+        }
+        Action::Assign { user } => Prepared::Assign {
+            account_id: edit::resolve_account_id(client, &user).await?,
+        },
+        Action::Comment { message } => {
+            if message.trim().is_empty() {
+                return Err("Comment body is empty".into());
+            }
+            Prepared::Comment {
+                payload: comment::comment_payload(adf::from_text(&message, plain), None, false),
+            }
+        }
+    })
+}
+
+/// Apply the action to one issue, returning a short description of what happened.
+async fn apply(
+    client: &AtlassianClient,
+    issue: &Value,
+    prepared: &Prepared,
+    plain: bool,
+) -> Result<String, Error> {
+    let key = issue["key"].as_str().unwrap_or_default();
+    match prepared {
+        Prepared::Transition {
+            status,
+            resolution,
+            comment,
+        } => {
+            let name = transition::transition_issue(
+                client,
+                key,
+                status,
+                resolution.clone(),
+                comment.clone(),
+                None,
+                Vec::new(),
+                plain,
+            )
+            .await?;
+            Ok(format!("→ {}", name))
+        }
+        Prepared::Edit { fields, update } => {
+            client
+                .update_issue(
+                    key,
+                    Value::Object(fields.clone()),
+                    edit::update_payload(update.clone()),
+                )
+                .await?;
+            Ok("updated".to_string())
+        }
+        Prepared::Assign { account_id } => {
+            assign::assign_issue(client, key, account_id).await?;
+            Ok("assigned".to_string())
+        }
+        Prepared::Comment { payload } => {
+            let result = client.add_comment(key, payload.clone()).await?;
+            Ok(format!("comment {}", result["id"].as_str().unwrap_or("?")))
+        }
+    }
+}
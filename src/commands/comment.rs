@@ -1,13 +1,220 @@
+use crate::adf;
 use crate::client::AtlassianClient;
+use crate::error::Error;
+use crate::formatter::Formatter;
+use crate::input;
 use owo_colors::OwoColorize;
+use serde_json::{json, Value};
+
+/// Jira Service Management property that marks a comment as internal (agents only).
+const SD_PUBLIC_COMMENT: &str = "sd.public.comment";
 
 /// Execute the comment command to add a comment to an issue.
-pub async fn run(client: &AtlassianClient, key: String, message: String) -> Result<(), String> {
-    client.add_comment(&key, &message).await?;
+pub async fn run_add(
+    client: &AtlassianClient,
+    key: String,
+    message: Option<String>,
+    visibility: Option<String>,
+    internal: bool,
+    plain: bool,
+) -> Result<(), Error> {
+    let visibility = visibility.as_deref().map(parse_visibility).transpose()?;
+    let message = match message {
+        Some(message) => message,
+        None => input::compose(
+            "comment",
+            "",
+            &[
+                &format!("Write your comment on {} in Markdown.", key),
+                "Lines like these are removed. Save an empty file to cancel.",
+            ],
+        )?
+        .ok_or("Aborted: empty comment")?,
+    };
+    if message.trim().is_empty() {
+        return Err("Comment body is empty".into());
+    }
+
+    let comment = comment_payload(adf::from_text(&message, plain), visibility, internal);
+    let result = client.add_comment(&key, comment).await?;
     println!(
-        "{} {}",
+        "{} {} {}",
         "Comment added to".green().bold(),
+        key.cyan().bold(),
+        format!("(comment {})", result["id"].as_str().unwrap_or("?")).dimmed()
+    );
+    Ok(())
+}
+
+/// List all comments of an issue.
+pub async fn run_list(
+    client: &AtlassianClient,
+    formatter: &Formatter,
+    key: String,
+) -> Result<(), Error> {
+    let comments = client.get_comments(&key).await?;
+
+    let mut rows = vec![vec![
+        "ID".to_string(),
+        "AUTHOR".to_string(),
+        "CREATED".to_string(),
+        "VISIBILITY".to_string(),
+        "BODY".to_string(),
+    ]];
+    for c in &comments {
+        rows.push(vec![
+            c["id"].as_str().unwrap_or_default().to_string(),
+            c["author"]["displayName"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            crate::dates::display_timestamp(c["created"].as_str().unwrap_or_default()),
+            describe_visibility(c),
+            adf::to_plain_text(&c["body"])
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+        ]);
+    }
+
+    println!("{}", formatter.render(rows));
+    Ok(())
+}
+
+/// Replace the body of a comment. Without a message, the current body is opened in the
+/// editor. Visibility is kept unless `--visibility` or `--internal` is given.
+pub async fn run_edit(
+    client: &AtlassianClient,
+    key: String,
+    id: String,
+    message: Option<String>,
+    visibility: Option<String>,
+    internal: bool,
+    plain: bool,
+) -> Result<(), Error> {
+    let visibility = visibility.as_deref().map(parse_visibility).transpose()?;
+    let existing = client.get_comment(&key, &id).await?;
+    let body = match message {
+        Some(message) if message.trim().is_empty() => return Err("Comment body is empty".into()),
+        Some(message) => adf::from_text(&message, plain),
+        None => {
+            let what = format!("comment {} on {}", id, key);
+            match super::edit::compose_body(client, "comment", &existing["body"], &what, plain)? {
+                Some(body) => body,
+                None if visibility.is_none() && !internal => {
+                    println!(
+                        "{} {} on {}",
+                        "Comment unchanged".yellow().bold(),
+                        id.bold(),
+                        key.cyan().bold()
+                    );
+                    return Ok(());
+                }
+                None => existing["body"].clone(),
+            }
+        }
+    };
+
+    let visibility = visibility.or_else(|| {
+        existing["visibility"]
+            .is_object()
+            .then(|| existing["visibility"].clone())
+    });
+    let comment = comment_payload(body, visibility, internal);
+    client.update_comment(&key, &id, comment).await?;
+    println!(
+        "{} {} on {}",
+        "Updated comment".green().bold(),
+        id.bold(),
         key.cyan().bold()
     );
     Ok(())
 }
+
+/// Delete a comment.
+pub async fn run_delete(client: &AtlassianClient, key: String, id: String) -> Result<(), Error> {
+    client.delete_comment(&key, &id).await?;
+    println!(
+        "{} {} on {}",
+        "Deleted comment".green().bold(),
+        id.bold(),
+        key.cyan().bold()
+    );
+    Ok(())
+}
+
+/// Parse `role:NAME` or `group:NAME` into a comment `visibility` object.
+fn parse_visibility(text: &str) -> Result<Value, String> {
+    let (kind, value) = text
+        .split_once(':')
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim()))
+        .unwrap_or_default();
+    if !matches!(kind.as_str(), "role" | "group") || value.is_empty() {
+        return Err(format!(
+            "Invalid visibility '{}'. Use role:NAME or group:NAME (e.g. role:Developers)",
+            text
+        ));
+    }
+    Ok(json!({ "type": kind, "value": value }))
+}
+
+pub(crate) fn comment_payload(body: Value, visibility: Option<Value>, internal: bool) -> Value {
+    let mut comment = json!({ "body": body });
+    if let Some(visibility) = visibility {
+        comment["visibility"] = visibility;
+    }
+    if internal {
+        comment["properties"] = json!([
+            { "key": SD_PUBLIC_COMMENT, "value": { "internal": true } }
+        ]);
+    }
+    comment
+}
+
+/// `role:Developers`, `group:jira-users`, `internal`, or empty for public comments.
+fn describe_visibility(comment: &Value) -> String {
+    let visibility = &comment["visibility"];
+    if let (Some(kind), Some(value)) = (visibility["type"].as_str(), visibility["value"].as_str()) {
+        return format!("{}:{}", kind, value);
+    }
+    let internal = comment["properties"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|p| p["key"] == SD_PUBLIC_COMMENT && p["value"]["internal"] == true);
+    if internal {
+        "internal".to_string()
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visibility() {
+        assert_eq!(
+            parse_visibility("role:Developers").unwrap(),
+            json!({ "type": "role", "value": "Developers" })
+        );
+        assert_eq!(
+            parse_visibility("Group: jira-users").unwrap(),
+            json!({ "type": "group", "value": "jira-users" })
+        );
+        assert!(parse_visibility("Developers").is_err());
+        assert!(parse_visibility("user:bob").is_err());
+        assert!(parse_visibility("role:").is_err());
+
+        let comment = comment_payload(json!({}), parse_visibility("role:Developers").ok(), true);
+        assert_eq!(describe_visibility(&comment), "role:Developers");
+        let comment = comment_payload(json!({}), None, true);
+        assert_eq!(comment["properties"][0]["key"], "sd.public.comment");
+        assert_eq!(describe_visibility(&comment), "internal");
+        assert_eq!(
+            describe_visibility(&comment_payload(json!({}), None, false)),
+            ""
+        );
+    }
+}
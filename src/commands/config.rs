@@ -1,29 +1,50 @@
-use crate::config::{mask_token, Config, FileConfig};
+use crate::client::AtlassianClient;
+use crate::config::{
+    mask_token, AuthConfig, AuthMode, Config, ConfigSource, Deployment, FileConfig, ProfileConfig,
+    TokenSpec,
+};
+use crate::error::{Error, ErrorKind};
+use crate::input;
 use owo_colors::OwoColorize;
+use serde_json::Value;
+use std::io::Write;
 use std::path::PathBuf;
 
-pub async fn run_show(global: bool, local: bool) -> Result<(), String> {
+/// Projects listed by `config init` before asking for a key instead.
+const MAX_LISTED_PROJECTS: usize = 30;
+
+pub async fn run_show(global: bool, local: bool, profile: Option<String>) -> Result<(), String> {
     if global {
         let path = Config::global_config_path().ok_or("Could not determine global config path")?;
-        show_file("Global", &path)?;
+        show_file("Global", &path, profile.as_deref())?;
     } else if local {
         let path = Config::local_config_path();
-        show_file("Local", &path)?;
+        show_file("Local", &path, profile.as_deref())?;
     } else {
         // Show effective config and source
-        let config = Config::load()?;
+        let config = Config::load(profile.as_deref())?;
         println!(
             "{} (from {})",
             "Effective Configuration".yellow().bold(),
             config.source.to_string().cyan()
         );
         println!("--------------------------------------------------");
+        println!(
+            "{} {}",
+            "Profile:".cyan().bold(),
+            config.profile.as_deref().unwrap_or("(none)")
+        );
         println!("{} {}", "Username:".cyan().bold(), config.user);
         println!("{} {}", "Site:".cyan().bold(), config.site);
+        if let Some(confluence_site) = &config.confluence_site {
+            println!("{} {}", "Confluence:".cyan().bold(), confluence_site);
+        }
+        println!("{} {}", "Deployment:".cyan().bold(), config.deployment);
+        println!("{} {}", "Auth mode:".cyan().bold(), config.auth_mode);
         println!(
             "{} {}",
             "Token:".cyan().bold(),
-            mask_token(&config.token).dimmed()
+            config.describe_credentials().dimmed()
         );
         println!(
             "{} {}",
@@ -58,7 +79,8 @@ pub async fn run_show(global: bool, local: bool) -> Result<(), String> {
     Ok(())
 }
 
-fn show_file(label: &str, path: &PathBuf) -> Result<(), String> {
+/// Print a config file. With `profile`, only that profile's table is shown.
+fn show_file(label: &str, path: &PathBuf, profile: Option<&str>) -> Result<(), String> {
     if !path.exists() {
         return Err(format!(
             "{} config file not found at {}",
@@ -75,74 +97,219 @@ fn show_file(label: &str, path: &PathBuf) -> Result<(), String> {
     );
     println!("--------------------------------------------------");
 
+    if let Some(name) = profile {
+        let profile = file_config
+            .profiles
+            .get(name)
+            .ok_or_else(|| format!("Profile '{}' not found in {}", name, path.display()))?;
+        show_profile(name, profile);
+        return Ok(());
+    }
+
+    if let Some(default_profile) = &file_config.default_profile {
+        println!(
+            "{} {}\n",
+            "default_profile =".cyan().bold(),
+            default_profile
+        );
+    }
+
     println!("{}", "[auth]".cyan().bold());
+    show_auth(&file_config.auth);
+
+    if let Some(general) = file_config.general {
+        println!("\n{}", "[general]".cyan().bold());
+        println!(
+            "  {} {}",
+            "default_project =".cyan().bold(),
+            general.default_project.as_deref().unwrap_or("")
+        );
+    }
+
+    if let Some(retry) = file_config.retry {
+        println!("\n{}", "[retry]".cyan().bold());
+        let entries = [
+            (
+                "max_retries         =",
+                retry.max_retries.map(|v| v.to_string()),
+            ),
+            (
+                "base_delay_ms       =",
+                retry.base_delay_ms.map(|v| v.to_string()),
+            ),
+            (
+                "max_delay_ms        =",
+                retry.max_delay_ms.map(|v| v.to_string()),
+            ),
+            (
+                "max_total_wait_secs =",
+                retry.max_total_wait_secs.map(|v| v.to_string()),
+            ),
+        ];
+        for (label, value) in entries {
+            if let Some(value) = value {
+                println!("  {} {}", label.cyan().bold(), value);
+            }
+        }
+    }
+
+    for (name, profile) in &file_config.profiles {
+        println!();
+        show_profile(name, profile);
+    }
+
+    Ok(())
+}
+
+fn show_profile(name: &str, profile: &ProfileConfig) {
+    println!("{}", format!("[profiles.{}]", name).cyan().bold());
+    show_auth(&profile.auth);
+    if let Some(project) = &profile.default_project {
+        println!("  {} {}", "default_project =".cyan().bold(), project);
+    }
+}
+
+fn show_auth(auth: &AuthConfig) {
     println!(
         "  {} {}",
         "username =".cyan().bold(),
-        file_config.auth.username.as_deref().unwrap_or("")
+        auth.username.as_deref().unwrap_or("")
     );
     println!(
         "  {} {}",
         "site     =".cyan().bold(),
-        file_config.auth.site.as_deref().unwrap_or("")
+        auth.site.as_deref().unwrap_or("")
     );
     println!(
         "  {} {}",
         "token    =".cyan().bold(),
-        file_config
-            .auth
-            .token
-            .as_ref()
-            .map(|t| mask_token(t).dimmed().to_string())
-            .unwrap_or_default()
+        auth.describe_token().dimmed()
     );
-
-    if let Some(general) = file_config.general {
-        println!("\n{}", "[general]".cyan().bold());
+    if let Some(mode) = auth.mode {
+        println!("  {} {}", "mode     =".cyan().bold(), mode);
+    }
+    if let Some(deployment) = auth.deployment {
+        println!("  {} {}", "deployment =".cyan().bold(), deployment);
+    }
+    if let Some(confluence_site) = &auth.confluence_site {
         println!(
             "  {} {}",
-            "default_project =".cyan().bold(),
-            general.default_project.as_deref().unwrap_or("")
+            "confluence_site =".cyan().bold(),
+            confluence_site
         );
     }
-
-    Ok(())
+    if let Some(client_id) = &auth.client_id {
+        println!("  {} {}", "client_id =".cyan().bold(), client_id);
+    }
+    if let Some(secret) = &auth.client_secret {
+        println!(
+            "  {} {}",
+            "client_secret =".cyan().bold(),
+            mask_token(secret).dimmed()
+        );
+    }
+    if let Some(oauth_url) = &auth.oauth_url {
+        println!("  {} {}", "oauth_url =".cyan().bold(), oauth_url);
+    }
+    if let Some(api_url) = &auth.api_url {
+        println!("  {} {}", "api_url =".cyan().bold(), api_url);
+    }
 }
 
-pub async fn run_set(key: String, value: String, global: bool, local: bool) -> Result<(), String> {
+pub async fn run_set(
+    key: String,
+    value: String,
+    global: bool,
+    local: bool,
+    profile: Option<String>,
+) -> Result<(), String> {
     let path = if global {
         Config::global_config_path().ok_or("Could not determine global config path")?
     } else if local {
         Config::local_config_path()
     } else {
-        // Default to local if it exists, otherwise global?
-        // Actually, let's follow git's lead: you must specify or it defaults to one.
-        // Let's default to global for convenience if nothing specified, or local if jiri.toml exists.
-        if Config::local_config_path().exists() {
-            Config::local_config_path()
+        // Without a flag, prefer the project's jiri.toml when there is one, and say so: the
+        // file that changes should never be a surprise.
+        let (path, reason) = if Config::local_config_path().exists() {
+            (Config::local_config_path(), "jiri.toml exists here")
         } else {
-            Config::global_config_path().ok_or("Could not determine global config path")?
-        }
+            (
+                Config::global_config_path().ok_or("Could not determine global config path")?,
+                "no jiri.toml here",
+            )
+        };
+        eprintln!(
+            "{} writing to {} ({}); pass --global or --local to choose",
+            "note:".yellow().bold(),
+            path.display(),
+            reason
+        );
+        path
     };
 
     let mut file_config = FileConfig::load_path(&path)?;
 
+    if let Some(name) = &profile {
+        set_profile_value(&mut file_config, name, &key, value)?;
+        file_config.save_path(&path)?;
+        println!(
+            "{} {} for profile {} in {}",
+            "Successfully set".green().bold(),
+            key.cyan().bold(),
+            name.cyan().bold(),
+            path.display().to_string().dimmed()
+        );
+        return Ok(());
+    }
+
     match key.as_str() {
         "auth.username" | "username" | "user" => {
             file_config.auth.username = Some(value);
         }
-        "auth.token" | "token" => {
-            file_config.auth.token = Some(value);
+        "auth.token" | "token" => file_config.auth.set_token(TokenSpec::Plain(value)),
+        "auth.token_command" | "token_command" => {
+            file_config.auth.set_token(TokenSpec::Command(value))
         }
+        "auth.token_file" | "token_file" => file_config.auth.set_token(TokenSpec::File(value)),
         "auth.site" | "site" => {
             file_config.auth.site = Some(value);
         }
+        "auth.mode" | "mode" => file_config.auth.mode = Some(value.parse()?),
+        "auth.deployment" | "deployment" => file_config.auth.deployment = Some(value.parse()?),
+        "auth.confluence_site" | "confluence_site" => {
+            file_config.auth.confluence_site = Some(value)
+        }
+        "auth.client_id" | "client_id" => file_config.auth.client_id = Some(value),
+        "auth.client_secret" | "client_secret" => file_config.auth.client_secret = Some(value),
+        "auth.oauth_url" | "oauth_url" => file_config.auth.oauth_url = Some(value),
+        "auth.api_url" | "api_url" => file_config.auth.api_url = Some(value),
+        "default_profile" => {
+            file_config.default_profile = Some(value);
+        }
         "general.default_project" | "project" | "default_project" => {
             if file_config.general.is_none() {
                 file_config.general = Some(Default::default());
             }
             file_config.general.as_mut().unwrap().default_project = Some(value);
         }
+        "retry.max_retries"
+        | "retry.base_delay_ms"
+        | "retry.max_delay_ms"
+        | "retry.max_total_wait_secs" => {
+            let retry = file_config.retry.get_or_insert_with(Default::default);
+            let parsed = value
+                .parse::<u64>()
+                .map_err(|_| format!("{} must be a non-negative integer", key))?;
+            match key.as_str() {
+                "retry.max_retries" => {
+                    retry.max_retries =
+                        Some(u32::try_from(parsed).map_err(|_| format!("{} is too large", key))?)
+                }
+                "retry.base_delay_ms" => retry.base_delay_ms = Some(parsed),
+                "retry.max_delay_ms" => retry.max_delay_ms = Some(parsed),
+                _ => retry.max_total_wait_secs = Some(parsed),
+            }
+        }
         _ => return Err(format!("Unknown configuration key: {}", key)),
     }
 
@@ -156,3 +323,401 @@ pub async fn run_set(key: String, value: String, global: bool, local: bool) -> R
 
     Ok(())
 }
+
+/// Set a key in `[profiles.<name>]`, creating the table if needed. Only auth keys and the
+/// default project can differ per profile.
+fn set_profile_value(
+    file_config: &mut FileConfig,
+    name: &str,
+    key: &str,
+    value: String,
+) -> Result<(), String> {
+    let profile = file_config.profiles.entry(name.to_string()).or_default();
+    match key {
+        "auth.username" | "username" | "user" => profile.auth.username = Some(value),
+        "auth.token" | "token" => profile.auth.set_token(TokenSpec::Plain(value)),
+        "auth.token_command" | "token_command" => profile.auth.set_token(TokenSpec::Command(value)),
+        "auth.token_file" | "token_file" => profile.auth.set_token(TokenSpec::File(value)),
+        "auth.site" | "site" => profile.auth.site = Some(value),
+        "auth.mode" | "mode" => profile.auth.mode = Some(value.parse()?),
+        "auth.deployment" | "deployment" => profile.auth.deployment = Some(value.parse()?),
+        "auth.confluence_site" | "confluence_site" => profile.auth.confluence_site = Some(value),
+        "auth.client_id" | "client_id" => profile.auth.client_id = Some(value),
+        "auth.client_secret" | "client_secret" => profile.auth.client_secret = Some(value),
+        "auth.oauth_url" | "oauth_url" => profile.auth.oauth_url = Some(value),
+        "auth.api_url" | "api_url" => profile.auth.api_url = Some(value),
+        "general.default_project" | "project" | "default_project" => {
+            profile.default_project = Some(value)
+        }
+        _ => {
+            return Err(format!(
+                "Cannot set {} per profile. Profiles hold auth.username, auth.site, \
+                 auth.token (or auth.token_command, auth.token_file), auth.mode, \
+                 auth.deployment, auth.confluence_site, the OAuth keys (auth.client_id, \
+                 auth.client_secret, auth.oauth_url, auth.api_url) and general.default_project",
+                key
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Interactively set up credentials: prompt for site, email and API token, verify them
+/// against the site, offer a default project, then write the global (or, with `local`, the
+/// project) config file. With `profile`, the answers go into that profile's table.
+///
+/// An existing `token_command` or `token_file` is kept and used for the check instead of
+/// asking for a token. A table set up for Data Center, a personal access token or OAuth is
+/// only replaced after asking.
+pub async fn run_init(local: bool, profile: Option<String>) -> Result<(), Error> {
+    if !input::is_interactive() {
+        return Err(
+            "`jiri config init` asks questions and needs a terminal. In scripts, use \
+                    `jiri config set` or the JIRA_* environment variables"
+                .into(),
+        );
+    }
+    let path = if local {
+        Config::local_config_path()
+    } else {
+        Config::global_config_path().ok_or("Could not determine global config path")?
+    };
+    let mut file_config = FileConfig::load_path(&path)?;
+    let existing = match &profile {
+        Some(name) => file_config.profiles.get(name).map(|p| &p.auth),
+        None => Some(&file_config.auth),
+    };
+    let mut site = existing.and_then(|a| a.site.clone()).unwrap_or_default();
+    let mut user = existing
+        .and_then(|a| a.username.clone())
+        .unwrap_or_default();
+    let mut token = String::new();
+    let other_setup = existing.map(other_setup_keys).unwrap_or_default();
+    let kept_token = match existing.map(|a| a.token_spec()).transpose()?.flatten() {
+        Some(spec @ (TokenSpec::Command(_) | TokenSpec::File(_))) if other_setup.is_empty() => {
+            Some(spec)
+        }
+        _ => None,
+    };
+
+    println!(
+        "{} {}{}",
+        "Setting up".yellow().bold(),
+        path.display(),
+        profile
+            .as_deref()
+            .map(|p| format!(", profile '{}'", p))
+            .unwrap_or_default()
+    );
+    if !other_setup.is_empty() {
+        let answer = input::prompt_line(&format!(
+            "{} it is set up with {}. Replace that with an email and API token on Cloud? [y/N] ",
+            "warning:".yellow().bold(),
+            other_setup.join(", ")
+        ))?;
+        if !matches!(answer.to_lowercase().as_str(), "y" | "yes") {
+            return Err("Setup aborted; nothing was written".into());
+        }
+    }
+    if kept_token.is_none() {
+        println!(
+            "{}\n",
+            "Create an API token at https://id.atlassian.com/manage-profile/security/api-tokens"
+                .dimmed()
+        );
+    }
+
+    let source = if local {
+        ConfigSource::LocalFile(path.clone(), profile.clone())
+    } else {
+        ConfigSource::GlobalFile(path.clone(), profile.clone())
+    };
+    let client = loop {
+        site = loop {
+            let answer = prompt_with_default("Site (e.g. your-org.atlassian.net)", &site)?;
+            match normalize_site(&answer) {
+                Ok(normalized) => break normalized,
+                Err(e) => println!("{} {}", "error:".red().bold(), e),
+            }
+        };
+        user = loop {
+            let answer = prompt_with_default("Email", &user)?;
+            if answer.contains('@') {
+                break answer;
+            }
+            println!(
+                "{} enter the email address of your Atlassian account",
+                "error:".red().bold()
+            );
+        };
+        let token_spec = match &kept_token {
+            Some(spec) => {
+                println!("API token: {}", spec.describe().dimmed());
+                spec.clone()
+            }
+            None => {
+                let label = if token.is_empty() {
+                    "API token: "
+                } else {
+                    "API token [keep]: "
+                };
+                let answer = input::prompt_secret(label)?;
+                if !answer.is_empty() {
+                    token = answer;
+                }
+                if token.is_empty() {
+                    println!("{} the API token is required", "error:".red().bold());
+                    continue;
+                }
+                TokenSpec::Plain(token.clone())
+            }
+        };
+
+        let mut config = Config::for_site(&site, AuthMode::Basic, Deployment::Cloud)
+            .with_credentials(&user, token_spec)?;
+        config.profile = profile.clone();
+        config.source = source.clone();
+        let client = AtlassianClient::new(config);
+        print!(
+            "{} {}... ",
+            "Verifying credentials with".cyan().bold(),
+            site
+        );
+        std::io::stdout().flush().ok();
+        match client.myself().await {
+            Ok(me) => {
+                println!("{}", "OK".green().bold());
+                println!(
+                    "{} {}",
+                    "Logged in as:".cyan().bold(),
+                    me["displayName"].as_str().unwrap_or(&user)
+                );
+                break client;
+            }
+            Err(e) => {
+                println!("{}", "FAILED".red().bold());
+                println!("{} {}", "error:".red().bold(), e);
+                if e.kind() == ErrorKind::Auth {
+                    println!("{} check the email and API token", "hint:".yellow());
+                }
+                let again = input::prompt_line("Try again? [Y/n] ")?;
+                if matches!(again.to_lowercase().as_str(), "n" | "no") {
+                    return Err("Setup aborted; nothing was written".into());
+                }
+            }
+        }
+    };
+
+    let projects = client.projects_all().await?;
+    let project = choose_project(&projects)?;
+
+    let auth = match &profile {
+        Some(name) => &mut file_config.profiles.entry(name.clone()).or_default().auth,
+        None => &mut file_config.auth,
+    };
+    auth.site = Some(site);
+    auth.username = Some(user);
+    if kept_token.is_none() {
+        auth.set_token(TokenSpec::Plain(token));
+    }
+    if !other_setup.is_empty() {
+        clear_other_setup(auth);
+    }
+    if let Some(project) = project {
+        match &profile {
+            Some(name) => {
+                file_config
+                    .profiles
+                    .entry(name.clone())
+                    .or_default()
+                    .default_project = Some(project)
+            }
+            None => {
+                file_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .default_project = Some(project)
+            }
+        }
+    }
+    file_config.save_path(&path)?;
+
+    println!(
+        "\n{} {}",
+        "Configuration written to".green().bold(),
+        path.display().to_string().cyan()
+    );
+    println!("Try {}", "jiri search \"assignee = currentUser()\"".bold());
+    Ok(())
+}
+
+/// The settings of `auth` for anything but an email and API token on Cloud.
+fn other_setup_keys(auth: &AuthConfig) -> Vec<&'static str> {
+    [
+        ("auth.mode", auth.mode.is_some_and(|m| m != AuthMode::Basic)),
+        (
+            "auth.deployment",
+            auth.deployment == Some(Deployment::DataCenter),
+        ),
+        ("auth.confluence_site", auth.confluence_site.is_some()),
+        ("auth.client_id", auth.client_id.is_some()),
+        ("auth.client_secret", auth.client_secret.is_some()),
+        ("auth.oauth_url", auth.oauth_url.is_some()),
+        ("auth.api_url", auth.api_url.is_some()),
+    ]
+    .into_iter()
+    .filter(|(_, set)| *set)
+    .map(|(key, _)| key)
+    .collect()
+}
+
+/// Remove the settings `other_setup_keys` looks at, leaving an email and API token table.
+fn clear_other_setup(auth: &mut AuthConfig) {
+    auth.mode = None;
+    auth.deployment = None;
+    auth.confluence_site = None;
+    auth.client_id = None;
+    auth.client_secret = None;
+    auth.oauth_url = None;
+    auth.api_url = None;
+}
+
+/// Prompt showing the current value in brackets; an empty answer keeps it.
+fn prompt_with_default(label: &str, current: &str) -> Result<String, Error> {
+    let answer = if current.is_empty() {
+        input::prompt_line(&format!("{}: ", label))?
+    } else {
+        input::prompt_line(&format!("{} [{}]: ", label, current))?
+    };
+    Ok(if answer.is_empty() {
+        current.to_string()
+    } else {
+        answer
+    })
+}
+
+/// Ask for a default project by number or key. Returns `None` when skipped.
+fn choose_project(projects: &[Value]) -> Result<Option<String>, Error> {
+    if projects.is_empty() {
+        return Ok(None);
+    }
+    let key = |p: &Value| p["key"].as_str().unwrap_or_default().to_string();
+    println!("\n{}", "Projects:".cyan().bold());
+    for (i, project) in projects.iter().take(MAX_LISTED_PROJECTS).enumerate() {
+        println!(
+            "  {:>2}. {:10} {}",
+            i + 1,
+            key(project),
+            project["name"].as_str().unwrap_or_default()
+        );
+    }
+    if projects.len() > MAX_LISTED_PROJECTS {
+        println!(
+            "  {}",
+            format!("... and {} more", projects.len() - MAX_LISTED_PROJECTS).dimmed()
+        );
+    }
+
+    loop {
+        let answer = input::prompt_line("Default project (number or key, Enter to skip): ")?;
+        if answer.is_empty() {
+            return Ok(None);
+        }
+        let chosen = match answer.parse::<usize>() {
+            Ok(n) => n.checked_sub(1).and_then(|i| projects.get(i)),
+            Err(_) => projects
+                .iter()
+                .find(|p| key(p).eq_ignore_ascii_case(&answer)),
+        };
+        match chosen {
+            Some(project) => return Ok(Some(key(project))),
+            None => println!("{} no project '{}'", "error:".red().bold(), answer),
+        }
+    }
+}
+
+/// Normalize a site as typed into a base URL: add `https://` when the scheme is missing,
+/// expand a bare name to `<name>.atlassian.net`, and drop trailing slashes. Paths on
+/// Atlassian Cloud sites (e.g. a pasted board URL) are dropped too.
+fn normalize_site(raw: &str) -> Result<String, String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Err("The site is required".to_string());
+    }
+    let (scheme, rest) = match raw.split_once("://") {
+        Some((scheme, rest)) => (scheme.to_lowercase(), rest),
+        None => ("https".to_string(), raw),
+    };
+    if scheme != "https" && scheme != "http" {
+        return Err(format!("Unsupported scheme '{}' in {}", scheme, raw));
+    }
+    let (host, path) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, ""),
+    };
+    let mut host = host.to_lowercase();
+    if host.is_empty() || host.contains(char::is_whitespace) {
+        return Err(format!("'{}' is not a valid site", raw));
+    }
+    if !host.contains('.') && !host.contains(':') && host != "localhost" {
+        host.push_str(".atlassian.net");
+    }
+    if host.ends_with(".atlassian.net") {
+        return Ok(format!("https://{}", host));
+    }
+    Ok(format!(
+        "{}://{}{}",
+        scheme,
+        host,
+        path.trim_end_matches('/')
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_site() {
+        let cases = [
+            ("acme.atlassian.net", "https://acme.atlassian.net"),
+            ("https://acme.atlassian.net/", "https://acme.atlassian.net"),
+            (" acme ", "https://acme.atlassian.net"),
+            (
+                "http://ACME.atlassian.net/jira/software/projects/X/boards/1",
+                "https://acme.atlassian.net",
+            ),
+            (
+                "https://jira.example.com/jira//",
+                "https://jira.example.com/jira",
+            ),
+            ("http://localhost:8080", "http://localhost:8080"),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(normalize_site(raw).unwrap(), expected, "{}", raw);
+        }
+        assert!(normalize_site("").is_err());
+        assert!(normalize_site("ftp://acme.atlassian.net").is_err());
+        assert!(normalize_site("https:///path").is_err());
+    }
+
+    #[test]
+    fn test_other_setup_keys() {
+        let mut auth = AuthConfig {
+            mode: Some(AuthMode::Basic),
+            deployment: Some(Deployment::Cloud),
+            ..Default::default()
+        };
+        assert!(other_setup_keys(&auth).is_empty());
+
+        auth.mode = Some(AuthMode::Bearer);
+        auth.deployment = Some(Deployment::DataCenter);
+        auth.confluence_site = Some("https://wiki.example.com".to_string());
+        assert_eq!(
+            other_setup_keys(&auth),
+            ["auth.mode", "auth.deployment", "auth.confluence_site"]
+        );
+        clear_other_setup(&mut auth);
+        assert!(other_setup_keys(&auth).is_empty());
+    }
+}
@@ -1,9 +1,13 @@
 use crate::adf;
 use crate::client::AtlassianClient;
-use crate::formatter::Formatter;
+use crate::error::{Error, ErrorKind};
+use crate::formatter::{DocumentFormat, Formatter};
+use crate::markup;
 use owo_colors::OwoColorize;
 use serde_json::Value;
 
+use super::attachments;
+
 /// Execute Confluence commands.
 pub async fn run_search(
     client: &AtlassianClient,
@@ -12,7 +16,7 @@ pub async fn run_search(
     space_id: Option<String>,
     limit: i64,
     is_cql: bool,
-) -> Result<(), String> {
+) -> Result<(), Error> {
     let cql = if is_cql {
         query.ok_or("Query is required when --cql is used")?
     } else {
@@ -57,7 +61,7 @@ pub async fn run_create(
     parent: Option<String>,
     content: Option<String>,
     is_adf: bool,
-) -> Result<(), String> {
+) -> Result<(), Error> {
     // 1. Resolve Space ID
     let space_id = client.get_space_id(&space).await?;
 
@@ -109,10 +113,33 @@ pub async fn run_create(
     Ok(())
 }
 
-pub async fn run_view(client: &AtlassianClient, id: String, raw: bool) -> Result<(), String> {
+pub async fn run_view(
+    client: &AtlassianClient,
+    id: String,
+    raw: bool,
+    format: DocumentFormat,
+) -> Result<(), Error> {
     let page = client.get_page(&id).await?;
 
     let title = page["title"].as_str().unwrap_or("(no title)");
+    // Data Center pages carry a storage format (XHTML) body instead of ADF
+    let storage = page["body"]["storage"]["value"].as_str();
+
+    if format == DocumentFormat::Markdown {
+        if let Some(storage) = storage {
+            println!("# {}\n", title);
+            print!("{}", markup::storage_to_text(storage));
+            return Ok(());
+        }
+        let adf_body: Value = match page["body"]["atlas_doc_format"]["value"].as_str() {
+            Some(s) => serde_json::from_str(s).map_err(|e| e.to_string())?,
+            None => Value::Null,
+        };
+        println!("# {}\n", title);
+        print!("{}", adf::to_markdown(&adf_body));
+        return Ok(());
+    }
+
     let space_id = page["spaceId"].as_str().unwrap_or("?");
     let version = page["version"]["number"].as_i64().unwrap_or(0);
 
@@ -129,6 +156,15 @@ pub async fn run_view(client: &AtlassianClient, id: String, raw: bool) -> Result
     println!("{}", "=".repeat(title.len()).dimmed());
     println!();
 
+    if let Some(storage) = storage {
+        if raw {
+            println!("{}", storage);
+        } else {
+            println!("{}", markup::storage_to_text(storage));
+        }
+        return Ok(());
+    }
+
     let adf_body_str = page["body"]["atlas_doc_format"]["value"]
         .as_str()
         .ok_or("No ADF body found")?;
@@ -143,6 +179,7 @@ pub async fn run_view(client: &AtlassianClient, id: String, raw: bool) -> Result
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn run_edit(
     client: &AtlassianClient,
     id: String,
@@ -157,7 +194,10 @@ pub async fn run_edit(
     new_title: Option<String>,
     is_adf: bool,
     minor: bool,
-) -> Result<(), String> {
+) -> Result<(), Error> {
+    if client.is_data_center() && anchor.is_some() {
+        return Err("Anchored edits need an ADF body and are only supported on Cloud".into());
+    }
     let mut retries = 3;
 
     loop {
@@ -172,98 +212,55 @@ pub async fn run_edit(
             .as_i64()
             .ok_or("No version found")?;
 
-        let adf_body_str = page["body"]["atlas_doc_format"]["value"]
-            .as_str()
-            .ok_or("No ADF body found")?;
-        let mut adf_body: Value = serde_json::from_str(adf_body_str).map_err(|e| e.to_string())?;
-
-        // 2. Modify
-        if let Some(ref content) = full {
-            if is_adf {
-                adf_body = serde_json::from_str(content)
-                    .map_err(|e| format!("Invalid ADF in --full: {}", e))?;
-            } else {
-                let nodes = adf::from_markdown(content);
-                adf_body = serde_json::json!({
-                    "type": "doc",
-                    "version": 1,
-                    "content": nodes
-                });
-            }
-        }
-
-        if let Some(ref content) = append {
-            let nodes = if is_adf {
-                serde_json::from_str(content)
-                    .map_err(|e| format!("Invalid ADF in --append: {}", e))?
-            } else {
-                adf::from_markdown(content)
-            };
-            adf::append_nodes(&mut adf_body, nodes);
-        }
-
-        if let Some(ref content) = prepend {
-            let nodes = if is_adf {
-                serde_json::from_str(content)
-                    .map_err(|e| format!("Invalid ADF in --prepend: {}", e))?
-            } else {
-                adf::from_markdown(content)
-            };
-            adf::prepend_nodes(&mut adf_body, nodes);
-        }
+        let title_to_use = new_title.clone().unwrap_or(current_title);
 
-        if let Some(ref r) = replace {
-            let parts: Vec<&str> = r.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                adf::replace_text(&mut adf_body, parts[0], parts[1]);
-            } else {
-                return Err("Replace format must be OLD:NEW".to_string());
+        // 2. Modify and update
+        let result = match page["body"]["storage"]["value"].as_str() {
+            Some(storage) => {
+                let storage = edit_storage(
+                    storage,
+                    full.as_deref(),
+                    append.as_deref(),
+                    prepend.as_deref(),
+                    replace.as_deref(),
+                    is_adf,
+                )?;
+                client
+                    .update_page_storage(
+                        &id,
+                        &title_to_use,
+                        &space_id,
+                        &storage,
+                        version + 1,
+                        minor,
+                    )
+                    .await
             }
-        }
-
-        // --- Anchored Edits ---
-        if let Some(ref selector) = anchor {
-            let index = adf::find_anchor_index(&adf_body, selector)?;
-            let content = doc_content_mut(&mut adf_body)?;
-
-            if let Some(ref val) = before {
-                let nodes = if is_adf {
-                    serde_json::from_str(val).map_err(|e| e.to_string())?
-                } else {
-                    adf::from_markdown(val)
-                };
-                for (i, node) in nodes.into_iter().enumerate() {
-                    content.insert(index + i, node);
-                }
-            } else if let Some(ref val) = after {
-                let nodes = if is_adf {
-                    serde_json::from_str(val).map_err(|e| e.to_string())?
-                } else {
-                    adf::from_markdown(val)
-                };
-                for (i, node) in nodes.into_iter().enumerate() {
-                    content.insert(index + 1 + i, node);
-                }
-            } else if let Some(ref val) = replace_node {
-                let nodes = if is_adf {
-                    serde_json::from_str(val).map_err(|e| e.to_string())?
-                } else {
-                    adf::from_markdown(val)
-                };
-                content.remove(index);
-                for (i, node) in nodes.into_iter().enumerate() {
-                    content.insert(index + i, node);
-                }
+            None => {
+                let adf_body_str = page["body"]["atlas_doc_format"]["value"]
+                    .as_str()
+                    .ok_or("No ADF body found")?;
+                let mut adf_body: Value =
+                    serde_json::from_str(adf_body_str).map_err(|e| e.to_string())?;
+                edit_adf(
+                    &mut adf_body,
+                    full.as_deref(),
+                    append.as_deref(),
+                    prepend.as_deref(),
+                    replace.as_deref(),
+                    anchor.as_deref(),
+                    before.as_deref(),
+                    after.as_deref(),
+                    replace_node.as_deref(),
+                    is_adf,
+                )?;
+                client
+                    .update_page(&id, &title_to_use, &space_id, &adf_body, version + 1, minor)
+                    .await
             }
-        }
+        };
 
-        let title_to_use = new_title.clone().unwrap_or(current_title);
-
-        // 3. Update
-        match client
-            .update_page(&id, &title_to_use, &space_id, &adf_body, version + 1, minor)
-            .await
-        {
+        match result {
             Ok(_) => {
                 println!(
                     "{} {}",
@@ -272,7 +269,7 @@ pub async fn run_edit(
                 );
                 return Ok(());
             }
-            Err(e) if e.contains("409") && retries > 0 => {
+            Err(e) if e.kind() == ErrorKind::Conflict && retries > 0 => {
                 eprintln!(
                     "{} version conflict, retrying ({} retries left)...",
                     "warning:".yellow().bold(),
@@ -287,12 +284,151 @@ pub async fn run_edit(
     }
 }
 
+/// Apply the body edits of `run_edit` to a page's ADF document.
+#[allow(clippy::too_many_arguments)]
+fn edit_adf(
+    adf_body: &mut Value,
+    full: Option<&str>,
+    append: Option<&str>,
+    prepend: Option<&str>,
+    replace: Option<&str>,
+    anchor: Option<&str>,
+    before: Option<&str>,
+    after: Option<&str>,
+    replace_node: Option<&str>,
+    is_adf: bool,
+) -> Result<(), Error> {
+    if let Some(content) = full {
+        if is_adf {
+            *adf_body = serde_json::from_str(content)
+                .map_err(|e| format!("Invalid ADF in --full: {}", e))?;
+        } else {
+            let nodes = adf::from_markdown(content);
+            *adf_body = serde_json::json!({
+                "type": "doc",
+                "version": 1,
+                "content": nodes
+            });
+        }
+    }
+
+    if let Some(content) = append {
+        let nodes = if is_adf {
+            serde_json::from_str(content).map_err(|e| format!("Invalid ADF in --append: {}", e))?
+        } else {
+            adf::from_markdown(content)
+        };
+        adf::append_nodes(adf_body, nodes);
+    }
+
+    if let Some(content) = prepend {
+        let nodes = if is_adf {
+            serde_json::from_str(content).map_err(|e| format!("Invalid ADF in --prepend: {}", e))?
+        } else {
+            adf::from_markdown(content)
+        };
+        adf::prepend_nodes(adf_body, nodes);
+    }
+
+    if let Some(r) = replace {
+        let parts: Vec<&str> = r.splitn(2, ':').collect();
+        if parts.len() == 2 {
+            adf::replace_text(adf_body, parts[0], parts[1]);
+        } else {
+            return Err("Replace format must be OLD:NEW".into());
+        }
+    }
+
+    // --- Anchored Edits ---
+    if let Some(selector) = anchor {
+        let index = adf::find_anchor_index(adf_body, selector)?;
+        let content = doc_content_mut(adf_body)?;
+
+        if let Some(val) = before {
+            let nodes = if is_adf {
+                serde_json::from_str(val).map_err(|e| e.to_string())?
+            } else {
+                adf::from_markdown(val)
+            };
+            for (i, node) in nodes.into_iter().enumerate() {
+                content.insert(index + i, node);
+            }
+        } else if let Some(val) = after {
+            let nodes = if is_adf {
+                serde_json::from_str(val).map_err(|e| e.to_string())?
+            } else {
+                adf::from_markdown(val)
+            };
+            for (i, node) in nodes.into_iter().enumerate() {
+                content.insert(index + 1 + i, node);
+            }
+        } else if let Some(val) = replace_node {
+            let nodes = if is_adf {
+                serde_json::from_str(val).map_err(|e| e.to_string())?
+            } else {
+                adf::from_markdown(val)
+            };
+            content.remove(index);
+            for (i, node) in nodes.into_iter().enumerate() {
+                content.insert(index + i, node);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Apply the body edits of `run_edit` to a storage format (Data Center) body. Markdown or
+/// ADF input is converted to storage format first.
+fn edit_storage(
+    storage: &str,
+    full: Option<&str>,
+    append: Option<&str>,
+    prepend: Option<&str>,
+    replace: Option<&str>,
+    is_adf: bool,
+) -> Result<String, Error> {
+    let to_storage = |content: &str, flag: &str| -> Result<String, Error> {
+        let nodes: Vec<Value> = if is_adf {
+            serde_json::from_str(content)
+                .map_err(|e| format!("Invalid ADF in --{}: {}", flag, e))?
+        } else {
+            adf::from_markdown(content)
+        };
+        Ok(markup::to_storage(&serde_json::json!({
+            "type": "doc",
+            "version": 1,
+            "content": nodes
+        })))
+    };
+
+    let mut storage = match full {
+        Some(content) if is_adf => {
+            let doc: Value = serde_json::from_str(content)
+                .map_err(|e| format!("Invalid ADF in --full: {}", e))?;
+            markup::to_storage(&doc)
+        }
+        Some(content) => to_storage(content, "full")?,
+        None => storage.to_string(),
+    };
+    if let Some(content) = append {
+        storage.push_str(&to_storage(content, "append")?);
+    }
+    if let Some(content) = prepend {
+        storage.insert_str(0, &to_storage(content, "prepend")?);
+    }
+    if let Some(r) = replace {
+        let (old, new) = r.split_once(':').ok_or("Replace format must be OLD:NEW")?;
+        storage = markup::replace_text(&storage, old, new);
+    }
+    Ok(storage)
+}
+
 pub async fn run_attach(
     client: &AtlassianClient,
     id: String,
     file_path: String,
     comment: Option<String>,
-) -> Result<(), String> {
+) -> Result<(), Error> {
     println!("Attaching {} to page {}...", file_path, id);
     let result = client.attach_to_page(&id, &file_path, comment).await?;
 
@@ -318,8 +454,99 @@ pub async fn run_attach(
     Ok(())
 }
 
-    fn doc_content_mut(doc: &mut Value) -> Result<&mut Vec<Value>, String> {
+/// List the attachments of a page.
+pub async fn run_attachments_list(
+    client: &AtlassianClient,
+    formatter: &Formatter,
+    id: String,
+) -> Result<(), Error> {
+    let attachments = client.page_attachments(&id).await?;
+
+    let mut rows = vec![vec![
+        "ID".to_string(),
+        "NAME".to_string(),
+        "SIZE".to_string(),
+        "AUTHOR".to_string(),
+        "CREATED".to_string(),
+    ]];
+    for a in &attachments {
+        rows.push(vec![
+            a["id"].as_str().unwrap_or_default().to_string(),
+            a["title"].as_str().unwrap_or_default().to_string(),
+            attachments::format_size(a["extensions"]["fileSize"].as_u64().unwrap_or(0)),
+            a["version"]["by"]["displayName"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            crate::dates::display_timestamp(a["version"]["when"].as_str().unwrap_or_default()),
+        ]);
+    }
+
+    println!("{}", formatter.render(rows));
+    Ok(())
+}
+
+/// Download the attachments of a page whose name matches `pattern` (all when omitted).
+pub async fn run_attachments_download(
+    client: &AtlassianClient,
+    id: String,
+    pattern: Option<String>,
+    out_dir: Option<String>,
+    force: bool,
+) -> Result<(), Error> {
+    let all = client.page_attachments(&id).await?;
+    let selected = attachments::select(&all, "title", pattern.as_deref());
+    if selected.is_empty() {
+        return Err(attachments::no_match(&format!("page {}", id), pattern.as_deref()).into());
+    }
+
+    let downloads = selected
+        .into_iter()
+        .map(|a| attachments::Download {
+            id: a["id"].as_str().unwrap_or_default().to_string(),
+            name: a["title"].as_str().unwrap_or_default().to_string(),
+            url: client
+                .confluence_download_url(a["_links"]["download"].as_str().unwrap_or_default()),
+        })
+        .collect();
+    attachments::download_all(client, downloads, out_dir.as_deref(), force).await
+}
+
+/// Delete attachments of a page by ID and/or name pattern.
+pub async fn run_attachments_delete(
+    client: &AtlassianClient,
+    id: String,
+    ids: Vec<String>,
+    pattern: Option<String>,
+) -> Result<(), Error> {
+    let all = client.page_attachments(&id).await?;
+    let selected: Vec<&Value> = all
+        .iter()
+        .filter(|a| {
+            ids.iter().any(|i| a["id"] == i.as_str())
+                || pattern.as_deref().is_some_and(|p| {
+                    attachments::glob_match(p, a["title"].as_str().unwrap_or_default())
+                })
+        })
+        .collect();
+    if selected.is_empty() {
+        return Err(attachments::no_match(&format!("page {}", id), pattern.as_deref()).into());
+    }
+
+    for a in selected {
+        let attachment_id = a["id"].as_str().unwrap_or_default();
+        client.delete_page_attachment(attachment_id).await?;
+        println!(
+            "{} {} {}",
+            "Deleted".green().bold(),
+            a["title"].as_str().unwrap_or("?").bold(),
+            format!("({})", attachment_id).dimmed()
+        );
+    }
+    Ok(())
+}
 
+fn doc_content_mut(doc: &mut Value) -> Result<&mut Vec<Value>, String> {
     doc.get_mut("content")
         .and_then(|c| c.as_array_mut())
         .ok_or_else(|| "Invalid ADF: missing content array".to_string())
@@ -1,16 +1,103 @@
+use crate::adf;
 use crate::client::AtlassianClient;
+use crate::error::Error;
+use crate::fields;
+use crate::input;
 use owo_colors::OwoColorize;
+use serde_json::Value;
 
 /// Execute the create command to create a new issue.
+///
+/// The issue type and required fields are checked against the project's create metadata
+/// before anything is submitted (and before an editor is opened, so no typing is lost).
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
     client: &AtlassianClient,
     project: String,
     summary: String,
     issue_type: String,
     description: Option<String>,
-) -> Result<(), String> {
+    parent: Option<String>,
+    field_args: Vec<String>,
+    plain: bool,
+) -> Result<(), Error> {
+    let issue_types = client.create_meta_issue_types(&project).await?;
+    let Some(issue_type) = find_issue_type(&issue_types, &issue_type) else {
+        let available: Vec<&str> = issue_types
+            .iter()
+            .filter_map(|t| t["name"].as_str())
+            .collect();
+        return Err(format!(
+            "Issue type '{}' is not available in project {}. Available types: {}",
+            issue_type,
+            project,
+            available.join(", ")
+        )
+        .into());
+    };
+    let type_id = issue_type["id"].as_str().unwrap_or_default().to_string();
+    let type_name = issue_type["name"].as_str().unwrap_or("?").to_string();
+    if issue_type["subtask"] == true && parent.is_none() {
+        return Err(format!(
+            "'{}' is a subtask type; pass the parent issue with --parent",
+            type_name
+        )
+        .into());
+    }
+    let meta_fields = client.create_meta_fields(&project, &type_id).await?;
+
+    let mut extra_fields = super::edit::resolve_field_args(client, &field_args).await?;
+    if let Some(parent) = &parent {
+        extra_fields.insert("parent".to_string(), serde_json::json!({ "key": parent }));
+    }
+
+    let mut provided: Vec<String> = ["project", "issuetype", "summary"]
+        .iter()
+        .map(|s| s.to_string())
+        .chain(extra_fields.keys().cloned())
+        .collect();
+    // Without --description, an interactive run asks for it in the editor below
+    if description.is_some() || input::is_interactive() {
+        provided.push("description".to_string());
+    }
+
+    let missing = missing_required_fields(&meta_fields, &provided);
+    if !missing.is_empty() {
+        if !input::is_interactive() {
+            return Err(missing_fields_error(&project, &type_name, &missing).into());
+        }
+        println!(
+            "{} {} {} {}:",
+            "Required fields for".yellow().bold(),
+            type_name.bold(),
+            "in".yellow().bold(),
+            project.bold()
+        );
+        for field in missing {
+            let value = prompt_field(client, field).await?;
+            extra_fields.insert(field_id(field).to_string(), value);
+        }
+    }
+
+    let description = match description {
+        Some(text) => Some(text),
+        None if input::is_interactive() => {
+            let text = input::compose(
+                "description",
+                "",
+                &[
+                    &format!("Write the description for \"{}\" in Markdown.", summary),
+                    "Lines like these are removed. Save an empty file to cancel.",
+                ],
+            )?;
+            Some(text.ok_or("Aborted: empty description")?)
+        }
+        None => None,
+    };
+
+    let description = description.map(|text| adf::from_text(&text, plain));
     let result = client
-        .create_issue(&project, &summary, &issue_type, description.as_deref())
+        .create_issue(&project, &summary, &type_id, description, extra_fields)
         .await?;
 
     let key = result["key"].as_str().unwrap_or("?");
@@ -25,3 +112,195 @@ pub async fn run(
 
     Ok(())
 }
+
+pub(crate) fn field_id(field: &Value) -> &str {
+    field["fieldId"]
+        .as_str()
+        .or_else(|| field["key"].as_str())
+        .unwrap_or_default()
+}
+
+/// Find an issue type by ID or case-insensitive name.
+fn find_issue_type<'a>(issue_types: &'a [Value], name: &str) -> Option<&'a Value> {
+    issue_types.iter().find(|t| {
+        t["id"].as_str() == Some(name)
+            || t["name"]
+                .as_str()
+                .is_some_and(|n| n.eq_ignore_ascii_case(name))
+    })
+}
+
+/// Required fields without a default value that were not provided.
+pub(crate) fn missing_required_fields<'a>(
+    meta_fields: &'a [Value],
+    provided: &[String],
+) -> Vec<&'a Value> {
+    meta_fields
+        .iter()
+        .filter(|f| f["required"] == true && f["hasDefaultValue"] != true)
+        .filter(|f| !provided.iter().any(|p| p == field_id(f)))
+        .collect()
+}
+
+/// Display names of a field's allowed values (options, components, versions, ...).
+fn allowed_values(field: &Value) -> Vec<String> {
+    field["allowedValues"]
+        .as_array()
+        .map(|values| {
+            values
+                .iter()
+                .map(fields::normalize_value)
+                .filter(|v| !v.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn describe_field(field: &Value) -> String {
+    let name = field["name"].as_str().unwrap_or("?");
+    let mut text = format!("{} ({})", name, field_id(field));
+    let allowed = allowed_values(field);
+    if !allowed.is_empty() {
+        let shown: Vec<&str> = allowed.iter().take(15).map(|s| s.as_str()).collect();
+        let more = if allowed.len() > shown.len() {
+            format!(", ... ({} more)", allowed.len() - shown.len())
+        } else {
+            String::new()
+        };
+        text.push_str(&format!(": {}{}", shown.join(", "), more));
+    }
+    text
+}
+
+fn missing_fields_error(project: &str, issue_type: &str, missing: &[&Value]) -> String {
+    let mut message = format!("Missing required fields for {} in {}:", issue_type, project);
+    for field in missing {
+        message.push_str(&format!("\n  {}", describe_field(field)));
+    }
+    message.push_str("\nSet them with --field \"NAME=VALUE\".");
+    message
+}
+
+/// Match each comma-separated part of `raw` (or all of it, for single-valued fields)
+/// against the allowed values, returning the canonical spelling.
+pub(crate) fn match_allowed(field: &Value, raw: &str) -> Result<String, String> {
+    let allowed = allowed_values(field);
+    if allowed.is_empty() || field["schema"]["type"] == "option-with-child" {
+        return Ok(raw.to_string());
+    }
+    let parts: Vec<&str> = if field["schema"]["type"] == "array" {
+        raw.split(',').map(|s| s.trim()).collect()
+    } else {
+        vec![raw]
+    };
+    parts
+        .into_iter()
+        .map(|part| {
+            allowed
+                .iter()
+                .find(|a| a.eq_ignore_ascii_case(part))
+                .cloned()
+                .ok_or_else(|| format!("'{}' is not one of: {}", part, allowed.join(", ")))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|values| values.join(","))
+}
+
+/// Prompt until the user enters a valid value for a required field. An empty answer aborts.
+async fn prompt_field(client: &AtlassianClient, field: &Value) -> Result<Value, Error> {
+    let schema = &field["schema"];
+    println!("  {}", describe_field(field).dimmed());
+    loop {
+        let name = field["name"].as_str().unwrap_or("?");
+        let raw = input::prompt_line(&format!("  {}: ", name.cyan().bold()))?;
+        if raw.is_empty() {
+            return Err(format!("Aborted: no value given for required field '{}'", name).into());
+        }
+
+        let raw = if fields::is_user_schema(schema) {
+            match super::edit::resolve_account_ids(client, &raw).await {
+                Ok(raw) => raw,
+                Err(e) => {
+                    println!("  {}", e.to_string().red());
+                    continue;
+                }
+            }
+        } else {
+            match match_allowed(field, &raw) {
+                Ok(raw) => raw,
+                Err(e) => {
+                    println!("  {}", e.red());
+                    continue;
+                }
+            }
+        };
+
+        match fields::coerce_value(schema, &raw) {
+            Ok(value) => return Ok(value),
+            Err(e) => println!("  {}", e.red()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn meta_fields() -> Vec<Value> {
+        vec![
+            json!({ "fieldId": "summary", "name": "Summary", "required": true, "schema": { "type": "string" } }),
+            json!({ "fieldId": "reporter", "name": "Reporter", "required": true, "hasDefaultValue": true, "schema": { "type": "user" } }),
+            json!({ "fieldId": "labels", "name": "Labels", "required": false, "schema": { "type": "array", "items": "string" } }),
+            json!({
+                "fieldId": "customfield_10050",
+                "name": "Severity",
+                "required": true,
+                "schema": { "type": "option" },
+                "allowedValues": [{ "id": "1", "value": "Low" }, { "id": "2", "value": "High" }]
+            }),
+            json!({
+                "fieldId": "components",
+                "name": "Components",
+                "required": true,
+                "schema": { "type": "array", "items": "component" },
+                "allowedValues": [{ "id": "10", "name": "Backend" }, { "id": "11", "name": "API" }]
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_find_issue_type() {
+        let types = vec![
+            json!({ "id": "10001", "name": "Task" }),
+            json!({ "id": "10002", "name": "Bug" }),
+        ];
+        assert_eq!(find_issue_type(&types, "bug").unwrap()["id"], "10002");
+        assert_eq!(find_issue_type(&types, "10001").unwrap()["name"], "Task");
+        assert!(find_issue_type(&types, "Epic").is_none());
+    }
+
+    #[test]
+    fn test_missing_required_fields() {
+        let fields = meta_fields();
+        let provided = vec!["summary".to_string(), "components".to_string()];
+        let missing = missing_required_fields(&fields, &provided);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(field_id(missing[0]), "customfield_10050");
+
+        let error = missing_fields_error("PROJ", "Bug", &missing);
+        assert!(error.contains("Severity (customfield_10050): Low, High"));
+    }
+
+    #[test]
+    fn test_match_allowed() {
+        let fields = meta_fields();
+        assert_eq!(match_allowed(&fields[3], "high").unwrap(), "High");
+        assert!(match_allowed(&fields[3], "Critical").is_err());
+        assert_eq!(
+            match_allowed(&fields[4], "api, backend").unwrap(),
+            "API,Backend"
+        );
+        assert_eq!(match_allowed(&fields[2], "anything").unwrap(), "anything");
+    }
+}
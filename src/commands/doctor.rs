@@ -1,11 +1,12 @@
 use crate::client::AtlassianClient;
-use crate::config::{mask_token, Config};
+use crate::config::{mask_token, Config, FileConfig};
+use crate::error::{Error, ErrorKind};
 use owo_colors::OwoColorize;
 use std::env;
 use std::path::PathBuf;
 
 /// Diagnostic tool to check configuration and connectivity.
-pub async fn run(client: &AtlassianClient) -> Result<(), String> {
+pub async fn run(client: &AtlassianClient) -> Result<(), Error> {
     println!("{}", "Jiri Doctor - Diagnostic Information".yellow().bold());
     println!("====================================");
 
@@ -15,12 +16,21 @@ pub async fn run(client: &AtlassianClient) -> Result<(), String> {
         "Active source:".cyan().bold(),
         client.config().source.to_string().cyan()
     );
+    println!(
+        "{} {}",
+        "Active profile:".cyan().bold(),
+        client.config().profile.as_deref().unwrap_or("(none)")
+    );
 
     println!("\n{}", "[Environment Variables]".blue().bold());
     check_env("JIRA_API_USERNAME");
     check_env("JIRA_API_TOKEN");
     check_env("JIRA_SITE");
+    check_env("JIRA_AUTH_MODE");
+    check_env("JIRA_DEPLOYMENT");
+    check_env("JIRA_CONFLUENCE_SITE");
     check_env("JIRA_DEFAULT_PROJECT");
+    check_env("JIRI_PROFILE");
 
     println!("\n{}", "[Configuration Files]".blue().bold());
     check_config_file("Local (jiri.toml)", PathBuf::from("jiri.toml"));
@@ -31,10 +41,23 @@ pub async fn run(client: &AtlassianClient) -> Result<(), String> {
     println!("\n{}", "[Effective Configuration]".blue().bold());
     println!("{} {}", "Username:".cyan().bold(), client.config().user);
     println!("{} {}", "Site:".cyan().bold(), client.config().site);
+    if let Some(confluence_site) = &client.config().confluence_site {
+        println!("{} {}", "Confluence:".cyan().bold(), confluence_site);
+    }
+    println!(
+        "{} {}",
+        "Deployment:".cyan().bold(),
+        client.config().deployment
+    );
+    println!(
+        "{} {}",
+        "Auth mode:".cyan().bold(),
+        client.config().auth_mode
+    );
     println!(
         "{} {}",
         "Token:".cyan().bold(),
-        mask_token(&client.config().token).dimmed()
+        client.config().describe_credentials().dimmed()
     );
     println!(
         "{} {}",
@@ -65,6 +88,14 @@ pub async fn run(client: &AtlassianClient) -> Result<(), String> {
         Err(e) => {
             println!("{}", "FAILED".red().bold());
             println!("{} {}", "Error:".red().bold(), e);
+            if e.kind() == ErrorKind::Auth {
+                println!(
+                    "{} check the credentials ({} auth) for {}",
+                    "hint:".yellow(),
+                    client.config().auth_mode,
+                    client.config().site
+                );
+            }
         }
     }
 
@@ -100,6 +131,21 @@ fn check_config_file(label: &str, path: PathBuf) {
             "FOUND".green().bold(),
             path.display().to_string().cyan()
         );
+        match FileConfig::load_path(&path) {
+            Ok(file) if !file.profiles.is_empty() => {
+                let names: Vec<String> = file
+                    .profiles
+                    .keys()
+                    .map(|name| match &file.default_profile {
+                        Some(default) if default == name => format!("{} (default)", name),
+                        _ => name.clone(),
+                    })
+                    .collect();
+                println!("  {:20} : {}", "  profiles", names.join(", "));
+            }
+            Ok(_) => {}
+            Err(e) => println!("  {:20} : {} {}", "", "INVALID".red().bold(), e),
+        }
     } else {
         println!("  {:20} : {}", label, "NOT FOUND".red().bold());
     }
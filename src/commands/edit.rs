@@ -1,37 +1,55 @@
+use crate::adf;
 use crate::client::AtlassianClient;
+use crate::error::Error;
+use crate::fields;
+use crate::input;
 use owo_colors::OwoColorize;
 use serde_json::Value;
 
 /// Execute the edit command to update issue fields.
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
     client: &AtlassianClient,
     key: String,
     summary: Option<String>,
-    description: Option<String>,
+    description: Option<Option<String>>,
     labels: Option<String>,
     assignee: Option<String>,
-) -> Result<(), String> {
+    field_args: Vec<String>,
+    plain: bool,
+) -> Result<(), Error> {
     let mut fields = serde_json::Map::new();
 
     if let Some(summary) = summary {
         fields.insert("summary".to_string(), Value::String(summary));
     }
 
+    let mut description_unchanged = false;
     if let Some(description) = description {
-        fields.insert(
-            "description".to_string(),
-            crate::adf::from_plain_text(&description),
-        );
+        let body = match description {
+            Some(text) => Some(adf::from_text(&text, plain)),
+            None => {
+                let issue = client.get_issue(&key).await?;
+                compose_body(
+                    client,
+                    "description",
+                    &issue["fields"]["description"],
+                    &format!("the description of {}", key),
+                    plain,
+                )?
+            }
+        };
+        match body {
+            Some(body) => {
+                fields.insert("description".to_string(), body);
+            }
+            None => description_unchanged = true,
+        }
     }
 
+    let mut update = serde_json::Map::new();
     if let Some(labels) = labels {
-        let labels = labels
-            .split(',')
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-            .map(|s| Value::String(s.to_string()))
-            .collect::<Vec<_>>();
-        fields.insert("labels".to_string(), Value::Array(labels));
+        label_edits(&labels, &mut fields, &mut update)?;
     }
 
     if let Some(assignee_query) = assignee {
@@ -42,22 +60,194 @@ pub async fn run(
         );
     }
 
-    if fields.is_empty() {
+    let (extra_fields, extra_update) = resolve_field_edits(client, &field_args).await?;
+    fields.extend(extra_fields);
+    update.extend(extra_update);
+
+    if fields.is_empty() && update.is_empty() {
+        if description_unchanged {
+            println!(
+                "{} {}",
+                "Description unchanged:".yellow().bold(),
+                key.cyan().bold()
+            );
+            return Ok(());
+        }
         return Err(
-            "No fields provided. Use --summary, --description, --labels, or --assignee."
-                .to_string(),
+            "No fields provided. Use --summary, --description, --labels, --assignee, or --field."
+                .into(),
         );
     }
 
-    client.update_issue(&key, Value::Object(fields)).await?;
+    client
+        .update_issue(&key, Value::Object(fields), update_payload(update))
+        .await?;
     println!("{} {}", "Updated issue:".green().bold(), key.cyan().bold());
     Ok(())
 }
 
+/// Add a `--labels` value to an edit: `+label`/`-label` items become `update` operations that
+/// keep labels other people added, and plain items replace the whole set.
+pub(crate) fn label_edits(
+    labels: &str,
+    fields: &mut serde_json::Map<String, Value>,
+    update: &mut serde_json::Map<String, Value>,
+) -> Result<(), String> {
+    let schema = serde_json::json!({ "type": "array", "items": "string", "system": "labels" });
+    match fields::update_ops(&schema, labels)? {
+        Some(ops) => {
+            update.insert("labels".to_string(), Value::Array(ops));
+        }
+        None => {
+            fields.insert("labels".to_string(), fields::coerce_value(&schema, labels)?);
+        }
+    }
+    Ok(())
+}
+
+/// The `update` block for `client.update_issue`, omitted when there are no operations.
+pub(crate) fn update_payload(update: serde_json::Map<String, Value>) -> Option<Value> {
+    (!update.is_empty()).then_some(Value::Object(update))
+}
+
+/// Open the editor on an existing description or comment `body`. Returns `None` when the
+/// text is saved unchanged, so the stored body (with everything Markdown cannot show) is
+/// left alone.
+///
+/// Data Center bodies are wiki markup and are edited as-is. Cloud bodies are edited as
+/// Markdown, and dropping content Markdown cannot represent needs confirmation.
+pub(crate) fn compose_body(
+    client: &AtlassianClient,
+    name: &str,
+    body: &Value,
+    what: &str,
+    plain: bool,
+) -> Result<Option<Value>, Error> {
+    const CANCEL: &str = "Lines like these are removed. Save an empty file to cancel.";
+    let aborted = || Error::from(format!("Aborted: empty {}", name));
+
+    if client.is_data_center() {
+        let current = body.as_str().unwrap_or_default();
+        let help = format!("Edit {} in wiki markup.", what);
+        let text = input::compose(name, current, &[&help, CANCEL])?.ok_or_else(aborted)?;
+        return Ok((text.trim() != current.trim()).then(|| Value::String(text.trim().to_string())));
+    }
+
+    let current = adf::to_markdown(body);
+    let losses = adf::markdown_losses(body).join(", ");
+    let mut help = vec![format!("Edit {} in Markdown.", what)];
+    if !losses.is_empty() {
+        help.push(format!(
+            "Saving changes drops what Markdown cannot show: {}.",
+            losses
+        ));
+    }
+    help.push(CANCEL.to_string());
+    let help: Vec<&str> = help.iter().map(String::as_str).collect();
+    let text = input::compose(name, &current, &help)?.ok_or_else(aborted)?;
+    if text.trim() == current.trim() {
+        return Ok(None);
+    }
+    if !losses.is_empty() {
+        let answer = input::prompt_line(&format!(
+            "{} {} has {} that will be lost. Save anyway? [y/N] ",
+            "warning:".yellow().bold(),
+            what,
+            losses
+        ))?;
+        if !matches!(answer.to_lowercase().as_str(), "y" | "yes") {
+            return Err(format!("Aborted; {} was not changed", what).into());
+        }
+    }
+    Ok(Some(adf::from_text(&text, plain)))
+}
+
+/// Resolve `--field "Name=value"` arguments into a Jira `fields` map, looking up field IDs
+/// and coercing values according to each field's schema.
+pub(crate) async fn resolve_field_args(
+    client: &AtlassianClient,
+    field_args: &[String],
+) -> Result<serde_json::Map<String, Value>, Error> {
+    let (fields, update) = resolve_field_edits(client, field_args).await?;
+    if let Some(id) = update.keys().next() {
+        return Err(format!(
+            "Adding or removing values with +/- is not possible when creating an issue (field '{}')",
+            id
+        )
+        .into());
+    }
+    Ok(fields)
+}
+
+/// Like `resolve_field_args`, but multi-value fields given as `+add,-remove` items go into a
+/// separate `update` map of add/remove operations instead of replacing the field.
+pub(crate) async fn resolve_field_edits(
+    client: &AtlassianClient,
+    field_args: &[String],
+) -> Result<
+    (
+        serde_json::Map<String, Value>,
+        serde_json::Map<String, Value>,
+    ),
+    Error,
+> {
+    let mut fields = serde_json::Map::new();
+    let mut update = serde_json::Map::new();
+    if field_args.is_empty() {
+        return Ok((fields, update));
+    }
+
+    let lookup = client.field_lookup().await?;
+    for arg in field_args {
+        let (name, raw) = fields::parse_assignment(arg)?;
+        let id = lookup
+            .resolve(&name)?
+            .ok_or_else(|| format!("Unknown field '{}'", name))?
+            .to_string();
+        let schema = lookup.schemas.get(&id).cloned().unwrap_or(Value::Null);
+
+        let raw = if fields::is_user_schema(&schema) {
+            resolve_account_ids(client, &raw).await?
+        } else {
+            raw
+        };
+
+        let invalid = |e: String| format!("Invalid value for field '{}': {}", name, e);
+        match fields::update_ops(&schema, &raw).map_err(invalid)? {
+            Some(ops) => {
+                update.insert(id, Value::Array(ops));
+            }
+            None => {
+                let value = fields::coerce_value(&schema, &raw).map_err(invalid)?;
+                fields.insert(id, value);
+            }
+        }
+    }
+    Ok((fields, update))
+}
+
+/// Resolve each comma-separated user in `raw` to an account ID, keeping any +/- prefix.
+pub(crate) async fn resolve_account_ids(
+    client: &AtlassianClient,
+    raw: &str,
+) -> Result<String, Error> {
+    let mut account_ids = Vec::new();
+    for query in raw.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        let (prefix, query) = if query.starts_with(['+', '-']) {
+            query.split_at(1)
+        } else {
+            ("", query)
+        };
+        let account_id = resolve_account_id(client, query.trim()).await?;
+        account_ids.push(format!("{}{}", prefix, account_id));
+    }
+    Ok(account_ids.join(","))
+}
+
 pub(crate) async fn resolve_account_id(
     client: &AtlassianClient,
     query: &str,
-) -> Result<String, String> {
+) -> Result<String, Error> {
     if query.starts_with("acct:")
         || query.len() > 20 && query.chars().all(|c| c.is_ascii_alphanumeric())
     {
@@ -70,33 +260,59 @@ pub(crate) async fn resolve_account_id(
         .ok_or("User search returned an unexpected response")?;
 
     if users.is_empty() {
-        return Err(format!("No Jira users matched '{}'", query));
+        return Err(format!("No Jira users matched '{}'", query).into());
     }
 
     if users.len() > 1 {
         let matches: Vec<String> = users
             .iter()
             .take(5)
-            .filter_map(|u| {
+            .map(|u| {
                 let name = u["displayName"].as_str().unwrap_or("?");
                 let email = u["emailAddress"].as_str().unwrap_or("");
                 let account_id = u["accountId"].as_str().unwrap_or("?");
-                Some(if email.is_empty() {
+                if email.is_empty() {
                     format!("{} ({})", name, account_id)
                 } else {
                     format!("{} <{}> ({})", name, email, account_id)
-                })
+                }
             })
             .collect();
         return Err(format!(
             "Multiple Jira users matched '{}': {}",
             query,
             matches.join(", ")
-        ));
+        )
+        .into());
     }
 
     users[0]["accountId"]
         .as_str()
         .map(|s| s.to_string())
-        .ok_or_else(|| "Matched Jira user had no accountId".to_string())
+        .ok_or_else(|| "Matched Jira user had no accountId".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_label_edits() {
+        let (mut fields, mut update) = (serde_json::Map::new(), serde_json::Map::new());
+        label_edits("+urgent,-triage", &mut fields, &mut update).unwrap();
+        assert!(fields.is_empty());
+        assert_eq!(
+            update_payload(update).unwrap(),
+            json!({ "labels": [{ "add": "urgent" }, { "remove": "triage" }] })
+        );
+
+        let (mut fields, mut update) = (serde_json::Map::new(), serde_json::Map::new());
+        label_edits("a, b", &mut fields, &mut update).unwrap();
+        assert_eq!(fields["labels"], json!(["a", "b"]));
+        assert_eq!(update_payload(update), None);
+
+        let (mut fields, mut update) = (serde_json::Map::new(), serde_json::Map::new());
+        assert!(label_edits("+a,b", &mut fields, &mut update).is_err());
+    }
 }
@@ -0,0 +1,206 @@
+use crate::client::AtlassianClient;
+use crate::error::Error;
+use crate::formatter::{Formatter, OutputFormat};
+use owo_colors::OwoColorize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+
+/// Fields fetched for every issue in a tree.
+const TREE_FIELDS: [&str; 5] = ["summary", "status", "assignee", "issuetype", "parent"];
+
+/// How many parent keys go into one `parent in (...)` query.
+const PARENT_CHUNK: usize = 50;
+
+/// Children fetched per `parent in (...)` query at most.
+const MAX_CHILDREN_PER_QUERY: i64 = 5000;
+
+/// Show an issue and its descendants (epic → story → subtask) as an indented tree.
+pub async fn run_tree(
+    client: &AtlassianClient,
+    formatter: &Formatter,
+    key: String,
+    depth: usize,
+) -> Result<(), Error> {
+    let root = client.get_issue(&key).await?;
+    let root_key = root["key"].as_str().unwrap_or(&key).to_string();
+    let fields: Vec<String> = TREE_FIELDS.iter().map(|f| f.to_string()).collect();
+
+    // Fetch one level at a time; `seen` guards against cycles.
+    let mut children: HashMap<String, Vec<Value>> = HashMap::new();
+    let mut seen = HashSet::from([root_key.clone()]);
+    let mut level = vec![root_key.clone()];
+    let mut truncated = false;
+    for _ in 0..depth {
+        let mut next = Vec::new();
+        for chunk in level.chunks(PARENT_CHUNK) {
+            let jql = format!("parent in ({}) ORDER BY rank", chunk.join(", "));
+            let (issues, more_available) = client
+                .search_all(&jql, fields.clone(), MAX_CHILDREN_PER_QUERY)
+                .await?;
+            truncated |= more_available;
+            for issue in issues {
+                let key = issue["key"].as_str().unwrap_or_default().to_string();
+                let parent = issue["fields"]["parent"]["key"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string();
+                if seen.insert(key.clone()) {
+                    next.push(key);
+                    children.entry(parent).or_default().push(issue);
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        level = next;
+    }
+    if truncated {
+        eprintln!(
+            "{} a query for children matched more than {} issues; the tree below is incomplete.",
+            "warning:".yellow().bold(),
+            MAX_CHILDREN_PER_QUERY
+        );
+    }
+
+    let lines = tree_lines(&root, &children);
+
+    if !matches!(formatter.format, OutputFormat::Table) {
+        let mut rows = vec![vec![
+            "KEY".to_string(),
+            "PARENT".to_string(),
+            "TYPE".to_string(),
+            "STATUS".to_string(),
+            "SUMMARY".to_string(),
+            "ASSIGNEE".to_string(),
+        ]];
+        for (_, issue) in &lines {
+            let f = &issue["fields"];
+            rows.push(vec![
+                issue["key"].as_str().unwrap_or_default().to_string(),
+                f["parent"]["key"].as_str().unwrap_or_default().to_string(),
+                f["issuetype"]["name"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                f["status"]["name"].as_str().unwrap_or_default().to_string(),
+                f["summary"].as_str().unwrap_or_default().to_string(),
+                assignee(f).to_string(),
+            ]);
+        }
+        println!("{}", formatter.render(rows));
+        return Ok(());
+    }
+
+    for (prefix, issue) in &lines {
+        let f = &issue["fields"];
+        println!(
+            "{}{} {} {} {}",
+            prefix.dimmed(),
+            issue["key"].as_str().unwrap_or("?").cyan().bold(),
+            format!("[{}]", f["status"]["name"].as_str().unwrap_or("?")).yellow(),
+            f["summary"].as_str().unwrap_or_default(),
+            format!("({})", assignee(f)).dimmed()
+        );
+    }
+    Ok(())
+}
+
+/// Move issues under a new parent, or detach them from their parent.
+pub async fn run_move_parent(
+    client: &AtlassianClient,
+    keys: Vec<String>,
+    parent: Option<String>,
+) -> Result<(), Error> {
+    let value = match &parent {
+        Some(parent) => json!({ "key": parent }),
+        None => Value::Null,
+    };
+    for key in &keys {
+        client
+            .update_issue(key, json!({ "parent": value.clone() }), None)
+            .await?;
+        match &parent {
+            Some(parent) => println!(
+                "{} {} {} {}",
+                "Moved".green().bold(),
+                key.cyan().bold(),
+                "under".green().bold(),
+                parent.cyan().bold()
+            ),
+            None => println!(
+                "{} {}",
+                "Removed parent of".green().bold(),
+                key.cyan().bold()
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn assignee(fields: &Value) -> &str {
+    fields["assignee"]["displayName"]
+        .as_str()
+        .unwrap_or("Unassigned")
+}
+
+/// Flatten the tree depth-first into `(prefix, issue)` pairs, where the prefix draws the
+/// branches (`├── `, `└── `, `│   `).
+fn tree_lines<'a>(
+    root: &'a Value,
+    children: &'a HashMap<String, Vec<Value>>,
+) -> Vec<(String, &'a Value)> {
+    fn walk<'a>(
+        issue: &'a Value,
+        children: &'a HashMap<String, Vec<Value>>,
+        indent: &str,
+        lines: &mut Vec<(String, &'a Value)>,
+    ) {
+        let key = issue["key"].as_str().unwrap_or_default();
+        let Some(kids) = children.get(key) else {
+            return;
+        };
+        for (i, child) in kids.iter().enumerate() {
+            let last = i + 1 == kids.len();
+            let branch = if last { "└── " } else { "├── " };
+            lines.push((format!("{}{}", indent, branch), child));
+            let indent = format!("{}{}", indent, if last { "    " } else { "│   " });
+            walk(child, children, &indent, lines);
+        }
+    }
+
+    let mut lines = vec![(String::new(), root)];
+    walk(root, children, "", &mut lines);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_lines() {
+        let issue = |key: &str| json!({ "key": key, "fields": {} });
+        let root = issue("P-1");
+        let children = HashMap::from([
+            ("P-1".to_string(), vec![issue("P-2"), issue("P-3")]),
+            ("P-2".to_string(), vec![issue("P-4")]),
+            ("P-3".to_string(), vec![issue("P-5")]),
+        ]);
+
+        let lines: Vec<(String, &str)> = tree_lines(&root, &children)
+            .into_iter()
+            .map(|(prefix, issue)| (prefix, issue["key"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                ("".to_string(), "P-1"),
+                ("├── ".to_string(), "P-2"),
+                ("│   └── ".to_string(), "P-4"),
+                ("└── ".to_string(), "P-3"),
+                ("    └── ".to_string(), "P-5"),
+            ]
+        );
+    }
+}
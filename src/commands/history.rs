@@ -0,0 +1,144 @@
+use crate::client::{AtlassianClient, FieldLookup};
+use crate::dates;
+use crate::error::Error;
+use crate::formatter::Formatter;
+use serde_json::Value;
+
+/// Show an issue's changelog as a chronological, field-by-field list of changes.
+pub async fn run(
+    client: &AtlassianClient,
+    formatter: &Formatter,
+    key: String,
+    field_filter: Vec<String>,
+) -> Result<(), Error> {
+    let histories = client.get_changelog(&key).await?;
+    let lookup = client.field_lookup().await?;
+
+    let mut rows = vec![vec![
+        "DATE".to_string(),
+        "AUTHOR".to_string(),
+        "FIELD".to_string(),
+        "FROM".to_string(),
+        "TO".to_string(),
+    ]];
+    rows.extend(change_rows(&histories, &lookup, &field_filter));
+
+    println!("{}", formatter.render(rows));
+    Ok(())
+}
+
+/// One row per changed field, oldest change first, keeping only fields in `filter` (names or
+/// IDs, case-insensitive) when it is non-empty.
+fn change_rows(histories: &[Value], lookup: &FieldLookup, filter: &[String]) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    for history in histories {
+        let date = dates::display_timestamp(history["created"].as_str().unwrap_or_default());
+        let author = history["author"]["displayName"]
+            .as_str()
+            .unwrap_or("Automation")
+            .to_string();
+
+        for item in history["items"].as_array().into_iter().flatten() {
+            let name = field_name(item, lookup);
+            if !filter.is_empty() && !filter.iter().any(|f| matches_field(item, &name, f, lookup)) {
+                continue;
+            }
+            rows.push(vec![
+                date.clone(),
+                author.clone(),
+                name,
+                change_value(item, "fromString", "from"),
+                change_value(item, "toString", "to"),
+            ]);
+        }
+    }
+    rows
+}
+
+/// The display name of a changed field. Custom fields use their `FieldLookup` name.
+fn field_name(item: &Value, lookup: &FieldLookup) -> String {
+    item["fieldId"]
+        .as_str()
+        .and_then(|id| lookup.id_to_name.get(id))
+        .cloned()
+        .or_else(|| item["field"].as_str().map(|f| f.to_string()))
+        .unwrap_or_default()
+}
+
+fn matches_field(item: &Value, name: &str, filter: &str, lookup: &FieldLookup) -> bool {
+    let resolved = lookup.resolve(filter).ok().flatten();
+    name.eq_ignore_ascii_case(filter)
+        || item["field"]
+            .as_str()
+            .is_some_and(|f| f.eq_ignore_ascii_case(filter))
+        || item["fieldId"]
+            .as_str()
+            .is_some_and(|id| id.eq_ignore_ascii_case(filter) || Some(id) == resolved)
+}
+
+/// The human-readable side of a change, falling back to the raw ID. Multi-line values are
+/// collapsed onto one line.
+fn change_value(item: &Value, display: &str, raw: &str) -> String {
+    item[display]
+        .as_str()
+        .or_else(|| item[raw].as_str())
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_change_rows() {
+        let lookup = FieldLookup {
+            id_to_name: HashMap::from([
+                ("status".to_string(), "Status".to_string()),
+                ("customfield_10016".to_string(), "Story Points".to_string()),
+            ]),
+            name_to_id: HashMap::from([
+                ("status".to_string(), "status".to_string()),
+                ("story points".to_string(), "customfield_10016".to_string()),
+            ]),
+            schemas: HashMap::new(),
+        };
+        let histories = vec![
+            json!({
+                "created": "2024-03-01T09:00:00.000+0100",
+                "author": { "displayName": "Alice" },
+                "items": [
+                    { "field": "assignee", "fieldId": "assignee", "from": null, "to": "abc", "fromString": null, "toString": "Alice" }
+                ]
+            }),
+            json!({
+                "created": "2024-03-02T10:00:00.000+0100",
+                "author": { "displayName": "Bob" },
+                "items": [
+                    { "field": "status", "fieldId": "status", "fromString": "In Progress", "toString": "Done" },
+                    { "field": "Story Points", "fieldId": "customfield_10016", "from": null, "to": null, "fromString": "3", "toString": "5" }
+                ]
+            }),
+        ];
+
+        let rows = change_rows(&histories, &lookup, &[]);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            rows[0],
+            vec!["2024-03-01 09:00", "Alice", "assignee", "", "Alice"]
+        );
+        assert_eq!(rows[2][2], "Story Points");
+
+        let rows = change_rows(&histories, &lookup, &["STATUS".to_string()]);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][3..], ["In Progress", "Done"]);
+
+        let rows = change_rows(&histories, &lookup, &["story points".to_string()]);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][4], "5");
+    }
+}
@@ -0,0 +1,343 @@
+use crate::client::AtlassianClient;
+use crate::error::Error;
+use crate::formatter::Formatter;
+use owo_colors::OwoColorize;
+use serde_json::Value;
+
+/// Which side of a link type a name matched, e.g. "blocks" (outward) or "is blocked by" (inward).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Outward,
+    Inward,
+}
+
+/// List the available issue link types.
+pub async fn run_types(client: &AtlassianClient, formatter: &Formatter) -> Result<(), Error> {
+    let types = client.issue_link_types().await?;
+
+    let mut rows = vec![vec![
+        "ID".to_string(),
+        "NAME".to_string(),
+        "OUTWARD".to_string(),
+        "INWARD".to_string(),
+    ]];
+    for t in &types {
+        rows.push(
+            ["id", "name", "outward", "inward"]
+                .iter()
+                .map(|k| t[*k].as_str().unwrap_or_default().to_string())
+                .collect(),
+        );
+    }
+
+    println!("{}", formatter.render(rows));
+    Ok(())
+}
+
+/// Link `key` to `other`, reading as "`key` <relation> `other`", e.g. "PROJ-1 blocks PROJ-2".
+pub async fn run_add(
+    client: &AtlassianClient,
+    key: String,
+    relation: String,
+    other: String,
+) -> Result<(), Error> {
+    let types = client.issue_link_types().await?;
+    let (link_type, direction) = match_link_type(&types, &relation)?;
+    let name = link_type["name"].as_str().unwrap_or_default();
+
+    // The inward issue is the one the outward description applies to.
+    let (inward, outward) = match direction {
+        Direction::Outward => (&key, &other),
+        Direction::Inward => (&other, &key),
+    };
+    client.create_issue_link(name, inward, outward).await?;
+
+    println!(
+        "{} {} {} {}",
+        "Linked".green().bold(),
+        key.cyan().bold(),
+        describe(link_type, direction),
+        other.cyan().bold()
+    );
+    Ok(())
+}
+
+/// List the links of an issue.
+pub async fn run_list(
+    client: &AtlassianClient,
+    formatter: &Formatter,
+    key: String,
+) -> Result<(), Error> {
+    let links = client.get_issue_links(&key).await?;
+
+    let mut rows = vec![vec![
+        "ID".to_string(),
+        "RELATION".to_string(),
+        "KEY".to_string(),
+        "SUMMARY".to_string(),
+        "STATUS".to_string(),
+    ]];
+    for link in &links {
+        let (relation, issue) = link_relation(link);
+        rows.push(vec![
+            link["id"].as_str().unwrap_or_default().to_string(),
+            relation.to_string(),
+            issue["key"].as_str().unwrap_or_default().to_string(),
+            issue["fields"]["summary"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            issue["fields"]["status"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        ]);
+    }
+
+    println!("{}", formatter.render(rows));
+    Ok(())
+}
+
+/// Remove a link, either by its ID or by the relation and the other issue's key.
+pub async fn run_remove(
+    client: &AtlassianClient,
+    target: String,
+    relation: Option<String>,
+    other: Option<String>,
+) -> Result<(), Error> {
+    let link_id = match (relation, other) {
+        (Some(relation), Some(other)) => {
+            let types = client.issue_link_types().await?;
+            let (link_type, direction) = match_link_type(&types, &relation)?;
+            let links = client.get_issue_links(&target).await?;
+            find_link(&links, link_type, direction, &other)
+                .ok_or_else(|| {
+                    format!(
+                        "{} has no '{}' link to {}",
+                        target,
+                        describe(link_type, direction),
+                        other
+                    )
+                })?
+                .to_string()
+        }
+        (None, None) if target.chars().all(|c| c.is_ascii_digit()) => target,
+        _ => {
+            return Err(
+                "Pass a link ID, or an issue key with a relation and the other issue key \
+                 (e.g. jiri link remove PROJ-1 blocks PROJ-2)"
+                    .into(),
+            )
+        }
+    };
+
+    client.delete_issue_link(&link_id).await?;
+    println!(
+        "{} {}",
+        "Removed link".green().bold(),
+        link_id.cyan().bold()
+    );
+    Ok(())
+}
+
+/// Find a link type by name, outward or inward description (case-insensitive, exact match
+/// first, then prefix match).
+fn match_link_type<'a>(types: &'a [Value], query: &str) -> Result<(&'a Value, Direction), String> {
+    let query = query.to_lowercase();
+    let candidates = |t: &'a Value| {
+        [
+            ("name", Direction::Outward),
+            ("outward", Direction::Outward),
+            ("inward", Direction::Inward),
+        ]
+        .into_iter()
+        .filter_map(move |(field, direction)| {
+            t[field]
+                .as_str()
+                .map(|text| (t, text.to_lowercase(), direction))
+        })
+    };
+
+    let exact = types
+        .iter()
+        .flat_map(candidates)
+        .find(|(_, text, _)| *text == query);
+    let prefix = || {
+        types
+            .iter()
+            .flat_map(candidates)
+            .find(|(_, text, _)| text.starts_with(&query))
+    };
+
+    match exact.or_else(prefix) {
+        Some((link_type, _, direction)) => Ok((link_type, direction)),
+        None => {
+            let available: Vec<String> = types
+                .iter()
+                .flat_map(|t| {
+                    [t["outward"].as_str(), t["inward"].as_str()]
+                        .into_iter()
+                        .flatten()
+                        .map(|s| s.to_string())
+                })
+                .collect();
+            Err(format!(
+                "No link type matching '{}'. Available: {}",
+                query,
+                available.join(", ")
+            ))
+        }
+    }
+}
+
+fn describe(link_type: &Value, direction: Direction) -> &str {
+    let field = match direction {
+        Direction::Outward => "outward",
+        Direction::Inward => "inward",
+    };
+    link_type[field]
+        .as_str()
+        .or_else(|| link_type["name"].as_str())
+        .unwrap_or("?")
+}
+
+/// The relation as seen from the issue that owns the link, and the linked issue.
+///
+/// A link with an `outwardIssue` reads "this <outward> other"; one with an `inwardIssue`
+/// reads "this <inward> other".
+fn link_relation(link: &Value) -> (&str, &Value) {
+    if link["outwardIssue"].is_object() {
+        (
+            describe(&link["type"], Direction::Outward),
+            &link["outwardIssue"],
+        )
+    } else {
+        (
+            describe(&link["type"], Direction::Inward),
+            &link["inwardIssue"],
+        )
+    }
+}
+
+/// Find the ID of a link of the given type and direction to `other_key`.
+///
+/// Symmetric types like "relates to" read the same both ways, so either side matches.
+fn find_link<'a>(
+    links: &'a [Value],
+    link_type: &Value,
+    direction: Direction,
+    other_key: &str,
+) -> Option<&'a str> {
+    let sides: &[&str] = if link_type["inward"] == link_type["outward"] {
+        &["outwardIssue", "inwardIssue"]
+    } else {
+        match direction {
+            Direction::Outward => &["outwardIssue"],
+            Direction::Inward => &["inwardIssue"],
+        }
+    };
+    links
+        .iter()
+        .find(|l| {
+            l["type"]["id"] == link_type["id"]
+                && sides.iter().any(|side| {
+                    l[*side]["key"]
+                        .as_str()
+                        .is_some_and(|k| k.eq_ignore_ascii_case(other_key))
+                })
+        })
+        .and_then(|l| l["id"].as_str())
+}
+
+/// Group an issue's links by relation, keeping the order in which relations first appear.
+pub fn group_links(links: &[Value]) -> Vec<(String, Vec<&Value>)> {
+    let mut groups: Vec<(String, Vec<&Value>)> = Vec::new();
+    for link in links {
+        let (relation, issue) = link_relation(link);
+        match groups.iter_mut().find(|(r, _)| r == relation) {
+            Some((_, issues)) => issues.push(issue),
+            None => groups.push((relation.to_string(), vec![issue])),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn link_types() -> Vec<Value> {
+        vec![
+            json!({ "id": "1", "name": "Blocks", "inward": "is blocked by", "outward": "blocks" }),
+            json!({ "id": "2", "name": "Relates", "inward": "relates to", "outward": "relates to" }),
+            json!({ "id": "3", "name": "Duplicate", "inward": "is duplicated by", "outward": "duplicates" }),
+        ]
+    }
+
+    #[test]
+    fn test_match_link_type() {
+        let types = link_types();
+
+        let (t, d) = match_link_type(&types, "blocks").unwrap();
+        assert_eq!((t["id"].as_str(), d), (Some("1"), Direction::Outward));
+
+        let (t, d) = match_link_type(&types, "Is Blocked By").unwrap();
+        assert_eq!((t["id"].as_str(), d), (Some("1"), Direction::Inward));
+
+        let (t, d) = match_link_type(&types, "dup").unwrap();
+        assert_eq!((t["id"].as_str(), d), (Some("3"), Direction::Outward));
+
+        let (t, d) = match_link_type(&types, "is dup").unwrap();
+        assert_eq!((t["id"].as_str(), d), (Some("3"), Direction::Inward));
+
+        let err = match_link_type(&types, "clones").unwrap_err();
+        assert!(err.contains("is blocked by"));
+    }
+
+    #[test]
+    fn test_links_relations_and_lookup() {
+        let links = vec![
+            json!({ "id": "100", "type": link_types()[0], "outwardIssue": { "key": "PROJ-2" } }),
+            json!({ "id": "101", "type": link_types()[0], "inwardIssue": { "key": "PROJ-3" } }),
+            json!({ "id": "102", "type": link_types()[0], "outwardIssue": { "key": "PROJ-4" } }),
+        ];
+
+        let (relation, issue) = link_relation(&links[1]);
+        assert_eq!(relation, "is blocked by");
+        assert_eq!(issue["key"], "PROJ-3");
+
+        let groups = group_links(&links);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, "blocks");
+        assert_eq!(groups[0].1.len(), 2);
+
+        let blocks = &link_types()[0];
+        assert_eq!(
+            find_link(&links, blocks, Direction::Outward, "proj-4"),
+            Some("102")
+        );
+        assert_eq!(
+            find_link(&links, blocks, Direction::Inward, "PROJ-3"),
+            Some("101")
+        );
+        assert_eq!(find_link(&links, blocks, Direction::Inward, "PROJ-2"), None);
+    }
+
+    #[test]
+    fn test_find_symmetric_link_on_either_side() {
+        let relates = &link_types()[1];
+        let links = vec![
+            json!({ "id": "200", "type": relates, "inwardIssue": { "key": "PROJ-5" } }),
+            json!({ "id": "201", "type": relates, "outwardIssue": { "key": "PROJ-6" } }),
+        ];
+        assert_eq!(
+            find_link(&links, relates, Direction::Outward, "PROJ-5"),
+            Some("200")
+        );
+        assert_eq!(
+            find_link(&links, relates, Direction::Outward, "PROJ-6"),
+            Some("201")
+        );
+    }
+}
@@ -1,5 +1,9 @@
 pub mod assign;
 pub mod attach;
+pub mod attachments;
+pub mod auth;
+pub mod board;
+pub mod bulk;
 pub mod comment;
 pub mod completions;
 pub mod config;
@@ -7,8 +11,13 @@ pub mod confluence;
 pub mod create;
 pub mod doctor;
 pub mod edit;
+pub mod hierarchy;
+pub mod history;
+pub mod link;
 pub mod open;
 pub mod projects;
 pub mod search;
+pub mod sprint;
 pub mod transition;
 pub mod view;
+pub mod worklog;
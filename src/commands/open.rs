@@ -1,9 +1,10 @@
 use crate::client::AtlassianClient;
+use crate::error::Error;
 use owo_colors::OwoColorize;
 use std::process::Command;
 
 /// Open a Jira issue in the browser.
-pub async fn run(client: &AtlassianClient, key: String) -> Result<(), String> {
+pub async fn run(client: &AtlassianClient, key: String) -> Result<(), Error> {
     let url = format!(
         "{}/browse/{}",
         client.config().site.trim_end_matches('/'),
@@ -17,7 +18,7 @@ pub async fn run(client: &AtlassianClient, key: String) -> Result<(), String> {
     Ok(())
 }
 
-fn open_url(url: &str) -> Result<(), String> {
+pub(crate) fn open_url(url: &str) -> Result<(), String> {
     let result = if cfg!(target_os = "macos") {
         Command::new("open").arg(url).status()
     } else if cfg!(target_os = "windows") {
@@ -1,8 +1,9 @@
 use crate::client::AtlassianClient;
+use crate::error::Error;
 use crate::formatter::Formatter;
 
 /// Execute the projects command to list projects.
-pub async fn run(client: &AtlassianClient, formatter: &Formatter) -> Result<(), String> {
+pub async fn run(client: &AtlassianClient, formatter: &Formatter) -> Result<(), Error> {
     let projects = client.projects_all().await?;
 
     let mut rows = vec![vec!["KEY".to_string(), "NAME".to_string()]];
@@ -1,21 +1,27 @@
 use crate::client::AtlassianClient;
+use crate::error::Error;
 use crate::fields;
 use crate::formatter::{Formatter, OutputFormat};
 use owo_colors::OwoColorize;
 use serde_json::Value;
 
 /// Execute the search command.
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
     client: &AtlassianClient,
     formatter: &Formatter,
     jql: String,
+    sprint: Option<String>,
     fields: Option<String>,
     get_fields: bool,
     limit: i64,
     all_projects: bool,
-) -> Result<(), String> {
+) -> Result<(), Error> {
     let original_jql = jql.clone();
-    let mut final_jql = jql;
+    let mut final_jql = match sprint {
+        Some(sprint) => add_clause(&jql, &super::sprint::sprint_clause(&sprint)),
+        None => jql,
+    };
 
     // If not searching all projects and a default project exists, prepend it.
     if !all_projects {
@@ -135,11 +141,25 @@ fn split_order_by_clause(jql: &str) -> (String, String) {
     (jql.to_string(), String::new())
 }
 
+/// AND a clause onto a query, keeping any ORDER BY at the end.
+fn add_clause(jql: &str, clause: &str) -> String {
+    let (filter, order_by) = split_order_by_clause(jql);
+    let query = if filter.is_empty() {
+        clause.to_string()
+    } else {
+        format!("{} AND ({})", clause, filter)
+    };
+    match order_by.trim() {
+        "" => query,
+        order_by => format!("{} {}", query, order_by),
+    }
+}
+
 fn matches_project_clause(jql_lower: &str, clause: &str) -> bool {
     jql_lower.contains(clause)
 }
 
-fn search_error_with_context(original_jql: &str, final_jql: &str, err: String) -> String {
+fn search_error_with_context(original_jql: &str, final_jql: &str, err: Error) -> Error {
     eprintln!("{} JQL search failed", "error:".red().bold());
     eprintln!("{} {}", "  input:".cyan(), original_jql);
     eprintln!("{} {}", "  sent:".cyan(), final_jql);
@@ -207,3 +227,21 @@ fn resolve_fields(requested: &[String], lookup: &crate::client::FieldLookup) ->
         keys,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_clause() {
+        assert_eq!(
+            add_clause("assignee = currentUser() ORDER BY rank", "sprint = 7"),
+            "sprint = 7 AND (assignee = currentUser()) ORDER BY rank"
+        );
+        assert_eq!(
+            add_clause("ORDER BY rank", "sprint = 7"),
+            "sprint = 7 ORDER BY rank"
+        );
+        assert_eq!(add_clause("", "sprint = 7"), "sprint = 7");
+    }
+}
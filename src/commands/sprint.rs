@@ -0,0 +1,361 @@
+use crate::client::AtlassianClient;
+use crate::dates;
+use crate::error::Error;
+use crate::formatter::{Formatter, OutputFormat};
+use owo_colors::OwoColorize;
+use serde_json::{json, Value};
+
+use super::board::resolve_board;
+
+/// Default sprint length when starting a sprint that has no end date yet.
+const DEFAULT_SPRINT_DAYS: i64 = 14;
+
+/// List the sprints of a board.
+pub async fn run_list(
+    client: &AtlassianClient,
+    formatter: &Formatter,
+    board: Option<String>,
+    state: Option<String>,
+) -> Result<(), Error> {
+    let board_id = resolve_board(client, board.as_deref()).await?;
+    let sprints = client.board_sprints(&board_id, state.as_deref()).await?;
+
+    let mut rows = vec![vec![
+        "ID".to_string(),
+        "NAME".to_string(),
+        "STATE".to_string(),
+        "START".to_string(),
+        "END".to_string(),
+        "GOAL".to_string(),
+    ]];
+    for s in &sprints {
+        rows.push(vec![
+            sprint_id(s),
+            s["name"].as_str().unwrap_or_default().to_string(),
+            s["state"].as_str().unwrap_or_default().to_string(),
+            day_of(&s["startDate"]),
+            day_of(&s["endDate"]),
+            s["goal"].as_str().unwrap_or_default().to_string(),
+        ]);
+    }
+
+    println!("{}", formatter.render(rows));
+    Ok(())
+}
+
+/// Show a sprint and its issues, grouped by status.
+pub async fn run_view(
+    client: &AtlassianClient,
+    formatter: &Formatter,
+    sprint: String,
+    board: Option<String>,
+) -> Result<(), Error> {
+    let sprint = resolve_sprint(client, &sprint, board.as_deref()).await?;
+    let id = sprint_id(&sprint);
+    let issues = client.sprint_issues(&id).await?;
+    let groups = group_by_status(&issues);
+
+    if !matches!(formatter.format, OutputFormat::Table) {
+        let mut rows = vec![vec![
+            "STATUS".to_string(),
+            "KEY".to_string(),
+            "TYPE".to_string(),
+            "SUMMARY".to_string(),
+            "ASSIGNEE".to_string(),
+        ]];
+        for (status, issues) in &groups {
+            for issue in issues {
+                let mut row = vec![status.clone()];
+                row.extend(issue_row(issue));
+                rows.push(row);
+            }
+        }
+        println!("{}", formatter.render(rows));
+        return Ok(());
+    }
+
+    println!(
+        "{} {} {}",
+        sprint["name"].as_str().unwrap_or("?").bold(),
+        format!("({})", id).dimmed(),
+        sprint["state"].as_str().unwrap_or_default().yellow()
+    );
+    let (start, end) = (day_of(&sprint["startDate"]), day_of(&sprint["endDate"]));
+    if !start.is_empty() || !end.is_empty() {
+        println!("{} {} → {}", "Dates:".cyan(), start, end);
+    }
+    if let Some(goal) = sprint["goal"].as_str().filter(|g| !g.is_empty()) {
+        println!("{} {}", "Goal:".cyan(), goal);
+    }
+
+    for (status, issues) in &groups {
+        println!();
+        println!("{} ({})", status.bold(), issues.len());
+        let mut rows = vec![vec![
+            "KEY".to_string(),
+            "TYPE".to_string(),
+            "SUMMARY".to_string(),
+            "ASSIGNEE".to_string(),
+        ]];
+        rows.extend(issues.iter().map(|issue| issue_row(issue)));
+        println!("{}", formatter.render(rows));
+    }
+    if groups.is_empty() {
+        println!();
+        println!("{}", "No issues in this sprint.".dimmed());
+    }
+    Ok(())
+}
+
+/// Move issues into a sprint.
+pub async fn run_add(
+    client: &AtlassianClient,
+    keys: Vec<String>,
+    sprint: String,
+    board: Option<String>,
+) -> Result<(), Error> {
+    let sprint = resolve_sprint(client, &sprint, board.as_deref()).await?;
+    client
+        .move_issues_to_sprint(&sprint_id(&sprint), &keys)
+        .await?;
+    println!(
+        "{} {} {} {}",
+        "Moved".green().bold(),
+        keys.join(", ").cyan().bold(),
+        "to".green().bold(),
+        sprint["name"].as_str().unwrap_or("?").bold()
+    );
+    Ok(())
+}
+
+/// Start a future sprint. The end date comes from `--end`, `--days`, the sprint itself, or
+/// defaults to two weeks from now.
+pub async fn run_start(
+    client: &AtlassianClient,
+    sprint: String,
+    board: Option<String>,
+    end: Option<String>,
+    days: Option<i64>,
+) -> Result<(), Error> {
+    let sprint = resolve_sprint(client, &sprint, board.as_deref()).await?;
+    let now = dates::now_unix();
+    let end_date = match (end, days) {
+        (Some(end), _) => {
+            // End of the given local day.
+            let day = dates::parse_day(&end, dates::today())?;
+            let end = day.and_hms_opt(23, 59, 59).ok_or("Invalid end date")?;
+            dates::format_utc(dates::unix_from_local(end))
+        }
+        (None, Some(days)) => dates::format_utc(now + days * dates::DAY),
+        (None, None) => match sprint["endDate"].as_str() {
+            Some(end) => end.to_string(),
+            None => dates::format_utc(now + DEFAULT_SPRINT_DAYS * dates::DAY),
+        },
+    };
+
+    let changes = json!({
+        "state": "active",
+        "startDate": dates::format_utc(now),
+        "endDate": end_date,
+    });
+    client.update_sprint(&sprint_id(&sprint), changes).await?;
+    println!(
+        "{} {} {}",
+        "Started".green().bold(),
+        sprint["name"].as_str().unwrap_or("?").bold(),
+        format!("(ends {})", end_date.get(..10).unwrap_or(&end_date)).dimmed()
+    );
+    Ok(())
+}
+
+/// Close an active sprint.
+pub async fn run_close(
+    client: &AtlassianClient,
+    sprint: String,
+    board: Option<String>,
+) -> Result<(), Error> {
+    let sprint = resolve_sprint(client, &sprint, board.as_deref()).await?;
+    client
+        .update_sprint(&sprint_id(&sprint), json!({ "state": "closed" }))
+        .await?;
+    println!(
+        "{} {}",
+        "Closed".green().bold(),
+        sprint["name"].as_str().unwrap_or("?").bold()
+    );
+    Ok(())
+}
+
+/// Turn a `--sprint` value from `jiri search` into a JQL clause.
+///
+/// `current`/`active`, `next`/`future` and `closed` map to Jira's sprint functions; a number
+/// is a sprint ID and anything else a sprint name.
+pub fn sprint_clause(sprint: &str) -> String {
+    match sprint.trim().to_lowercase().as_str() {
+        "current" | "active" | "open" => "sprint in openSprints()".to_string(),
+        "next" | "future" => "sprint in futureSprints()".to_string(),
+        "closed" => "sprint in closedSprints()".to_string(),
+        id if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) => {
+            format!("sprint = {}", id)
+        }
+        _ => format!("sprint = \"{}\"", sprint.trim().replace('"', "\\\"")),
+    }
+}
+
+/// Resolve a sprint given as an ID, `active`/`current`/`open`, `next`/`future`, `closed`
+/// (the last closed one), or a name on the board. These match `sprint_clause`.
+async fn resolve_sprint(
+    client: &AtlassianClient,
+    sprint: &str,
+    board: Option<&str>,
+) -> Result<Value, Error> {
+    if sprint.chars().all(|c| c.is_ascii_digit()) && !sprint.is_empty() {
+        return client.get_sprint(sprint).await;
+    }
+
+    let board_id = resolve_board(client, board).await?;
+    let sprints = client.board_sprints(&board_id, None).await?;
+    pick_sprint(&sprints, sprint).cloned().map_err(Error::from)
+}
+
+fn pick_sprint<'a>(sprints: &'a [Value], query: &str) -> Result<&'a Value, String> {
+    let in_state = |state: &str| -> Vec<&'a Value> {
+        sprints.iter().filter(|s| s["state"] == state).collect()
+    };
+    match query.trim().to_lowercase().as_str() {
+        "active" | "current" | "open" => {
+            let active = in_state("active");
+            match active.as_slice() {
+                [one] => Ok(one),
+                [] => Err("The board has no active sprint".to_string()),
+                _ => Err(format!(
+                    "The board has several active sprints; pass one by ID: {}",
+                    describe_sprints(&active)
+                )),
+            }
+        }
+        "next" | "future" => in_state("future")
+            .first()
+            .copied()
+            .ok_or_else(|| "The board has no future sprint".to_string()),
+        "closed" => in_state("closed")
+            .last()
+            .copied()
+            .ok_or_else(|| "The board has no closed sprint".to_string()),
+        name => sprints
+            .iter()
+            .find(|s| s["name"].as_str().is_some_and(|n| n.to_lowercase() == name))
+            .ok_or_else(|| {
+                format!(
+                    "No sprint named '{}'. Sprints: {}",
+                    query,
+                    describe_sprints(&sprints.iter().collect::<Vec<_>>())
+                )
+            }),
+    }
+}
+
+fn describe_sprints(sprints: &[&Value]) -> String {
+    sprints
+        .iter()
+        .map(|s| format!("{} ({})", s["name"].as_str().unwrap_or("?"), sprint_id(s)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn sprint_id(sprint: &Value) -> String {
+    match &sprint["id"] {
+        Value::Number(n) => n.to_string(),
+        other => other.as_str().unwrap_or_default().to_string(),
+    }
+}
+
+/// The `YYYY-MM-DD` part of an ISO timestamp.
+fn day_of(value: &Value) -> String {
+    value
+        .as_str()
+        .and_then(|s| s.get(..10))
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn issue_row(issue: &Value) -> Vec<String> {
+    let fields = &issue["fields"];
+    vec![
+        issue["key"].as_str().unwrap_or_default().to_string(),
+        fields["issuetype"]["name"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        fields["summary"].as_str().unwrap_or_default().to_string(),
+        fields["assignee"]["displayName"]
+            .as_str()
+            .unwrap_or("Unassigned")
+            .to_string(),
+    ]
+}
+
+/// Group issues by status, ordered by status category (to do, in progress, done) and then by
+/// first appearance.
+fn group_by_status(issues: &[Value]) -> Vec<(String, Vec<&Value>)> {
+    let mut groups: Vec<(u8, String, Vec<&Value>)> = Vec::new();
+    for issue in issues {
+        let status = &issue["fields"]["status"];
+        let name = status["name"].as_str().unwrap_or("Unknown").to_string();
+        let rank = match status["statusCategory"]["key"].as_str() {
+            Some("new") => 0,
+            Some("indeterminate") => 1,
+            Some("done") => 2,
+            _ => 3,
+        };
+        match groups.iter_mut().find(|(_, n, _)| *n == name) {
+            Some((_, _, issues)) => issues.push(issue),
+            None => groups.push((rank, name, vec![issue])),
+        }
+    }
+    groups.sort_by_key(|(rank, _, _)| *rank);
+    groups
+        .into_iter()
+        .map(|(_, name, issues)| (name, issues))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sprint_clause() {
+        assert_eq!(sprint_clause("current"), "sprint in openSprints()");
+        assert_eq!(sprint_clause("Next"), "sprint in futureSprints()");
+        assert_eq!(sprint_clause("closed"), "sprint in closedSprints()");
+        assert_eq!(sprint_clause("42"), "sprint = 42");
+        assert_eq!(sprint_clause("Sprint 7"), "sprint = \"Sprint 7\"");
+    }
+
+    #[test]
+    fn test_pick_sprint_and_grouping() {
+        let sprints = vec![
+            json!({ "id": 1, "name": "Sprint 1", "state": "closed" }),
+            json!({ "id": 2, "name": "Sprint 2", "state": "active" }),
+            json!({ "id": 3, "name": "Sprint 3", "state": "future" }),
+        ];
+        assert_eq!(sprint_id(pick_sprint(&sprints, "active").unwrap()), "2");
+        assert_eq!(sprint_id(pick_sprint(&sprints, "next").unwrap()), "3");
+        assert_eq!(sprint_id(pick_sprint(&sprints, "Open").unwrap()), "2");
+        assert_eq!(sprint_id(pick_sprint(&sprints, "closed").unwrap()), "1");
+        assert_eq!(sprint_id(pick_sprint(&sprints, "sprint 1").unwrap()), "1");
+        assert!(pick_sprint(&sprints, "Sprint 9").is_err());
+
+        let issue = |key: &str, status: &str, category: &str| json!({ "key": key, "fields": { "status": { "name": status, "statusCategory": { "key": category } } } });
+        let issues = vec![
+            issue("P-1", "Done", "done"),
+            issue("P-2", "To Do", "new"),
+            issue("P-3", "Done", "done"),
+        ];
+        let groups = group_by_status(&issues);
+        assert_eq!(groups[0].0, "To Do");
+        assert_eq!(groups[1].0, "Done");
+        assert_eq!(groups[1].1.len(), 2);
+    }
+}
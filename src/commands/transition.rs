@@ -1,20 +1,44 @@
+use crate::adf;
 use crate::client::AtlassianClient;
+use crate::error::{Error, ErrorKind};
 use owo_colors::OwoColorize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::create;
 
 /// Execute the transition command.
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
     client: &AtlassianClient,
     key: String,
     status: Option<String>,
-) -> Result<(), String> {
+    resolution: Option<String>,
+    comment: Option<String>,
+    assignee: Option<String>,
+    field_args: Vec<String>,
+    via_path: bool,
+    plain: bool,
+) -> Result<(), Error> {
     match status {
         None => list_transitions(client, &key).await,
-        Some(target) => do_transition(client, &key, &target).await,
+        Some(target) if via_path => {
+            transition_via_path(
+                client, &key, &target, resolution, comment, assignee, field_args, plain,
+            )
+            .await
+        }
+        Some(target) => {
+            do_transition(
+                client, &key, &target, resolution, comment, assignee, field_args, plain,
+            )
+            .await
+        }
     }
 }
 
 /// List available transitions for an issue.
-async fn list_transitions(client: &AtlassianClient, key: &str) -> Result<(), String> {
+async fn list_transitions(client: &AtlassianClient, key: &str) -> Result<(), Error> {
     let data = client.get_transitions(key).await?;
     let transitions = data["transitions"]
         .as_array()
@@ -33,42 +57,495 @@ async fn list_transitions(client: &AtlassianClient, key: &str) -> Result<(), Str
     Ok(())
 }
 
-/// Perform a transition on an issue.
-async fn do_transition(client: &AtlassianClient, key: &str, target: &str) -> Result<(), String> {
+/// Perform a transition on an issue, filling in its screen fields.
+#[allow(clippy::too_many_arguments)]
+async fn do_transition(
+    client: &AtlassianClient,
+    key: &str,
+    target: &str,
+    resolution: Option<String>,
+    comment: Option<String>,
+    assignee: Option<String>,
+    field_args: Vec<String>,
+    plain: bool,
+) -> Result<(), Error> {
+    let name = transition_issue(
+        client, key, target, resolution, comment, assignee, field_args, plain,
+    )
+    .await?;
+    println!(
+        "{} {} → {}",
+        "Transitioned".green().bold(),
+        key.cyan().bold(),
+        style_transition_name(&name)
+    );
+    Ok(())
+}
+
+/// Perform a transition without printing anything, returning the transition's name.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn transition_issue(
+    client: &AtlassianClient,
+    key: &str,
+    target: &str,
+    resolution: Option<String>,
+    comment: Option<String>,
+    assignee: Option<String>,
+    field_args: Vec<String>,
+    plain: bool,
+) -> Result<String, Error> {
     let data = client.get_transitions(key).await?;
     let transitions = data["transitions"]
         .as_array()
         .ok_or("No transitions found")?;
+    let transition = find_transition(transitions, target)?;
+
+    let id = transition["id"].as_str().unwrap_or("?");
+    let name = transition["name"].as_str().unwrap_or("?");
+    let screen = screen_fields(transition);
+
+    let (mut fields, mut update) = super::edit::resolve_field_edits(client, &field_args).await?;
+    if let Some(resolution) = resolution {
+        let value = match screen.iter().find(|f| create::field_id(f) == "resolution") {
+            Some(meta) => {
+                let raw = create::match_allowed(meta, &resolution)?;
+                crate::fields::coerce_value(&meta["schema"], &raw)?
+            }
+            None => json!({ "name": resolution }),
+        };
+        fields.insert("resolution".to_string(), value);
+    }
+    if let Some(assignee) = assignee {
+        let account_id = super::edit::resolve_account_id(client, &assignee).await?;
+        fields.insert("assignee".to_string(), json!({ "accountId": account_id }));
+    }
+
+    let mut provided: Vec<String> = fields.keys().chain(update.keys()).cloned().collect();
+    let fields_to_check = provided.clone();
+    if comment.is_some() {
+        provided.push("comment".to_string());
+    }
+    check_screen(name, &screen, &fields_to_check, &provided)?;
+
+    if let Some(text) = comment {
+        let body = adf::from_text(&text, plain);
+        update.insert("comment".to_string(), json!([{ "add": { "body": body } }]));
+    }
+    let update = (!update.is_empty()).then_some(Value::Object(update));
+    client.do_transition(key, id, fields, update).await?;
+    Ok(name.to_string())
+}
+
+/// Upper bound on hops, so exploring an unreadable workflow cannot loop forever.
+const MAX_HOPS: usize = 10;
+
+/// A workflow transition between statuses. `from` is `None` for global transitions, which
+/// are available from every status.
+#[derive(Debug, Clone, PartialEq)]
+struct Edge {
+    from: Option<String>,
+    to: String,
+}
+
+/// Move an issue to the status `target` (name or ID) through as many transitions as needed.
+///
+/// The shortest path is computed from the workflow definition when it is readable (this
+/// needs admin permission); otherwise the path is explored hop by hop. Transitions are
+/// re-queried before every hop, and the walk stops at the first failure. Fields, resolution
+/// and comment only apply to the last hop.
+#[allow(clippy::too_many_arguments)]
+async fn transition_via_path(
+    client: &AtlassianClient,
+    key: &str,
+    target: &str,
+    resolution: Option<String>,
+    comment: Option<String>,
+    assignee: Option<String>,
+    field_args: Vec<String>,
+    plain: bool,
+) -> Result<(), Error> {
+    let issue = client.get_issue(key).await?;
+    let mut current = status_of(&issue);
+    if status_matches(&current, target) {
+        println!(
+            "{} is already in {}",
+            key.cyan().bold(),
+            style_transition_name(&current.1)
+        );
+        return Ok(());
+    }
+
+    let plan = match load_workflow(client, &issue).await? {
+        Some(workflow) => {
+            let (edges, statuses) = workflow_graph(&workflow);
+            let target_id = statuses
+                .iter()
+                .find(|s| status_matches(s, target))
+                .map(|(id, _)| id.clone())
+                .ok_or_else(|| {
+                    let names: Vec<&str> = statuses.iter().map(|(_, n)| n.as_str()).collect();
+                    format!(
+                        "No status matching '{}' in this workflow. Statuses: {}",
+                        target,
+                        names.join(", ")
+                    )
+                })?;
+            let path = shortest_path(&edges, &current.0, &target_id).ok_or_else(|| {
+                format!(
+                    "No path from {} to {} in workflow '{}'",
+                    current.1,
+                    target,
+                    workflow["id"]["name"].as_str().unwrap_or("?")
+                )
+            })?;
+            let names: Vec<String> = std::iter::once(current.1.clone())
+                .chain(path.iter().map(|e| status_name(&statuses, &e.to)))
+                .collect();
+            println!("{} {}", "Path:".cyan().bold(), names.join(" → "));
+            Some(path)
+        }
+        None => {
+            eprintln!(
+                "{} the workflow is not readable (admin permission needed); exploring transitions instead",
+                "note:".yellow().bold()
+            );
+            None
+        }
+    };
+
+    let mut visited = vec![current.0.clone()];
+    let total = plan.as_ref().map(|p| p.len());
+    let mut final_transition = None;
+    for hop in 0..total.unwrap_or(MAX_HOPS) {
+        let data = client.get_transitions(key).await?;
+        let transitions = data["transitions"].as_array().cloned().unwrap_or_default();
+
+        let next = match &plan {
+            Some(plan) => transitions
+                .iter()
+                .find(|t| t["to"]["id"].as_str() == Some(plan[hop].to.as_str())),
+            None => next_exploration_hop(&transitions, target, &visited),
+        };
+        let Some(next) = next else {
+            return Err(stopped(client, key, hop, "no transition leads on from here").await);
+        };
+
+        let id = next["id"].as_str().unwrap_or_default().to_string();
+        let is_last = status_matches(&transition_target(next), target);
+        let step = match total {
+            Some(total) => format!("[{}/{}]", hop + 1, total),
+            None => format!("[{}]", hop + 1),
+        };
+        print!("{} ", step.dimmed());
+
+        let result = if is_last {
+            do_transition(
+                client,
+                key,
+                &id,
+                resolution.clone(),
+                comment.clone(),
+                assignee.clone(),
+                field_args.clone(),
+                plain,
+            )
+            .await
+        } else {
+            do_transition(client, key, &id, None, None, None, Vec::new(), plain).await
+        };
+        if let Err(e) = result {
+            println!();
+            eprintln!("{} {}", "error:".red().bold(), e);
+            return Err(stopped(client, key, hop, "a transition failed").await);
+        }
+
+        current = transition_target(next);
+        visited.push(current.0.clone());
+        if is_last {
+            final_transition = Some(current.1.clone());
+            break;
+        }
+    }
+
+    match final_transition {
+        Some(status) => {
+            println!(
+                "{} {} is now in {}",
+                "Final status:".green().bold(),
+                key.cyan().bold(),
+                style_transition_name(&status)
+            );
+            Ok(())
+        }
+        None => Err(stopped(client, key, MAX_HOPS, "no path found within the hop limit").await),
+    }
+}
+
+/// Build the error for a walk that stopped early, reporting where the issue ended up.
+async fn stopped(client: &AtlassianClient, key: &str, hops: usize, reason: &str) -> Error {
+    let status = match client.get_issue(key).await {
+        Ok(issue) => status_of(&issue).1,
+        Err(_) => "unknown".to_string(),
+    };
+    format!(
+        "Stopped after {} hop(s): {}. {} is in {}",
+        hops, reason, key, status
+    )
+    .into()
+}
+
+/// Fetch the workflow used by the issue, or `None` when the user may not read it.
+async fn load_workflow(client: &AtlassianClient, issue: &Value) -> Result<Option<Value>, Error> {
+    let fields = &issue["fields"];
+    let project_id = fields["project"]["id"].as_str().unwrap_or_default();
+    let type_id = fields["issuetype"]["id"].as_str().unwrap_or_default();
+
+    let scheme = match client.project_workflow_scheme(project_id).await {
+        Ok(scheme) => scheme,
+        Err(e) if matches!(e.kind(), ErrorKind::Auth | ErrorKind::NotFound) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let Some(name) = scheme["issueTypeMappings"][type_id]
+        .as_str()
+        .or_else(|| scheme["defaultWorkflow"].as_str())
+    else {
+        return Ok(None);
+    };
+
+    match client.get_workflow(name).await {
+        Ok(workflow) => Ok(Some(workflow)),
+        Err(e) if matches!(e.kind(), ErrorKind::Auth | ErrorKind::NotFound) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Transitions and `(id, name)` statuses of a workflow from `/workflow/search`.
+fn workflow_graph(workflow: &Value) -> (Vec<Edge>, Vec<(String, String)>) {
+    let statuses = workflow["statuses"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|s| {
+            Some((
+                s["id"].as_str()?.to_string(),
+                s["name"].as_str()?.to_string(),
+            ))
+        })
+        .collect();
+
+    let mut edges = Vec::new();
+    for t in workflow["transitions"].as_array().into_iter().flatten() {
+        let Some(to) = t["to"].as_str() else {
+            continue;
+        };
+        let from: Vec<&str> = t["from"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|f| f.as_str())
+            .collect();
+        match t["type"].as_str() {
+            Some("initial") => {}
+            _ if from.is_empty() => edges.push(Edge {
+                from: None,
+                to: to.to_string(),
+            }),
+            _ => edges.extend(from.into_iter().map(|f| Edge {
+                from: Some(f.to_string()),
+                to: to.to_string(),
+            })),
+        }
+    }
+    (edges, statuses)
+}
 
-    // Find matching transition (case-insensitive, prefix match)
+/// Breadth-first search for the fewest transitions from status `from` to status `to`.
+fn shortest_path(edges: &[Edge], from: &str, to: &str) -> Option<Vec<Edge>> {
+    let mut previous: HashMap<String, Edge> = HashMap::new();
+    let mut queue = VecDeque::from([from.to_string()]);
+    let mut seen = HashSet::from([from.to_string()]);
+
+    while let Some(status) = queue.pop_front() {
+        if status == to {
+            let mut path = Vec::new();
+            let mut at = status;
+            while let Some(edge) = previous.get(&at) {
+                path.push(edge.clone());
+                at = edge.from.clone().unwrap_or_default();
+                if at == from {
+                    break;
+                }
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for edge in edges
+            .iter()
+            .filter(|e| e.from.as_deref().is_none_or(|f| f == status))
+        {
+            if seen.insert(edge.to.clone()) {
+                // Record the concrete source so global transitions can be walked back.
+                let edge = Edge {
+                    from: Some(status.clone()),
+                    to: edge.to.clone(),
+                };
+                previous.insert(edge.to.clone(), edge.clone());
+                queue.push_back(edge.to);
+            }
+        }
+    }
+    None
+}
+
+/// Without the workflow definition: take the transition to `target` if available, otherwise
+/// move to an unvisited status, preferring the furthest along (to do → in progress → done).
+fn next_exploration_hop<'a>(
+    transitions: &'a [Value],
+    target: &str,
+    visited: &[String],
+) -> Option<&'a Value> {
+    if let Some(direct) = transitions
+        .iter()
+        .find(|t| status_matches(&transition_target(t), target))
+    {
+        return Some(direct);
+    }
+    let rank = |t: &Value| match t["to"]["statusCategory"]["key"].as_str() {
+        Some("new") => 0,
+        Some("indeterminate") => 1,
+        _ => 2,
+    };
+    transitions
+        .iter()
+        .filter(|t| {
+            t["to"]["id"]
+                .as_str()
+                .is_some_and(|id| !visited.iter().any(|v| v == id))
+        })
+        .min_by_key(|t| std::cmp::Reverse(rank(t)))
+}
+
+fn status_of(issue: &Value) -> (String, String) {
+    let status = &issue["fields"]["status"];
+    (
+        status["id"].as_str().unwrap_or_default().to_string(),
+        status["name"].as_str().unwrap_or_default().to_string(),
+    )
+}
+
+fn transition_target(transition: &Value) -> (String, String) {
+    let to = &transition["to"];
+    (
+        to["id"].as_str().unwrap_or_default().to_string(),
+        to["name"].as_str().unwrap_or_default().to_string(),
+    )
+}
+
+/// Whether an `(id, name)` status is the one the user asked for, by ID or name.
+fn status_matches((id, name): &(String, String), target: &str) -> bool {
+    id == target || name.eq_ignore_ascii_case(target)
+}
+
+fn status_name(statuses: &[(String, String)], id: &str) -> String {
+    statuses
+        .iter()
+        .find(|(s, _)| s == id)
+        .map(|(_, name)| name.clone())
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Find a transition by ID, or by name (case-insensitive, exact match first, then prefix).
+fn find_transition<'a>(transitions: &'a [Value], target: &str) -> Result<&'a Value, String> {
     let target_lower = target.to_lowercase();
-    let matched = transitions.iter().find(|t| {
-        let name = t["name"].as_str().unwrap_or("").to_lowercase();
-        name == target_lower || name.starts_with(&target_lower)
-    });
+    let name = |t: &Value| t["name"].as_str().unwrap_or("").to_lowercase();
+    transitions
+        .iter()
+        .find(|t| t["id"].as_str() == Some(target) || name(t) == target_lower)
+        .or_else(|| {
+            transitions
+                .iter()
+                .find(|t| name(t).starts_with(&target_lower))
+        })
+        .ok_or_else(|| {
+            let available: Vec<String> = transitions
+                .iter()
+                .filter_map(|t| t["name"].as_str().map(|s| s.to_string()))
+                .collect();
+            format!(
+                "No transition matching '{}'. Available: {}",
+                target,
+                available.join(", ")
+            )
+        })
+}
 
-    let transition = matched.ok_or_else(|| {
-        let available: Vec<String> = transitions
+/// The fields on a transition's screen (from `expand=transitions.fields`), each carrying
+/// its ID as `fieldId`.
+fn screen_fields(transition: &Value) -> Vec<Value> {
+    transition["fields"]
+        .as_object()
+        .map(|fields| {
+            fields
+                .iter()
+                .map(|(id, meta)| {
+                    let mut meta = meta.clone();
+                    if meta["fieldId"].is_null() && meta.is_object() {
+                        meta["fieldId"] = json!(id);
+                    }
+                    meta
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Check that every field being set is on the transition screen, and that every required
+/// screen field without a default is provided.
+fn check_screen(
+    transition: &str,
+    screen: &[Value],
+    setting: &[String],
+    provided: &[String],
+) -> Result<(), String> {
+    let on_screen = |id: &str| screen.iter().any(|f| create::field_id(f) == id);
+    let not_on_screen: Vec<&str> = setting
+        .iter()
+        .map(|s| s.as_str())
+        .filter(|id| !on_screen(id))
+        .collect();
+    if !not_on_screen.is_empty() {
+        let available: Vec<String> = screen
             .iter()
-            .filter_map(|t| t["name"].as_str().map(|s| s.to_string()))
+            .map(|f| {
+                format!(
+                    "{} ({})",
+                    f["name"].as_str().unwrap_or("?"),
+                    create::field_id(f)
+                )
+            })
             .collect();
-        format!(
-            "No transition matching '{}'. Available: {}",
-            target,
+        let available = if available.is_empty() {
+            "none (the transition has no screen)".to_string()
+        } else {
             available.join(", ")
-        )
-    })?;
-
-    let id = transition["id"].as_str().unwrap_or("?");
-    let name = transition["name"].as_str().unwrap_or("?");
+        };
+        return Err(format!(
+            "Transition '{}' cannot set: {}. Fields on its screen: {}",
+            transition,
+            not_on_screen.join(", "),
+            available
+        ));
+    }
 
-    client.do_transition(key, id).await?;
-    println!(
-        "{} {} → {}",
-        "Transitioned".green().bold(),
-        key.cyan().bold(),
-        style_transition_name(name)
-    );
+    let missing = create::missing_required_fields(screen, provided);
+    if !missing.is_empty() {
+        let mut message = format!("Transition '{}' requires:", transition);
+        for field in missing {
+            message.push_str(&format!("\n  {}", create::describe_field(field)));
+        }
+        message.push_str("\nSet them with --resolution, --assignee or --field \"NAME=VALUE\".");
+        return Err(message);
+    }
     Ok(())
 }
 
@@ -81,3 +558,123 @@ fn style_transition_name(name: &str) -> String {
         _ => name.to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transitions() -> Vec<Value> {
+        vec![
+            json!({ "id": "11", "name": "In Progress", "fields": {} }),
+            json!({
+                "id": "31",
+                "name": "Done",
+                "fields": {
+                    "resolution": {
+                        "required": true,
+                        "name": "Resolution",
+                        "schema": { "type": "resolution", "system": "resolution" },
+                        "allowedValues": [{ "id": "1", "name": "Fixed" }, { "id": "2", "name": "Won't Do" }]
+                    },
+                    "assignee": { "required": false, "name": "Assignee", "schema": { "type": "user" } }
+                }
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let workflow = json!({
+            "statuses": [
+                { "id": "1", "name": "To Do" },
+                { "id": "3", "name": "In Progress" },
+                { "id": "4", "name": "Review" },
+                { "id": "5", "name": "Done" },
+                { "id": "6", "name": "Blocked" }
+            ],
+            "transitions": [
+                { "id": "1", "name": "Create", "from": [], "to": "1", "type": "initial" },
+                { "id": "11", "name": "Start", "from": ["1"], "to": "3", "type": "directed" },
+                { "id": "21", "name": "Submit", "from": ["3"], "to": "4", "type": "directed" },
+                { "id": "31", "name": "Approve", "from": ["4"], "to": "5", "type": "directed" },
+                { "id": "41", "name": "Block", "from": [], "to": "6", "type": "global" },
+                { "id": "51", "name": "Unblock", "from": ["6"], "to": "3", "type": "directed" }
+            ]
+        });
+        let (edges, statuses) = workflow_graph(&workflow);
+        assert_eq!(statuses.len(), 5);
+        assert!(!edges.iter().any(|e| e.from.is_none() && e.to == "1"));
+
+        let hops: Vec<String> = shortest_path(&edges, "1", "5")
+            .unwrap()
+            .iter()
+            .map(|e| status_name(&statuses, &e.to))
+            .collect();
+        assert_eq!(hops, ["In Progress", "Review", "Done"]);
+
+        // Through a global transition
+        let path = shortest_path(&edges, "5", "3").unwrap();
+        let hops: Vec<&str> = path.iter().map(|e| e.to.as_str()).collect();
+        assert_eq!(hops, ["6", "3"]);
+
+        assert!(shortest_path(&edges, "5", "1").is_none());
+    }
+
+    #[test]
+    fn test_next_exploration_hop() {
+        let to = |id: &str, name: &str, category: &str| json!({ "id": format!("t{}", id), "to": { "id": id, "name": name, "statusCategory": { "key": category } } });
+        let transitions = vec![
+            to("1", "To Do", "new"),
+            to("3", "In Progress", "indeterminate"),
+            to("5", "Done", "done"),
+        ];
+        let visited = vec!["1".to_string()];
+        assert_eq!(
+            next_exploration_hop(&transitions, "done", &visited).unwrap()["to"]["id"],
+            "5"
+        );
+        assert_eq!(
+            next_exploration_hop(&transitions[..2], "Closed", &visited).unwrap()["to"]["id"],
+            "3"
+        );
+        assert!(next_exploration_hop(&transitions[..1], "Closed", &visited).is_none());
+    }
+
+    #[test]
+    fn test_find_transition() {
+        let transitions = transitions();
+        assert_eq!(find_transition(&transitions, "31").unwrap()["name"], "Done");
+        assert_eq!(
+            find_transition(&transitions, "in prog").unwrap()["id"],
+            "11"
+        );
+        let err = find_transition(&transitions, "Closed").unwrap_err();
+        assert!(err.contains("Available: In Progress, Done"));
+    }
+
+    #[test]
+    fn test_check_screen() {
+        let transitions = transitions();
+        let done = screen_fields(&transitions[1]);
+        assert!(done.iter().any(|f| f["fieldId"] == "resolution"));
+
+        let err = check_screen("Done", &done, &[], &[]).unwrap_err();
+        assert!(
+            err.contains("Resolution (resolution): Fixed, Won't Do"),
+            "{}",
+            err
+        );
+
+        let set = vec!["resolution".to_string()];
+        assert!(check_screen("Done", &done, &set, &set).is_ok());
+
+        let set = vec!["resolution".to_string(), "labels".to_string()];
+        let err = check_screen("Done", &done, &set, &set).unwrap_err();
+        assert!(err.contains("cannot set: labels"), "{}", err);
+
+        let in_progress = screen_fields(&transitions[0]);
+        let set = vec!["resolution".to_string()];
+        let err = check_screen("In Progress", &in_progress, &set, &set).unwrap_err();
+        assert!(err.contains("has no screen"), "{}", err);
+    }
+}
@@ -1,12 +1,25 @@
 use crate::adf;
 use crate::client::AtlassianClient;
+use crate::commands::link::group_links;
+use crate::error::Error;
+use crate::formatter::DocumentFormat;
 use owo_colors::OwoColorize;
+use serde_json::Value;
 use textwrap::wrap;
 
 /// Execute the view command to show issue details.
-pub async fn run(client: &AtlassianClient, key: String) -> Result<(), String> {
+pub async fn run(
+    client: &AtlassianClient,
+    key: String,
+    format: DocumentFormat,
+) -> Result<(), Error> {
     let issue = client.get_issue(&key).await?;
 
+    if format == DocumentFormat::Markdown {
+        print!("{}", render_markdown(&issue));
+        return Ok(());
+    }
+
     let issue_key = issue["key"].as_str().unwrap_or("?");
     let summary = issue["fields"]["summary"]
         .as_str()
@@ -36,6 +49,34 @@ pub async fn run(client: &AtlassianClient, key: String) -> Result<(), String> {
     println!("  {} {}", "Reporter:".cyan().bold(), reporter);
     println!("  {} {}", "Created:".cyan().bold(), created.dimmed());
     println!("  {} {}", "Updated:".cyan().bold(), updated.dimmed());
+    if let Some(time) = time_tracking(&issue["fields"]["timetracking"]) {
+        println!("  {} {}", "Time:".cyan().bold(), time);
+    }
+
+    // Links, grouped by relation
+    let links = issue["fields"]["issuelinks"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    if !links.is_empty() {
+        println!();
+        println!("  {}", "Links:".cyan().bold());
+        for (relation, issues) in group_links(&links) {
+            println!("    {}", relation.bold());
+            for linked in issues {
+                println!(
+                    "      {} {} {}",
+                    linked["key"].as_str().unwrap_or("?").cyan(),
+                    linked["fields"]["summary"].as_str().unwrap_or_default(),
+                    format!(
+                        "[{}]",
+                        linked["fields"]["status"]["name"].as_str().unwrap_or("?")
+                    )
+                    .dimmed()
+                );
+            }
+        }
+    }
 
     // Description
     let desc = adf::to_plain_text(&issue["fields"]["description"]);
@@ -76,6 +117,98 @@ pub async fn run(client: &AtlassianClient, key: String) -> Result<(), String> {
     Ok(())
 }
 
+/// Summarize the `timetracking` field, e.g. "original 2d, remaining 1d 4h, logged 4h".
+fn time_tracking(tracking: &Value) -> Option<String> {
+    let parts: Vec<String> = [
+        ("original", "originalEstimate"),
+        ("remaining", "remainingEstimate"),
+        ("logged", "timeSpent"),
+    ]
+    .iter()
+    .filter_map(|(label, field)| {
+        tracking[*field]
+            .as_str()
+            .map(|value| format!("{} {}", label, value))
+    })
+    .collect();
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(", "))
+    }
+}
+
+/// Render an issue, its description and all of its comments as a Markdown document.
+fn render_markdown(issue: &Value) -> String {
+    let fields = &issue["fields"];
+    let text = |v: &Value, default: &str| v.as_str().unwrap_or(default).to_string();
+
+    let mut out = format!(
+        "# {}: {}\n\n",
+        text(&issue["key"], "?"),
+        text(&fields["summary"], "(no summary)")
+    );
+
+    let metadata = [
+        ("Type", text(&fields["issuetype"]["name"], "?")),
+        ("Status", text(&fields["status"]["name"], "?")),
+        ("Priority", text(&fields["priority"]["name"], "?")),
+        (
+            "Assignee",
+            text(&fields["assignee"]["displayName"], "Unassigned"),
+        ),
+        ("Reporter", text(&fields["reporter"]["displayName"], "?")),
+        ("Created", text(&fields["created"], "?")),
+        ("Updated", text(&fields["updated"], "?")),
+    ];
+    for (label, value) in metadata {
+        out.push_str(&format!("- **{}:** {}\n", label, value));
+    }
+    if let Some(time) = time_tracking(&fields["timetracking"]) {
+        out.push_str(&format!("- **Time:** {}\n", time));
+    }
+
+    let links = fields["issuelinks"].as_array().cloned().unwrap_or_default();
+    if !links.is_empty() {
+        out.push_str("\n## Links\n");
+        for (relation, issues) in group_links(&links) {
+            out.push_str(&format!("\n**{}**\n\n", relation));
+            for linked in issues {
+                out.push_str(&format!(
+                    "- {} {} ({})\n",
+                    text(&linked["key"], "?"),
+                    text(&linked["fields"]["summary"], ""),
+                    text(&linked["fields"]["status"]["name"], "?")
+                ));
+            }
+        }
+    }
+
+    let description = adf::to_markdown(&fields["description"]);
+    if !description.is_empty() {
+        out.push_str("\n## Description\n\n");
+        out.push_str(&description);
+    }
+
+    let comments = fields["comment"]["comments"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    if !comments.is_empty() {
+        out.push_str("\n## Comments\n");
+        for c in &comments {
+            out.push_str(&format!(
+                "\n### {} ({})\n\n",
+                text(&c["author"]["displayName"], "?"),
+                text(&c["created"], "?")
+            ));
+            out.push_str(&adf::to_markdown(&c["body"]));
+        }
+    }
+
+    out
+}
+
 fn stylize_status(status: &str) -> String {
     match status.to_lowercase().as_str() {
         "done" | "closed" | "resolved" => status.green().bold().to_string(),
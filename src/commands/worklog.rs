@@ -0,0 +1,354 @@
+use crate::adf;
+use crate::client::AtlassianClient;
+use crate::dates;
+use crate::error::Error;
+use crate::formatter::Formatter;
+use futures_util::stream::{self, StreamExt, TryStreamExt};
+use owo_colors::OwoColorize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// Issues a report covers at most; a wider period must be narrowed.
+const MAX_REPORT_ISSUES: i64 = 1000;
+/// Issues whose worklogs are fetched in parallel.
+const REPORT_CONCURRENCY: usize = 5;
+
+/// Log time on an issue.
+pub async fn run_add(
+    client: &AtlassianClient,
+    key: String,
+    duration: String,
+    started: Option<String>,
+    comment: Option<String>,
+    plain: bool,
+) -> Result<(), Error> {
+    let seconds = dates::parse_duration(&duration)?;
+    let started = parse_started(started.as_deref().unwrap_or("now"))?;
+
+    let mut worklog = json!({
+        "timeSpentSeconds": seconds,
+        "started": started,
+    });
+    if let Some(comment) = comment {
+        worklog["comment"] = adf::from_text(&comment, plain);
+    }
+
+    let result = client.add_worklog(&key, worklog).await?;
+    println!(
+        "{} {} on {} {}",
+        "Logged".green().bold(),
+        dates::format_duration(seconds).bold(),
+        key.cyan().bold(),
+        format!("(worklog {})", result["id"].as_str().unwrap_or("?")).dimmed()
+    );
+    Ok(())
+}
+
+/// List the worklogs of an issue.
+pub async fn run_list(
+    client: &AtlassianClient,
+    formatter: &Formatter,
+    key: String,
+) -> Result<(), Error> {
+    let worklogs = client.get_worklogs(&key).await?;
+
+    let mut rows = vec![vec![
+        "ID".to_string(),
+        "AUTHOR".to_string(),
+        "STARTED".to_string(),
+        "TIME SPENT".to_string(),
+        "COMMENT".to_string(),
+    ]];
+    for w in &worklogs {
+        rows.push(vec![
+            w["id"].as_str().unwrap_or_default().to_string(),
+            w["author"]["displayName"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            dates::display_timestamp(w["started"].as_str().unwrap_or_default()),
+            w["timeSpent"].as_str().unwrap_or_default().to_string(),
+            adf::to_plain_text(&w["comment"])
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+        ]);
+    }
+
+    println!("{}", formatter.render(rows));
+    Ok(())
+}
+
+/// Change the time spent, start time or comment of a worklog.
+pub async fn run_edit(
+    client: &AtlassianClient,
+    key: String,
+    id: String,
+    time: Option<String>,
+    started: Option<String>,
+    comment: Option<String>,
+    plain: bool,
+) -> Result<(), Error> {
+    let mut worklog = serde_json::Map::new();
+    if let Some(time) = time {
+        worklog.insert(
+            "timeSpentSeconds".to_string(),
+            json!(dates::parse_duration(&time)?),
+        );
+    }
+    if let Some(started) = started {
+        worklog.insert("started".to_string(), json!(parse_started(&started)?));
+    }
+    if let Some(comment) = comment {
+        worklog.insert("comment".to_string(), adf::from_text(&comment, plain));
+    }
+    if worklog.is_empty() {
+        return Err("Nothing to change. Use --time, --started, or --comment.".into());
+    }
+
+    client
+        .update_worklog(&key, &id, Value::Object(worklog))
+        .await?;
+    println!(
+        "{} {} on {}",
+        "Updated worklog".green().bold(),
+        id.bold(),
+        key.cyan().bold()
+    );
+    Ok(())
+}
+
+/// Delete a worklog.
+pub async fn run_delete(client: &AtlassianClient, key: String, id: String) -> Result<(), Error> {
+    client.delete_worklog(&key, &id).await?;
+    println!(
+        "{} {} on {}",
+        "Deleted worklog".green().bold(),
+        id.bold(),
+        key.cyan().bold()
+    );
+    Ok(())
+}
+
+/// Sum my logged time per day and issue between `from` and `to` (inclusive, default: the
+/// last 7 days).
+pub async fn run_report(
+    client: &AtlassianClient,
+    formatter: &Formatter,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<(), Error> {
+    let today = dates::today();
+    let to = dates::parse_day(to.as_deref().unwrap_or("today"), today)?;
+    let from = match from {
+        Some(from) => dates::parse_day(&from, today)?,
+        None => to - chrono::Days::new(6),
+    };
+    if from > to {
+        return Err("--from must not be after --to".into());
+    }
+    let (from, to) = (from.to_string(), to.to_string());
+    let (summaries, totals) = collect_report(client, &from, &to).await?;
+
+    let mut rows = vec![vec![
+        "DATE".to_string(),
+        "KEY".to_string(),
+        "SUMMARY".to_string(),
+        "TIME SPENT".to_string(),
+    ]];
+    let mut total = 0;
+    for ((date, key), seconds) in &totals {
+        total += seconds;
+        rows.push(vec![
+            date.clone(),
+            key.clone(),
+            summaries.get(key).cloned().unwrap_or_default(),
+            dates::format_duration(*seconds),
+        ]);
+    }
+    rows.push(vec![
+        String::new(),
+        String::new(),
+        "Total".to_string(),
+        dates::format_duration(total),
+    ]);
+
+    println!("{}", formatter.render(rows));
+    Ok(())
+}
+
+/// Fetch the current user's worklogs dated `from..=to` (`YYYY-MM-DD`) and total them per
+/// (date, issue key). Also returns the summary of each issue.
+async fn collect_report(
+    client: &AtlassianClient,
+    from: &str,
+    to: &str,
+) -> Result<(HashMap<String, String>, BTreeMap<(String, String), i64>), Error> {
+    let me = client.myself().await?;
+    let account_id = me["accountId"].as_str().unwrap_or_default();
+
+    let jql = format!(
+        "worklogAuthor = currentUser() AND worklogDate >= \"{}\" AND worklogDate <= \"{}\" ORDER BY key",
+        from, to
+    );
+    let (issues, more_available) = client
+        .search_all(&jql, vec!["summary".to_string()], MAX_REPORT_ISSUES)
+        .await?;
+    if more_available {
+        return Err(format!(
+            "Work was logged on more than {} issues in this period. Narrow it with --from and --to.",
+            MAX_REPORT_ISSUES
+        )
+        .into());
+    }
+
+    let summaries: HashMap<String, String> = issues
+        .iter()
+        .map(|issue| {
+            (
+                issue["key"].as_str().unwrap_or_default().to_string(),
+                issue["fields"]["summary"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            )
+        })
+        .collect();
+    let worklogs: Vec<(String, Vec<Value>)> = stream::iter(summaries.keys())
+        .map(|key| async move { Ok::<_, Error>((key.clone(), client.get_worklogs(key).await?)) })
+        .buffer_unordered(REPORT_CONCURRENCY)
+        .try_collect()
+        .await?;
+
+    Ok((summaries, aggregate(&worklogs, account_id, from, to)))
+}
+
+fn parse_started(text: &str) -> Result<String, Error> {
+    let local = dates::parse_local_time(text, dates::now_local())?;
+    Ok(dates::jira_timestamp(local))
+}
+
+/// Sum the seconds logged by `account_id` per (date, issue key) for dates in `from..=to`
+/// (`YYYY-MM-DD`). The date is taken from the worklog's own `started` timestamp.
+fn aggregate(
+    worklogs: &[(String, Vec<Value>)],
+    account_id: &str,
+    from: &str,
+    to: &str,
+) -> BTreeMap<(String, String), i64> {
+    let mut totals = BTreeMap::new();
+    for (key, logs) in worklogs {
+        for w in logs {
+            if w["author"]["accountId"].as_str() != Some(account_id) {
+                continue;
+            }
+            let Some(date) = w["started"].as_str().and_then(|s| s.get(..10)) else {
+                continue;
+            };
+            if date < from || date > to {
+                continue;
+            }
+            *totals.entry((date.to_string(), key.clone())).or_insert(0) +=
+                w["timeSpentSeconds"].as_i64().unwrap_or(0);
+        }
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AuthMode, Config, Deployment, TokenSpec};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_aggregate() {
+        let log = |author: &str, started: &str, seconds: i64| {
+            json!({
+                "author": { "accountId": author },
+                "started": started,
+                "timeSpentSeconds": seconds
+            })
+        };
+        let worklogs = vec![
+            (
+                "PROJ-1".to_string(),
+                vec![
+                    log("me", "2024-03-01T09:00:00.000+0100", 3_600),
+                    log("me", "2024-03-01T14:00:00.000+0100", 1_800),
+                    log("someone", "2024-03-01T10:00:00.000+0100", 7_200),
+                    log("me", "2024-02-20T10:00:00.000+0100", 600),
+                ],
+            ),
+            (
+                "PROJ-2".to_string(),
+                vec![log("me", "2024-03-02T09:00:00.000+0100", 900)],
+            ),
+        ];
+
+        let totals = aggregate(&worklogs, "me", "2024-03-01", "2024-03-07");
+        let totals: Vec<_> = totals.into_iter().collect();
+        assert_eq!(
+            totals,
+            vec![
+                (("2024-03-01".to_string(), "PROJ-1".to_string()), 5_400),
+                (("2024-03-02".to_string(), "PROJ-2".to_string()), 900),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_report_on_data_center_matches_authors_by_name() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/2/myself"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "name": "jdoe", "displayName": "J. Doe" })),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/2/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "startAt": 0, "total": 1,
+                "issues": [{ "key": "PROJ-1", "fields": { "summary": "Fix it" } }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/2/issue/PROJ-1/worklog"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "startAt": 0, "total": 2,
+                "worklogs": [
+                    {
+                        "author": { "name": "jdoe" },
+                        "started": "2024-03-01T09:00:00.000+0100",
+                        "timeSpentSeconds": 3_600
+                    },
+                    {
+                        "author": { "name": "someone" },
+                        "started": "2024-03-01T10:00:00.000+0100",
+                        "timeSpentSeconds": 7_200
+                    }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let config = Config::for_site(&server.uri(), AuthMode::Bearer, Deployment::DataCenter)
+            .with_credentials("", TokenSpec::Plain("secret".to_string()))
+            .unwrap();
+        let client = AtlassianClient::new(config);
+        let (summaries, totals) = collect_report(&client, "2024-03-01", "2024-03-07")
+            .await
+            .unwrap();
+        assert_eq!(summaries["PROJ-1"], "Fix it");
+        let totals: Vec<_> = totals.into_iter().collect();
+        assert_eq!(
+            totals,
+            vec![(("2024-03-01".to_string(), "PROJ-1".to_string()), 3_600)]
+        );
+    }
+}
@@ -1,22 +1,33 @@
+use crate::oauth::{self, OAuthSettings};
+use crate::retry::RetryPolicy;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+/// Where the effective configuration came from. File sources carry the profile that was
+/// read from them, if any.
 #[derive(Debug, Clone)]
 pub enum ConfigSource {
-    LocalFile(PathBuf),
-    GlobalFile(PathBuf),
+    LocalFile(PathBuf, Option<String>),
+    GlobalFile(PathBuf, Option<String>),
     Env,
 }
 
 impl std::fmt::Display for ConfigSource {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ConfigSource::LocalFile(p) => write!(f, "Local file ({})", p.display()),
-            ConfigSource::GlobalFile(p) => write!(f, "Global file ({})", p.display()),
-            ConfigSource::Env => write!(f, "Environment variables"),
+        let (label, path, profile) = match self {
+            ConfigSource::LocalFile(p, profile) => ("Local file", p, profile),
+            ConfigSource::GlobalFile(p, profile) => ("Global file", p, profile),
+            ConfigSource::Env => return write!(f, "Environment variables"),
+        };
+        write!(f, "{} ({})", label, path.display())?;
+        if let Some(profile) = profile {
+            write!(f, ", profile '{}'", profile)?;
         }
+        Ok(())
     }
 }
 
@@ -24,22 +35,272 @@ impl std::fmt::Display for ConfigSource {
 pub struct Config {
     pub user: String,
     pub token: String,
+    /// How the token was obtained.
+    pub token_spec: TokenSpec,
     pub site: String,
+    /// Base URL of Confluence when it is not served from `site` (Data Center).
+    pub confluence_site: Option<String>,
+    pub auth_mode: AuthMode,
+    pub deployment: Deployment,
+    /// The OAuth app, set when `auth_mode` is `oauth`.
+    pub oauth: Option<OAuthSettings>,
     pub default_project: Option<String>,
+    /// The selected profile, if any.
+    pub profile: Option<String>,
     pub source: ConfigSource,
+    pub retry: RetryPolicy,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct FileConfig {
+    /// Profile used when neither `--profile` nor `JIRI_PROFILE` is given.
+    pub default_profile: Option<String>,
+    #[serde(default)]
     pub auth: AuthConfig,
     pub general: Option<GeneralConfig>,
+    pub retry: Option<RetryConfig>,
+    /// `[profiles.<name>]` tables for working with several sites.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+/// A `[profiles.<name>]` table. Its values override `[auth]` and `[general]` of the same file.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct ProfileConfig {
+    #[serde(flatten)]
+    pub auth: AuthConfig,
+    pub default_project: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct AuthConfig {
     pub username: Option<String>,
     pub token: Option<String>,
+    /// Shell command whose standard output is the API token, e.g. `pass show atlassian`.
+    pub token_command: Option<String>,
+    /// File holding the API token. Must not be readable by group or others.
+    pub token_file: Option<String>,
     pub site: Option<String>,
+    /// `basic` (email and API token) or `bearer` (personal access token).
+    pub mode: Option<AuthMode>,
+    /// `cloud` or `datacenter`.
+    pub deployment: Option<Deployment>,
+    /// Confluence base URL, for Data Center installs where it differs from `site`.
+    pub confluence_site: Option<String>,
+    /// OAuth 2.0 (3LO) app credentials, used with `mode = "oauth"`.
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    /// Authorization server and API gateway overrides, e.g. for a local stand-in server.
+    pub oauth_url: Option<String>,
+    pub api_url: Option<String>,
+}
+
+/// How requests are authenticated.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthMode {
+    /// HTTP Basic with the username and API token.
+    #[default]
+    Basic,
+    /// `Authorization: Bearer` with a personal access token.
+    Bearer,
+    /// OAuth 2.0 (3LO) access tokens from `jiri auth login`, refreshed automatically.
+    #[serde(rename = "oauth")]
+    OAuth,
+}
+
+impl std::str::FromStr for AuthMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "basic" => Ok(AuthMode::Basic),
+            "bearer" | "pat" => Ok(AuthMode::Bearer),
+            "oauth" => Ok(AuthMode::OAuth),
+            _ => Err(format!(
+                "Invalid auth mode '{}'. Use basic, bearer or oauth",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for AuthMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthMode::Basic => write!(f, "basic"),
+            AuthMode::Bearer => write!(f, "bearer"),
+            AuthMode::OAuth => write!(f, "oauth"),
+        }
+    }
+}
+
+/// Which Atlassian product flavor the site runs. Data Center uses the Jira v2 REST API with
+/// wiki markup bodies and the Confluence v1 content API.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Deployment {
+    #[default]
+    Cloud,
+    #[serde(alias = "server")]
+    DataCenter,
+}
+
+impl std::str::FromStr for Deployment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "cloud" => Ok(Deployment::Cloud),
+            "datacenter" | "server" => Ok(Deployment::DataCenter),
+            _ => Err(format!(
+                "Invalid deployment '{}'. Use cloud or datacenter",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for Deployment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Deployment::Cloud => write!(f, "cloud"),
+            Deployment::DataCenter => write!(f, "datacenter"),
+        }
+    }
+}
+
+impl AuthConfig {
+    /// The token setting of this table. At most one of `token`, `token_command` and
+    /// `token_file` may be set.
+    pub fn token_spec(&self) -> Result<Option<TokenSpec>, String> {
+        let specs: Vec<TokenSpec> = [
+            self.token.clone().map(TokenSpec::Plain),
+            self.token_command.clone().map(TokenSpec::Command),
+            self.token_file.clone().map(TokenSpec::File),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if specs.len() > 1 {
+            return Err("Set only one of token, token_command and token_file".to_string());
+        }
+        Ok(specs.into_iter().next())
+    }
+
+    /// Set one token mechanism, clearing the others so the table stays unambiguous.
+    pub fn set_token(&mut self, spec: TokenSpec) {
+        self.token = None;
+        self.token_command = None;
+        self.token_file = None;
+        match spec {
+            TokenSpec::Plain(t) => self.token = Some(t),
+            TokenSpec::Command(c) => self.token_command = Some(c),
+            TokenSpec::File(f) => self.token_file = Some(f),
+        }
+    }
+
+    /// How this table supplies its token, for display. Never includes the token itself.
+    pub fn describe_token(&self) -> String {
+        match self.token_spec() {
+            Ok(Some(spec)) => spec.describe(),
+            Ok(None) => String::new(),
+            Err(e) => e,
+        }
+    }
+}
+
+/// Where the API token is read from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenSpec {
+    /// The token itself, from the config file or `JIRA_API_TOKEN`.
+    Plain(String),
+    /// A shell command printing the token.
+    Command(String),
+    /// A file holding the token.
+    File(String),
+}
+
+impl TokenSpec {
+    /// e.g. `token_command (pass show atlassian)`. Plain tokens are masked.
+    pub fn describe(&self) -> String {
+        match self {
+            TokenSpec::Plain(token) => mask_token(token),
+            TokenSpec::Command(command) => format!("token_command ({})", command),
+            TokenSpec::File(path) => format!("token_file ({})", path),
+        }
+    }
+
+    /// Obtain the token: run the command or read the file.
+    fn resolve(&self) -> Result<String, String> {
+        let token = match self {
+            TokenSpec::Plain(token) => return Ok(token.clone()),
+            TokenSpec::Command(command) => run_token_command(command)?,
+            TokenSpec::File(path) => read_token_file(&expand_home(path))?,
+        };
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            return Err(format!("{} produced an empty token", self.describe()));
+        }
+        Ok(token)
+    }
+}
+
+/// Run `token_command` and return the first line of its output. Password managers like
+/// `pass` print the secret first and metadata (user, URL, ...) on the following lines.
+fn run_token_command(command: &str) -> Result<String, String> {
+    let output = if cfg!(windows) {
+        std::process::Command::new("cmd")
+            .args(["/C", command])
+            .output()
+    } else {
+        std::process::Command::new("sh")
+            .args(["-c", command])
+            .output()
+    }
+    .map_err(|e| format!("Could not run token_command '{}': {}", command, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "token_command '{}' failed ({}): {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let stdout = String::from_utf8(output.stdout)
+        .map_err(|_| format!("token_command '{}' printed invalid UTF-8", command))?;
+    Ok(stdout.lines().next().unwrap_or_default().to_string())
+}
+
+/// Read a token file, refusing files that other users could read.
+fn read_token_file(path: &Path) -> Result<String, String> {
+    let metadata = fs::metadata(path)
+        .map_err(|e| format!("Could not read token_file {}: {}", path.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = metadata.permissions().mode() & 0o777;
+        if mode & 0o077 != 0 {
+            return Err(format!(
+                "token_file {} is accessible by group or others (mode {:o}). Run: chmod 600 {}",
+                path.display(),
+                mode,
+                path.display()
+            ));
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = metadata;
+    fs::read_to_string(path)
+        .map_err(|e| format!("Could not read token_file {}: {}", path.display(), e))
+}
+
+/// Expand a leading `~/` to the home directory.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
 }
 
 #[derive(Serialize, Deserialize, Default, Debug)]
@@ -47,6 +308,33 @@ pub struct GeneralConfig {
     pub default_project: Option<String>,
 }
 
+/// `[retry]` table controlling how 429/503 responses are retried.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct RetryConfig {
+    pub max_retries: Option<u32>,
+    pub base_delay_ms: Option<u64>,
+    pub max_delay_ms: Option<u64>,
+    pub max_total_wait_secs: Option<u64>,
+}
+
+impl RetryConfig {
+    /// Override the fields of `policy` that are set in this table.
+    pub fn apply(&self, policy: &mut RetryPolicy) {
+        if let Some(n) = self.max_retries {
+            policy.max_retries = n;
+        }
+        if let Some(ms) = self.base_delay_ms {
+            policy.base_delay = Duration::from_millis(ms);
+        }
+        if let Some(ms) = self.max_delay_ms {
+            policy.max_delay = Duration::from_millis(ms);
+        }
+        if let Some(secs) = self.max_total_wait_secs {
+            policy.max_total_wait = Duration::from_secs(secs);
+        }
+    }
+}
+
 impl FileConfig {
     pub fn load_path(path: &PathBuf) -> Result<Self, String> {
         if !path.exists() {
@@ -58,145 +346,379 @@ impl FileConfig {
             .map_err(|e| format!("Invalid config at {}: {}", path.display(), e))
     }
 
+    /// Write the file readable by the owner only, since it may hold tokens.
     pub fn save_path(&self, path: &PathBuf) -> Result<(), String> {
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
         let contents = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
-        fs::write(path, contents).map_err(|e| e.to_string())
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            // The mode only applies to new files; tighten an existing one as well
+            options.mode(0o600);
+            if path.exists() {
+                fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        let mut file = options.open(path).map_err(|e| e.to_string())?;
+        std::io::Write::write_all(&mut file, contents.as_bytes()).map_err(|e| e.to_string())
     }
 }
 
-impl Config {
-    /// Load config with layering: Env < Global < Local.
-    pub fn load() -> Result<Self, String> {
-        // 1. Try to build a base from any source that provides a complete configuration.
-        // We try them in order of priority (lowest to highest) as a fallback mechanism,
-        // but the layering below will ensure the correct final priority.
-        let mut config = Self::from_env()
-            .or_else(|_| Self::from_global_file())
-            .or_else(|_| Self::from_local_file())
-            .map_err(|e| format!("Could not find a complete configuration source: {}", e))?;
-
-        // 2. Layer Global overrides if they exist
-        if let Some(global_path) = Self::global_config_path() {
-            if global_path.exists() {
-                if let Ok(global_file) = FileConfig::load_path(&global_path) {
-                    if let Some(u) = global_file.auth.username {
-                        config.user = u;
-                        config.source = ConfigSource::GlobalFile(global_path.clone());
-                    }
-                    if let Some(t) = global_file.auth.token {
-                        config.token = t;
-                        config.source = ConfigSource::GlobalFile(global_path.clone());
-                    }
-                    if let Some(s) = global_file.auth.site {
-                        config.site = s;
-                        config.source = ConfigSource::GlobalFile(global_path.clone());
-                    }
-                    if let Some(g) = global_file.general {
-                        if let Some(p) = g.default_project {
-                            config.default_project = Some(p);
-                            config.source = ConfigSource::GlobalFile(global_path.clone());
-                        }
-                    }
+/// Settings collected while layering sources, before checking that auth is complete.
+#[derive(Default)]
+struct Layered {
+    user: Option<String>,
+    token: Option<TokenSpec>,
+    site: Option<String>,
+    confluence_site: Option<String>,
+    mode: Option<AuthMode>,
+    deployment: Option<Deployment>,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    oauth_url: Option<String>,
+    api_url: Option<String>,
+    default_project: Option<String>,
+    source: Option<ConfigSource>,
+    retry: RetryPolicy,
+}
+
+impl Layered {
+    /// Override with the values set in `auth`, recording `source` if any were set.
+    fn apply(
+        &mut self,
+        auth: &AuthConfig,
+        default_project: Option<&String>,
+        source: &ConfigSource,
+    ) -> Result<(), String> {
+        let token = auth
+            .token_spec()
+            .map_err(|e| format!("{} in {}", e, source))?;
+        if let Some(token) = token {
+            self.token = Some(token);
+            self.source = Some(source.clone());
+        }
+        let values = [
+            (&mut self.user, &auth.username),
+            (&mut self.site, &auth.site),
+            (&mut self.confluence_site, &auth.confluence_site),
+            (&mut self.client_id, &auth.client_id),
+            (&mut self.client_secret, &auth.client_secret),
+            (&mut self.oauth_url, &auth.oauth_url),
+            (&mut self.api_url, &auth.api_url),
+            (&mut self.default_project, &default_project.cloned()),
+        ];
+        for (slot, value) in values {
+            if let Some(value) = value {
+                *slot = Some(value.clone());
+                self.source = Some(source.clone());
+            }
+        }
+        if auth.mode.is_some() {
+            self.mode = auth.mode;
+        }
+        if auth.deployment.is_some() {
+            self.deployment = auth.deployment;
+        }
+        Ok(())
+    }
+
+    /// Layer config files, lowest precedence first: the `[auth]`, `[general]` and `[retry]`
+    /// tables of every file, then the selected profile of every file, so a profile is never
+    /// overridden by a plain table. Returns whether any file defines that profile.
+    fn apply_files(
+        &mut self,
+        files: &[(&FileConfig, &Path, bool)],
+        profile: Option<&str>,
+    ) -> Result<bool, String> {
+        let source = |path: &Path, local: bool, profile: Option<&str>| {
+            let profile = profile.map(|p| p.to_string());
+            if local {
+                ConfigSource::LocalFile(path.to_path_buf(), profile)
+            } else {
+                ConfigSource::GlobalFile(path.to_path_buf(), profile)
+            }
+        };
+        for &(file, path, local) in files {
+            self.apply(
+                &file.auth,
+                file.general
+                    .as_ref()
+                    .and_then(|g| g.default_project.as_ref()),
+                &source(path, local, None),
+            )?;
+            if let Some(r) = &file.retry {
+                r.apply(&mut self.retry);
+            }
+        }
+
+        let Some(name) = profile else {
+            return Ok(false);
+        };
+        let mut found = false;
+        for &(file, path, local) in files {
+            let Some(p) = file.profiles.get(name) else {
+                continue;
+            };
+            // A token belongs to its site: a profile for another site must bring its own
+            if let (Some(current), Some(site)) = (&self.site, &p.auth.site) {
+                if current.trim_end_matches('/') != site.trim_end_matches('/') {
+                    self.token = None;
                 }
             }
+            self.apply(
+                &p.auth,
+                p.default_project.as_ref(),
+                &source(path, local, Some(name)),
+            )?;
+            found = true;
         }
+        Ok(found)
+    }
+}
 
-        // 3. Layer Local overrides if they exist
+impl Config {
+    /// Load config with layering: Env < Global < Local, and the selected profile (global,
+    /// then local) over all of them.
+    ///
+    /// The profile is `profile` if given, else `JIRI_PROFILE`, else `default_profile` from the
+    /// local or global file.
+    pub fn load(profile: Option<&str>) -> Result<Self, String> {
+        let global = match Self::global_config_path().filter(|p| p.exists()) {
+            Some(path) => Some((FileConfig::load_path(&path)?, path)),
+            None => None,
+        };
         let local_path = Self::local_config_path();
-        if local_path.exists() {
-            if let Ok(local_file) = FileConfig::load_path(&local_path) {
-                if let Some(u) = local_file.auth.username {
-                    config.user = u;
-                    config.source = ConfigSource::LocalFile(local_path.clone());
-                }
-                if let Some(t) = local_file.auth.token {
-                    config.token = t;
-                    config.source = ConfigSource::LocalFile(local_path.clone());
-                }
-                if let Some(s) = local_file.auth.site {
-                    config.site = s;
-                    config.source = ConfigSource::LocalFile(local_path.clone());
-                }
-                if let Some(g) = local_file.general {
-                    if let Some(p) = g.default_project {
-                        config.default_project = Some(p);
-                        config.source = ConfigSource::LocalFile(local_path.clone());
-                    }
-                }
+        let local = if local_path.exists() {
+            Some((FileConfig::load_path(&local_path)?, local_path))
+        } else {
+            None
+        };
+
+        let profile = profile
+            .map(|p| p.to_string())
+            .or_else(Self::env_profile)
+            .or_else(|| local.as_ref().and_then(|(f, _)| f.default_profile.clone()))
+            .or_else(|| global.as_ref().and_then(|(f, _)| f.default_profile.clone()));
+
+        let files: Vec<(&FileConfig, &Path, bool)> = [
+            global.as_ref().map(|(f, p)| (f, p.as_path(), false)),
+            local.as_ref().map(|(f, p)| (f, p.as_path(), true)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let mut layered = Self::from_env()?;
+        let found = layered.apply_files(&files, profile.as_deref())?;
+
+        if let Some(name) = &profile {
+            if !found {
+                let defined: Vec<&String> = [&global, &local]
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|(f, _)| f.profiles.keys())
+                    .collect();
+                return Err(if defined.is_empty() {
+                    format!(
+                        "Profile '{}' not found: no [profiles.*] tables are configured",
+                        name
+                    )
+                } else {
+                    format!(
+                        "Profile '{}' not found. Available profiles: {}",
+                        name,
+                        defined
+                            .iter()
+                            .map(|p| p.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                });
             }
         }
 
-        Ok(config)
+        Self::complete(layered, profile)
     }
 
-    pub fn local_config_path() -> PathBuf {
-        PathBuf::from("jiri.toml")
+    /// Build the final config, naming every missing auth setting.
+    fn complete(layered: Layered, profile: Option<String>) -> Result<Self, String> {
+        let mode = layered.mode.unwrap_or_default();
+        let deployment = layered.deployment.unwrap_or_default();
+        if mode == AuthMode::OAuth {
+            return Self::complete_oauth(layered, profile);
+        }
+        let missing: Vec<&str> = [
+            // Personal access tokens identify the user on their own
+            (
+                "auth.username",
+                layered.user.is_some() || mode == AuthMode::Bearer,
+            ),
+            ("auth.token", layered.token.is_some()),
+            ("auth.site", layered.site.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, set)| !set)
+        .map(|(key, _)| key)
+        .collect();
+        if !missing.is_empty() {
+            return Err(format!(
+                "Could not find a complete configuration source: missing {}. Set it with \
+                 `jiri config set` or the JIRA_API_USERNAME, JIRA_API_TOKEN and JIRA_SITE \
+                 environment variables",
+                missing.join(", ")
+            ));
+        }
+
+        let token_spec = layered.token.unwrap_or(TokenSpec::Plain(String::new()));
+        Ok(Config {
+            user: layered.user.unwrap_or_default(),
+            token: token_spec.resolve()?,
+            token_spec,
+            site: layered.site.unwrap_or_default(),
+            confluence_site: layered.confluence_site,
+            auth_mode: mode,
+            deployment,
+            oauth: None,
+            default_project: layered.default_project,
+            profile,
+            source: layered.source.unwrap_or(ConfigSource::Env),
+            retry: layered.retry,
+        })
     }
 
-    fn from_local_file() -> Result<Self, String> {
-        let path = Self::local_config_path();
-        if !path.exists() {
-            return Err("Local config not found".to_string());
+    /// Build an OAuth config. Only the app's client ID is required: the tokens come from
+    /// `jiri auth login`, and the site defaults to the one chosen there.
+    fn complete_oauth(layered: Layered, profile: Option<String>) -> Result<Self, String> {
+        if layered.deployment == Some(Deployment::DataCenter) {
+            return Err("OAuth 2.0 (3LO) login is only available on Cloud".to_string());
         }
-        Self::parse_file(path.clone(), ConfigSource::LocalFile(path))
+        let client_id = layered.client_id.ok_or(
+            "Could not find a complete configuration source: missing auth.client_id, \
+             which OAuth needs. Set it with `jiri config set auth.client_id <id>`",
+        )?;
+        let store = Self::oauth_session_path(profile.as_deref())
+            .ok_or("Could not determine where to store the OAuth session")?;
+        let site = match layered.site {
+            Some(site) => site,
+            None => oauth::Session::load(&store)?
+                .map(|s| s.site)
+                .unwrap_or_default(),
+        };
+
+        Ok(Config {
+            user: layered.user.unwrap_or_default(),
+            token: String::new(),
+            token_spec: TokenSpec::Plain(String::new()),
+            site,
+            confluence_site: layered.confluence_site,
+            auth_mode: AuthMode::OAuth,
+            deployment: Deployment::Cloud,
+            oauth: Some(OAuthSettings {
+                client_id,
+                client_secret: layered.client_secret,
+                oauth_url: layered
+                    .oauth_url
+                    .unwrap_or_else(|| oauth::DEFAULT_OAUTH_URL.to_string()),
+                api_url: layered
+                    .api_url
+                    .unwrap_or_else(|| oauth::DEFAULT_API_URL.to_string()),
+                store,
+            }),
+            default_project: layered.default_project,
+            profile,
+            source: layered.source.unwrap_or(ConfigSource::Env),
+            retry: layered.retry,
+        })
     }
 
-    pub fn global_config_path() -> Option<PathBuf> {
-        dirs::config_dir().map(|d| d.join("jiri").join("config.toml"))
+    /// A config for `site` that is not read from any file, without credentials yet. Used to
+    /// check answers before they are saved, and by tests.
+    pub fn for_site(site: &str, auth_mode: AuthMode, deployment: Deployment) -> Self {
+        Config {
+            user: String::new(),
+            token: String::new(),
+            token_spec: TokenSpec::Plain(String::new()),
+            site: site.to_string(),
+            confluence_site: None,
+            auth_mode,
+            deployment,
+            oauth: None,
+            default_project: None,
+            profile: None,
+            source: ConfigSource::Env,
+            retry: RetryPolicy::default(),
+        }
     }
 
-    fn from_global_file() -> Result<Self, String> {
-        let path = Self::global_config_path().ok_or("Could not determine config directory")?;
+    /// Set the user and token, resolving `token_spec` (running a command or reading a file).
+    pub fn with_credentials(mut self, user: &str, token_spec: TokenSpec) -> Result<Self, String> {
+        self.user = user.to_string();
+        self.token = token_spec.resolve()?;
+        self.token_spec = token_spec;
+        Ok(self)
+    }
 
-        if !path.exists() {
-            return Err("Global config not found".to_string());
+    /// Where the credentials come from, for display: the token mechanism, or the OAuth
+    /// session store and whether it holds a login.
+    pub fn describe_credentials(&self) -> String {
+        match &self.oauth {
+            Some(oauth) => format!(
+                "OAuth session in {} ({})",
+                oauth.store.display(),
+                if oauth.store.exists() {
+                    "logged in"
+                } else {
+                    "not logged in; run `jiri auth login`"
+                }
+            ),
+            None => self.token_spec.describe(),
         }
-        Self::parse_file(path.clone(), ConfigSource::GlobalFile(path))
     }
 
-    fn parse_file(path: PathBuf, source: ConfigSource) -> Result<Self, String> {
-        let file_config = FileConfig::load_path(&path)?;
+    /// Where `jiri auth login` stores the tokens of a profile.
+    pub fn oauth_session_path(profile: Option<&str>) -> Option<PathBuf> {
+        dirs::config_dir().map(|d| {
+            d.join("jiri")
+                .join("oauth")
+                .join(format!("{}.json", profile.unwrap_or("default")))
+        })
+    }
 
-        let user = file_config
-            .auth
-            .username
-            .ok_or_else(|| format!("Missing auth.username in {}", path.display()))?;
-        let token = file_config
-            .auth
-            .token
-            .ok_or_else(|| format!("Missing auth.token in {}", path.display()))?;
-        let site = file_config
-            .auth
-            .site
-            .ok_or_else(|| format!("Missing auth.site in {}", path.display()))?;
+    /// The profile named by `JIRI_PROFILE`, if set and non-empty.
+    pub fn env_profile() -> Option<String> {
+        env::var("JIRI_PROFILE").ok().filter(|p| !p.is_empty())
+    }
 
-        Ok(Config {
-            user,
-            token,
-            site,
-            default_project: file_config.general.and_then(|g| g.default_project),
-            source,
-        })
+    pub fn local_config_path() -> PathBuf {
+        PathBuf::from("jiri.toml")
     }
 
-    fn from_env() -> Result<Self, String> {
-        let user = env::var("JIRA_API_USERNAME")
-            .map_err(|_| "Missing JIRA_API_USERNAME environment variable")?;
-        let token = env::var("JIRA_API_TOKEN")
-            .map_err(|_| "Missing JIRA_API_TOKEN environment variable")?;
-        let site = env::var("JIRA_SITE").map_err(|_| "Missing JIRA_SITE environment variable")?;
-        let default_project = env::var("JIRA_DEFAULT_PROJECT").ok();
+    pub fn global_config_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("jiri").join("config.toml"))
+    }
 
-        Ok(Config {
-            user,
-            token,
-            site,
-            default_project,
-            source: ConfigSource::Env,
-        })
+    fn from_env() -> Result<Layered, String> {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+        let mut layered = Layered::default();
+        let auth = AuthConfig {
+            username: var("JIRA_API_USERNAME"),
+            token: var("JIRA_API_TOKEN"),
+            site: var("JIRA_SITE"),
+            confluence_site: var("JIRA_CONFLUENCE_SITE"),
+            mode: var("JIRA_AUTH_MODE").map(|m| m.parse()).transpose()?,
+            deployment: var("JIRA_DEPLOYMENT").map(|d| d.parse()).transpose()?,
+            ..Default::default()
+        };
+        layered.apply(
+            &auth,
+            var("JIRA_DEFAULT_PROJECT").as_ref(),
+            &ConfigSource::Env,
+        )?;
+        Ok(layered)
     }
 }
 
@@ -206,3 +728,237 @@ pub fn mask_token(token: &str) -> String {
     }
     format!("{}...{}", &token[..4], &token[token.len() - 4..])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(toml: &str) -> FileConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_profiles_override_file_tables() {
+        let global = file(
+            r#"
+            default_profile = "prod"
+
+            [auth]
+            username = "me@example.com"
+            token = "base-token"
+            site = "https://base.atlassian.net"
+
+            [general]
+            default_project = "BASE"
+
+            [profiles.prod]
+            site = "https://prod.atlassian.net"
+
+            [profiles.sandbox]
+            token = "sandbox-token"
+            site = "https://sandbox.atlassian.net"
+            default_project = "SBX"
+            "#,
+        );
+        let path = PathBuf::from("/home/me/.config/jiri/config.toml");
+
+        let mut layered = Layered::default();
+        assert!(layered
+            .apply_files(&[(&global, path.as_path(), false)], Some("sandbox"))
+            .unwrap());
+        let config = Config::complete(layered, Some("sandbox".to_string())).unwrap();
+        assert_eq!(config.user, "me@example.com");
+        assert_eq!(config.token, "sandbox-token");
+        assert_eq!(config.site, "https://sandbox.atlassian.net");
+        assert_eq!(config.default_project.as_deref(), Some("SBX"));
+        assert_eq!(
+            config.source.to_string(),
+            "Global file (/home/me/.config/jiri/config.toml), profile 'sandbox'"
+        );
+
+        let mut layered = Layered::default();
+        assert!(!layered
+            .apply_files(&[(&global, path.as_path(), false)], Some("staging"))
+            .unwrap());
+        let mut layered = Layered::default();
+        assert!(!layered
+            .apply_files(&[(&global, path.as_path(), false)], None)
+            .unwrap());
+        assert_eq!(layered.site.as_deref(), Some("https://base.atlassian.net"));
+    }
+
+    #[test]
+    fn test_local_layer_wins_and_missing_keys_are_named() {
+        let global =
+            file("[auth]\nusername = \"me@example.com\"\nsite = \"https://a.atlassian.net\"");
+        let local = file("[profiles.work]\nsite = \"https://b.atlassian.net\"");
+
+        let mut layered = Layered::default();
+        let files = [
+            (&global, Path::new("global.toml"), false),
+            (&local, Path::new("jiri.toml"), true),
+        ];
+        assert!(layered.apply_files(&files, Some("work")).unwrap());
+        assert_eq!(layered.site.as_deref(), Some("https://b.atlassian.net"));
+
+        let err = Config::complete(layered, Some("work".to_string())).unwrap_err();
+        assert!(err.contains("missing auth.token"), "{}", err);
+    }
+
+    #[test]
+    fn test_profiles_win_over_later_tables_and_keep_tokens_to_their_site() {
+        let global = file(
+            r#"
+            [auth]
+            username = "me@example.com"
+            token = "base-token"
+            site = "https://a.atlassian.net"
+
+            [profiles.other]
+            site = "https://b.atlassian.net"
+
+            [profiles.same]
+            site = "https://a.atlassian.net/"
+            default_project = "SAME"
+            "#,
+        );
+        let local = file("[general]\ndefault_project = \"LOCAL\"");
+        let files = [
+            (&global, Path::new("global.toml"), false),
+            (&local, Path::new("jiri.toml"), true),
+        ];
+
+        let mut layered = Layered::default();
+        assert!(layered.apply_files(&files, Some("same")).unwrap());
+        let config = Config::complete(layered, Some("same".to_string())).unwrap();
+        assert_eq!(config.default_project.as_deref(), Some("SAME"));
+        assert_eq!(config.token, "base-token");
+
+        let mut layered = Layered::default();
+        assert!(layered.apply_files(&files, Some("other")).unwrap());
+        assert_eq!(layered.default_project.as_deref(), Some("LOCAL"));
+        let err = Config::complete(layered, Some("other".to_string())).unwrap_err();
+        assert!(err.contains("missing auth.token"), "{}", err);
+    }
+
+    #[test]
+    fn test_bearer_datacenter_profile() {
+        let global = file(
+            r#"
+            [profiles.dc]
+            mode = "bearer"
+            deployment = "datacenter"
+            token = "personal-access-token"
+            site = "https://jira.example.com"
+            confluence_site = "https://confluence.example.com"
+            "#,
+        );
+        let mut layered = Layered::default();
+        layered
+            .apply_files(&[(&global, Path::new("config.toml"), false)], Some("dc"))
+            .unwrap();
+        let config = Config::complete(layered, Some("dc".to_string())).unwrap();
+        assert_eq!(config.auth_mode, AuthMode::Bearer);
+        assert_eq!(config.deployment, Deployment::DataCenter);
+        assert_eq!(config.user, "");
+        assert_eq!(
+            config.confluence_site.as_deref(),
+            Some("https://confluence.example.com")
+        );
+
+        assert_eq!("PAT".parse::<AuthMode>().unwrap(), AuthMode::Bearer);
+        assert_eq!(
+            "server".parse::<Deployment>().unwrap(),
+            Deployment::DataCenter
+        );
+        assert!("digest".parse::<AuthMode>().is_err());
+    }
+
+    #[test]
+    fn test_oauth_needs_only_client_id() {
+        let global = file(
+            r#"
+            [profiles.cloud]
+            mode = "oauth"
+            client_id = "app-id"
+            oauth_url = "http://127.0.0.1:9999"
+            "#,
+        );
+        let mut layered = Layered::default();
+        layered
+            .apply_files(&[(&global, Path::new("config.toml"), false)], Some("cloud"))
+            .unwrap();
+        let config = Config::complete(layered, Some("cloud".to_string())).unwrap();
+        assert_eq!(config.auth_mode, AuthMode::OAuth);
+        let oauth = config.oauth.unwrap();
+        assert_eq!(oauth.client_id, "app-id");
+        assert_eq!(oauth.oauth_url, "http://127.0.0.1:9999");
+        assert_eq!(oauth.api_url, crate::oauth::DEFAULT_API_URL);
+        assert!(oauth.store.ends_with("oauth/cloud.json"));
+
+        let no_client = file(
+            "[auth]
+mode = \"oauth\"",
+        );
+        let mut layered = Layered::default();
+        layered
+            .apply_files(&[(&no_client, Path::new("config.toml"), false)], None)
+            .unwrap();
+        let err = Config::complete(layered, None).unwrap_err();
+        assert!(err.contains("auth.client_id"), "{}", err);
+    }
+
+    #[test]
+    fn test_token_mechanisms() {
+        let both = file("[auth]\ntoken = \"abc\"\ntoken_command = \"echo abc\"");
+        let mut layered = Layered::default();
+        assert!(layered
+            .apply_files(&[(&both, Path::new("jiri.toml"), true)], None)
+            .is_err());
+
+        let mut auth = AuthConfig::default();
+        auth.set_token(TokenSpec::Plain("secret-token-value".to_string()));
+        auth.set_token(TokenSpec::Command("echo ' cmd-token '".to_string()));
+        assert_eq!(auth.token, None);
+        assert_eq!(auth.describe_token(), "token_command (echo ' cmd-token ')");
+        #[cfg(unix)]
+        assert_eq!(
+            auth.token_spec().unwrap().unwrap().resolve().unwrap(),
+            "cmd-token"
+        );
+
+        #[cfg(unix)]
+        assert_eq!(
+            TokenSpec::Command("printf 'pass-token\\nlogin: me\\nurl: x\\n'".to_string())
+                .resolve()
+                .unwrap(),
+            "pass-token"
+        );
+        #[cfg(unix)]
+        assert!(TokenSpec::Command("echo; echo late-token".to_string())
+            .resolve()
+            .is_err());
+        assert!(TokenSpec::Command("exit 3".to_string()).resolve().is_err());
+        assert_eq!(
+            TokenSpec::Plain("secret-token-value".to_string()).describe(),
+            "secr...alue"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_token_file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let path = std::env::temp_dir().join(format!("jiri-token-test-{}", std::process::id()));
+        fs::write(&path, "file-token\n").unwrap();
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        let spec = TokenSpec::File(path.display().to_string());
+        let err = spec.resolve().unwrap_err();
+        assert!(err.contains("chmod 600"), "{}", err);
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        assert_eq!(spec.resolve().unwrap(), "file-token");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,253 @@
+use chrono::{
+    DateTime, Days, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc,
+};
+
+const MINUTE: i64 = 60;
+const HOUR: i64 = 60 * MINUTE;
+pub const DAY: i64 = 24 * HOUR;
+/// Jira's default working day and week, used for `d` and `w` in durations.
+const WORK_DAY: i64 = 8 * HOUR;
+const WORK_WEEK: i64 = 5 * WORK_DAY;
+
+/// Format a Unix timestamp in milliseconds as `YYYY-MM-DD` (UTC).
+pub fn format_date(millis: i64) -> String {
+    DateTime::from_timestamp_millis(millis)
+        .map(|t| t.date_naive().to_string())
+        .unwrap_or_default()
+}
+
+/// Parse `YYYY-MM-DD`, `today` or `yesterday`.
+pub fn parse_day(text: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    match text.trim().to_lowercase().as_str() {
+        "today" => Ok(today),
+        "yesterday" => Ok(today - Days::new(1)),
+        other => NaiveDate::parse_from_str(other, "%Y-%m-%d").map_err(|_| {
+            format!(
+                "'{}' is not a date (expected YYYY-MM-DD, today or yesterday)",
+                text
+            )
+        }),
+    }
+}
+
+/// Parse a Jira duration such as `1h30m`, `2d`, `1w 2d` or `1.5h` into seconds.
+///
+/// Uses Jira's default time tracking settings: 1d = 8h and 1w = 5d.
+pub fn parse_duration(text: &str) -> Result<i64, String> {
+    let invalid = || {
+        format!(
+            "'{}' is not a duration (expected e.g. 1h30m, 45m, 2d, 1w)",
+            text
+        )
+    };
+    let mut total = 0.0;
+    let mut number = String::new();
+    let mut seen_unit = false;
+
+    for c in text.trim().chars() {
+        match c {
+            '0'..='9' | '.' => number.push(c),
+            ' ' if number.is_empty() => {}
+            'w' | 'd' | 'h' | 'm' => {
+                let value: f64 = number.parse().map_err(|_| invalid())?;
+                let unit = match c {
+                    'w' => WORK_WEEK,
+                    'd' => WORK_DAY,
+                    'h' => HOUR,
+                    _ => MINUTE,
+                };
+                total += value * unit as f64;
+                number.clear();
+                seen_unit = true;
+            }
+            _ => return Err(invalid()),
+        }
+    }
+
+    if !number.is_empty() || !seen_unit {
+        return Err(invalid());
+    }
+    let seconds = total.round() as i64;
+    if seconds < MINUTE {
+        return Err(format!("'{}' is shorter than one minute", text));
+    }
+    Ok(seconds)
+}
+
+/// Format seconds as hours and minutes, e.g. `7h 30m`.
+pub fn format_duration(seconds: i64) -> String {
+    let minutes = seconds / MINUTE;
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+/// The current Unix time in seconds.
+pub fn now_unix() -> i64 {
+    Utc::now().timestamp()
+}
+
+/// The current local wall-clock time.
+pub fn now_local() -> NaiveDateTime {
+    Local::now().naive_local()
+}
+
+/// Format a Unix time as an ISO 8601 UTC timestamp, e.g. `2024-03-01T14:00:00.000Z`.
+pub fn format_utc(unix: i64) -> String {
+    DateTime::from_timestamp(unix, 0)
+        .map(|t| t.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string())
+        .unwrap_or_default()
+}
+
+/// Shorten a Jira timestamp for display: `2024-03-01T14:00:00.000+0100` → `2024-03-01 14:00`.
+pub fn display_timestamp(timestamp: &str) -> String {
+    timestamp
+        .get(..16)
+        .unwrap_or(timestamp)
+        .replacen('T', " ", 1)
+}
+
+/// Today's local date.
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Parse a start time given as `now`, `HH:MM`, `today|yesterday|YYYY-MM-DD [HH:MM]` or
+/// `YYYY-MM-DDTHH:MM`, relative to the local wall-clock time `now`. A day without a time
+/// means 09:00.
+pub fn parse_local_time(text: &str, now: NaiveDateTime) -> Result<NaiveDateTime, String> {
+    let text = text.trim().to_lowercase();
+    if text == "now" {
+        return Ok(now);
+    }
+
+    let iso_separator = text.len() > 10 && text.as_bytes()[10] == b't';
+    let (day_part, time_part) = match text.split_once(' ') {
+        Some((day, time)) => (Some(day), Some(time.trim())),
+        None if iso_separator => (Some(&text[..10]), Some(&text[11..])),
+        None if text.contains(':') => (None, Some(text.as_str())),
+        None => (Some(text.as_str()), None),
+    };
+
+    let day = match day_part {
+        Some(day) => parse_day(day, now.date())?,
+        None => now.date(),
+    };
+    let time = match time_part {
+        Some(time) => parse_clock(time)
+            .ok_or_else(|| format!("'{}' is not a time of day (expected HH:MM)", time))?,
+        None => NaiveTime::from_hms_opt(9, 0, 0).expect("09:00 is a valid time"),
+    };
+    Ok(day.and_time(time))
+}
+
+fn parse_clock(text: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(text, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(text, "%H:%M"))
+        .ok()
+}
+
+/// Convert a local wall-clock time to a Unix time.
+pub fn unix_from_local(local: NaiveDateTime) -> i64 {
+    from_local(local).timestamp()
+}
+
+/// Format a local wall-clock time as a Jira timestamp, e.g. `2024-03-01T14:00:00.000+0100`.
+pub fn jira_timestamp(local: NaiveDateTime) -> String {
+    from_local(local)
+        .format("%Y-%m-%dT%H:%M:%S%.3f%z")
+        .to_string()
+}
+
+/// Attach the local UTC offset. An ambiguous time (when clocks go back) takes the earlier
+/// offset, and a time skipped when clocks go forward is shifted past the gap.
+fn from_local(local: NaiveDateTime) -> DateTime<Local> {
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .unwrap_or_else(|| {
+            let offset = Local.offset_from_utc_datetime(&local).local_minus_utc();
+            Local.from_utc_datetime(&(local - TimeDelta::seconds(offset as i64)))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_format_days() {
+        assert_eq!(format_date(1_700_000_000_000), "2023-11-14");
+        assert_eq!(format_utc(1_700_000_000), "2023-11-14T22:13:20.000Z");
+        let today = day(2024, 3, 1);
+        assert_eq!(parse_day("2024-02-29", today), Ok(day(2024, 2, 29)));
+        assert_eq!(parse_day("yesterday", today), Ok(day(2024, 2, 29)));
+        assert_eq!(parse_day(" Today ", today), Ok(today));
+        assert!(parse_day("2024-13-01", today).is_err());
+        assert!(parse_day("2024-02-30", today).is_err());
+        assert!(parse_day("2023-02-29", today).is_err());
+        assert!(parse_day("2024-04-31", today).is_err());
+        assert!(parse_day("1900-02-29", today).is_err());
+        assert!(parse_day("last week", today).is_err());
+        assert_eq!(parse_day("2000-02-29", today), Ok(day(2000, 2, 29)));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("1h30m"), Ok(5_400));
+        assert_eq!(parse_duration("1h 30m"), Ok(5_400));
+        assert_eq!(parse_duration("45m"), Ok(2_700));
+        assert_eq!(parse_duration("2d"), Ok(16 * 3_600));
+        assert_eq!(parse_duration("1w 1d"), Ok(48 * 3_600));
+        assert_eq!(parse_duration("1.5h"), Ok(5_400));
+        assert!(parse_duration("90").is_err());
+        assert!(parse_duration("1x").is_err());
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("0m").is_err());
+
+        assert_eq!(format_duration(5_400), "1h 30m");
+        assert_eq!(format_duration(7_200), "2h");
+        assert_eq!(format_duration(2_700), "45m");
+    }
+
+    #[test]
+    fn test_parse_local_time() {
+        let at = |date: NaiveDate, h: u32, m: u32| date.and_hms_opt(h, m, 0).unwrap();
+        let today = day(2024, 3, 1);
+        let now = at(today, 10, 15);
+        assert_eq!(parse_local_time("now", now), Ok(now));
+        assert_eq!(
+            parse_local_time("yesterday 14:00", now),
+            Ok(at(day(2024, 2, 29), 14, 0))
+        );
+        assert_eq!(parse_local_time("8:30", now), Ok(at(today, 8, 30)));
+        assert_eq!(
+            parse_local_time("2024-02-01T17:45", now),
+            parse_local_time("2024-02-01 17:45", now)
+        );
+        assert_eq!(parse_local_time("today", now), Ok(at(today, 9, 0)));
+        assert!(parse_local_time("yesterday 25:00", now).is_err());
+        assert!(parse_local_time("last week", now).is_err());
+
+        let stamp = jira_timestamp(at(today, 14, 0));
+        assert!(stamp.starts_with("2024-03-01T14:00:00.000"), "{}", stamp);
+        assert_eq!(stamp.len(), "2024-03-01T14:00:00.000+0000".len());
+        assert_eq!(display_timestamp(&stamp), "2024-03-01 14:00");
+
+        let end_of_day = day(2024, 3, 2).and_hms_opt(23, 59, 59).unwrap();
+        let unix = unix_from_local(end_of_day);
+        assert_eq!(
+            DateTime::from_timestamp(unix, 0)
+                .unwrap()
+                .with_timezone(&Local)
+                .naive_local(),
+            end_of_day
+        );
+    }
+}
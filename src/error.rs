@@ -0,0 +1,297 @@
+use reqwest::{Method, StatusCode};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Broad category of an error, used for matching and for the process exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// 401/403: missing or invalid credentials, or insufficient permissions.
+    Auth,
+    /// 404: the issue, page or resource does not exist (or is not visible).
+    NotFound,
+    /// 409: e.g. a Confluence version conflict.
+    Conflict,
+    /// 400/422: the request was rejected by server-side validation.
+    Validation,
+    /// 429 after retries were exhausted.
+    RateLimited,
+    /// 5xx responses.
+    Server,
+    /// The request could not be sent or the response could not be read.
+    Transport,
+    /// Local failures: bad input, configuration, unexpected response shape.
+    Other,
+}
+
+impl ErrorKind {
+    /// Process exit code for this kind of error. 2 is left to clap for usage errors.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::Auth => 3,
+            ErrorKind::NotFound => 4,
+            ErrorKind::Conflict => 5,
+            ErrorKind::Validation => 6,
+            ErrorKind::RateLimited => 7,
+            ErrorKind::Server => 8,
+            ErrorKind::Transport => 9,
+        }
+    }
+}
+
+/// A non-success HTTP response from an Atlassian API.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub method: Method,
+    pub url: String,
+    /// Top-level messages (Jira `errorMessages`, Confluence `errors[].title`).
+    pub error_messages: Vec<String>,
+    /// Per-field messages (Jira `errors` object), keyed by field ID.
+    pub errors: BTreeMap<String, String>,
+    /// Raw response body, kept for diagnostics when it could not be parsed.
+    pub body: String,
+}
+
+impl ApiError {
+    /// Build an error from a response status and body, parsing the Atlassian error payload.
+    pub fn new(status: StatusCode, method: Method, url: String, body: String) -> Self {
+        let mut error_messages = Vec::new();
+        let mut errors = BTreeMap::new();
+
+        if let Ok(json) = serde_json::from_str::<Value>(&body) {
+            if let Some(messages) = json["errorMessages"].as_array() {
+                error_messages.extend(messages.iter().filter_map(|m| m.as_str()).map(String::from));
+            }
+            match &json["errors"] {
+                // Jira: { "errors": { "fieldId": "message" } }
+                Value::Object(map) => {
+                    for (field, message) in map {
+                        let message = message
+                            .as_str()
+                            .map(String::from)
+                            .unwrap_or_else(|| message.to_string());
+                        errors.insert(field.clone(), message);
+                    }
+                }
+                // Confluence v2: { "errors": [{ "status": 409, "code": "...", "title": "..." }] }
+                Value::Array(items) => {
+                    for item in items {
+                        let text = item["title"]
+                            .as_str()
+                            .or_else(|| item["detail"].as_str())
+                            .or_else(|| item["code"].as_str());
+                        if let Some(text) = text {
+                            error_messages.push(text.to_string());
+                        }
+                    }
+                }
+                _ => {}
+            }
+            // Confluence v1: { "statusCode": 404, "message": "..." }
+            if error_messages.is_empty() {
+                if let Some(message) = json["message"].as_str() {
+                    error_messages.push(message.to_string());
+                }
+            }
+        }
+
+        Self {
+            status,
+            method,
+            url,
+            error_messages,
+            errors,
+            body,
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self.status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ErrorKind::Auth,
+            StatusCode::NOT_FOUND => ErrorKind::NotFound,
+            StatusCode::CONFLICT => ErrorKind::Conflict,
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => ErrorKind::Validation,
+            StatusCode::TOO_MANY_REQUESTS => ErrorKind::RateLimited,
+            s if s.is_server_error() => ErrorKind::Server,
+            _ => ErrorKind::Other,
+        }
+    }
+
+    /// Whether any message, field error, or the raw body mentions `code`
+    /// (e.g. `ATTACHMENT_VALIDATION_ERROR`).
+    pub fn mentions(&self, code: &str) -> bool {
+        self.error_messages.iter().any(|m| m.contains(code))
+            || self.errors.values().any(|m| m.contains(code))
+            || self.body.contains(code)
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Atlassian request failed ({}) for {} {}",
+            self.status, self.method, self.url
+        )?;
+
+        if self.error_messages.is_empty() && self.errors.is_empty() {
+            if !self.body.trim().is_empty() {
+                write!(f, ": {}", self.body.trim())?;
+            }
+            return Ok(());
+        }
+
+        for message in &self.error_messages {
+            write!(f, "\n  {}", message)?;
+        }
+        for (field, message) in &self.errors {
+            write!(f, "\n  {}: {}", field, message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Error type shared by `AtlassianClient` and the command implementations.
+#[derive(Debug)]
+pub enum Error {
+    /// The API answered with a non-success status.
+    Api(Box<ApiError>),
+    /// The request could not be sent, or the response body could not be read.
+    Transport {
+        method: Method,
+        url: String,
+        message: String,
+    },
+    /// Any other failure, described by a message.
+    Other(String),
+}
+
+impl Error {
+    pub fn transport(method: &Method, url: &str, err: impl fmt::Display) -> Self {
+        Error::Transport {
+            method: method.clone(),
+            url: url.to_string(),
+            message: err.to_string(),
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Api(e) => e.kind(),
+            Error::Transport { .. } => ErrorKind::Transport,
+            Error::Other(_) => ErrorKind::Other,
+        }
+    }
+
+    /// The parsed API error, if any.
+    pub fn api(&self) -> Option<&ApiError> {
+        match self {
+            Error::Api(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        self.kind().exit_code()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Api(e) => e.fmt(f),
+            Error::Transport {
+                method,
+                url,
+                message,
+            } => write!(f, "Request to {} {} failed: {}", method, url, message),
+            Error::Other(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ApiError> for Error {
+    fn from(e: ApiError) -> Self {
+        Error::Api(Box::new(e))
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Other(message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Error::Other(message.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_jira_error_payload() {
+        let body = r#"{"errorMessages":["Issue does not exist"],"errors":{"customfield_10010":"Sprint is required"}}"#;
+        let err = ApiError::new(
+            StatusCode::BAD_REQUEST,
+            Method::POST,
+            "https://example.atlassian.net/rest/api/3/issue".to_string(),
+            body.to_string(),
+        );
+
+        assert_eq!(err.kind(), ErrorKind::Validation);
+        assert_eq!(err.error_messages, vec!["Issue does not exist"]);
+        assert_eq!(err.errors["customfield_10010"], "Sprint is required");
+        assert!(err.mentions("Sprint is required"));
+
+        let text = err.to_string();
+        assert!(text.contains("400 Bad Request"));
+        assert!(text.contains("POST https://example.atlassian.net/rest/api/3/issue"));
+        assert!(text.contains("customfield_10010: Sprint is required"));
+    }
+
+    #[test]
+    fn test_parses_confluence_error_payload() {
+        let body = r#"{"errors":[{"status":409,"code":"CONFLICT","title":"Version must be incremented"}]}"#;
+        let err: Error = ApiError::new(
+            StatusCode::CONFLICT,
+            Method::PUT,
+            "https://example.atlassian.net/wiki/api/v2/pages/1".to_string(),
+            body.to_string(),
+        )
+        .into();
+
+        assert_eq!(err.kind(), ErrorKind::Conflict);
+        assert_eq!(err.exit_code(), 5);
+        assert_eq!(
+            err.api().unwrap().error_messages,
+            vec!["Version must be incremented"]
+        );
+    }
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let kinds = [
+            ErrorKind::Other,
+            ErrorKind::Auth,
+            ErrorKind::NotFound,
+            ErrorKind::Conflict,
+            ErrorKind::Validation,
+            ErrorKind::RateLimited,
+            ErrorKind::Server,
+            ErrorKind::Transport,
+        ];
+        let mut codes: Vec<i32> = kinds.iter().map(|k| k.exit_code()).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), kinds.len());
+        assert!(!codes.contains(&0) && !codes.contains(&2));
+    }
+}
@@ -1,4 +1,4 @@
-use serde_json::Value;
+use serde_json::{json, Value};
 
 /// Normalize a Jira field value into a human-readable string.
 /// Handles strings, numbers, booleans, arrays, and complex objects (e.g., users, status).
@@ -69,3 +69,230 @@ pub fn get_field_value(issue: &Value, key: &str) -> String {
     let val = &issue["fields"][key];
     normalize_value(val)
 }
+
+/// Split a `--field "Name=value"` argument into its name and raw value.
+pub fn parse_assignment(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!(
+            "Invalid field '{}'. Expected NAME=VALUE, e.g. \"Story Points=5\"",
+            arg
+        )),
+    }
+}
+
+/// Whether values for this schema are Jira users (single or multi user pickers).
+pub fn is_user_schema(schema: &Value) -> bool {
+    schema["type"] == "user" || (schema["type"] == "array" && schema["items"] == "user")
+}
+
+/// Convert a raw command-line value into the JSON Jira expects for a field with this schema.
+///
+/// Multi-value fields take comma-separated values, cascading selects take `Parent > Child`,
+/// and an empty value clears the field. User values must already be account IDs.
+pub fn coerce_value(schema: &Value, raw: &str) -> Result<Value, String> {
+    let field_type = schema["type"].as_str().unwrap_or("string");
+
+    if field_type == "array" {
+        let item_type = schema["items"].as_str().unwrap_or("string");
+        let items = raw
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| coerce_scalar(item_type, schema, s))
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(Value::Array(items));
+    }
+
+    if raw.is_empty() {
+        return Ok(Value::Null);
+    }
+    coerce_scalar(field_type, schema, raw)
+}
+
+/// Jira `update` operations for a multi-value field whose items are prefixed with `+` (add)
+/// or `-` (remove), e.g. `+urgent,-triage`. Returns `None` when no item is prefixed, in which
+/// case the value replaces the whole field as usual.
+pub fn update_ops(schema: &Value, raw: &str) -> Result<Option<Vec<Value>>, String> {
+    if schema["type"] != "array" {
+        return Ok(None);
+    }
+    let items: Vec<&str> = raw
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let prefixed = items.iter().filter(|i| i.starts_with(['+', '-'])).count();
+    if prefixed == 0 {
+        return Ok(None);
+    }
+    if prefixed != items.len() {
+        return Err(format!(
+            "'{}' mixes prefixed and plain values. Prefix every value with + or -, or none of them",
+            raw
+        ));
+    }
+
+    let item_type = schema["items"].as_str().unwrap_or("string");
+    items
+        .into_iter()
+        .map(|item| {
+            let (op, value) = item.split_at(1);
+            let verb = if op == "+" { "add" } else { "remove" };
+            Ok(json!({ verb: coerce_scalar(item_type, schema, value.trim())? }))
+        })
+        .collect::<Result<Vec<_>, String>>()
+        .map(Some)
+}
+
+fn coerce_scalar(field_type: &str, schema: &Value, raw: &str) -> Result<Value, String> {
+    let value = match field_type {
+        "number" => {
+            if let Ok(n) = raw.parse::<i64>() {
+                json!(n)
+            } else {
+                let n = raw
+                    .parse::<f64>()
+                    .map_err(|_| format!("'{}' is not a number", raw))?;
+                json!(n)
+            }
+        }
+        "date" => {
+            if !is_iso_date(raw) {
+                return Err(format!("'{}' is not a date (expected YYYY-MM-DD)", raw));
+            }
+            json!(raw)
+        }
+        "option" => json!({ "value": raw }),
+        "option-with-child" => match raw.split_once('>') {
+            Some((parent, child)) => {
+                json!({ "value": parent.trim(), "child": { "value": child.trim() } })
+            }
+            None => json!({ "value": raw }),
+        },
+        "user" => json!({ "accountId": raw }),
+        "version" | "component" | "priority" | "resolution" | "issuetype" | "securitylevel" => {
+            json!({ "name": raw })
+        }
+        "project" => json!({ "key": raw }),
+        "issuelink" => json!({ "key": raw }),
+        "string" if is_rich_text(schema) => crate::adf::from_text(raw, false),
+        _ => json!(raw),
+    };
+    Ok(value)
+}
+
+/// Multi-line text fields take ADF documents in the v3 API.
+fn is_rich_text(schema: &Value) -> bool {
+    matches!(
+        schema["system"].as_str(),
+        Some("description" | "environment")
+    ) || schema["custom"]
+        .as_str()
+        .is_some_and(|c| c.ends_with(":textarea"))
+}
+
+fn is_iso_date(raw: &str) -> bool {
+    let parts: Vec<&str> = raw.split('-').collect();
+    parts.len() == 3
+        && [4, 2, 2]
+            .iter()
+            .zip(&parts)
+            .all(|(len, part)| part.len() == *len && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_assignment() {
+        assert_eq!(
+            parse_assignment("Story Points=5").unwrap(),
+            ("Story Points".to_string(), "5".to_string())
+        );
+        assert_eq!(
+            parse_assignment("Formula=a=b").unwrap(),
+            ("Formula".to_string(), "a=b".to_string())
+        );
+        assert!(parse_assignment("NoValue").is_err());
+        assert!(parse_assignment("=5").is_err());
+    }
+
+    #[test]
+    fn test_update_ops() {
+        let labels = json!({ "type": "array", "items": "string", "system": "labels" });
+        assert_eq!(
+            update_ops(&labels, "+urgent, -triage").unwrap(),
+            Some(vec![
+                json!({ "add": "urgent" }),
+                json!({ "remove": "triage" })
+            ])
+        );
+        assert_eq!(update_ops(&labels, "urgent,triage").unwrap(), None);
+        assert!(update_ops(&labels, "+urgent,triage").is_err());
+
+        let versions = json!({ "type": "array", "items": "version", "system": "fixVersions" });
+        assert_eq!(
+            update_ops(&versions, "-1.3,+1.4").unwrap(),
+            Some(vec![
+                json!({ "remove": { "name": "1.3" } }),
+                json!({ "add": { "name": "1.4" } })
+            ])
+        );
+
+        let number = json!({ "type": "number" });
+        assert_eq!(update_ops(&number, "-3").unwrap(), None);
+    }
+
+    #[test]
+    fn test_coerce_value() {
+        let number = json!({ "type": "number", "custom": "com.atlassian.jira.plugin.system.customfieldtypes:float" });
+        assert_eq!(coerce_value(&number, "5").unwrap(), json!(5));
+        assert_eq!(coerce_value(&number, "2.5").unwrap(), json!(2.5));
+        assert!(coerce_value(&number, "five").is_err());
+        assert_eq!(coerce_value(&number, "").unwrap(), Value::Null);
+
+        let components = json!({ "type": "array", "items": "component", "system": "components" });
+        assert_eq!(
+            coerce_value(&components, "Backend, API").unwrap(),
+            json!([{ "name": "Backend" }, { "name": "API" }])
+        );
+
+        let options = json!({ "type": "array", "items": "option" });
+        assert_eq!(
+            coerce_value(&options, "Red,Blue").unwrap(),
+            json!([{ "value": "Red" }, { "value": "Blue" }])
+        );
+        assert_eq!(coerce_value(&options, "").unwrap(), json!([]));
+
+        let cascading = json!({ "type": "option-with-child" });
+        assert_eq!(
+            coerce_value(&cascading, "EMEA > Germany").unwrap(),
+            json!({ "value": "EMEA", "child": { "value": "Germany" } })
+        );
+
+        let date = json!({ "type": "date" });
+        assert_eq!(
+            coerce_value(&date, "2024-03-01").unwrap(),
+            json!("2024-03-01")
+        );
+        assert!(coerce_value(&date, "03/01/2024").is_err());
+
+        let user = json!({ "type": "user" });
+        assert!(is_user_schema(&user));
+        assert_eq!(
+            coerce_value(&user, "abc123").unwrap(),
+            json!({ "accountId": "abc123" })
+        );
+
+        let textarea = json!({ "type": "string", "custom": "com.atlassian.jira.plugin.system.customfieldtypes:textarea" });
+        assert_eq!(coerce_value(&textarea, "**hi**").unwrap()["type"], "doc");
+        assert_eq!(
+            coerce_value(&json!({ "type": "string" }), "hi").unwrap(),
+            json!("hi")
+        );
+    }
+}
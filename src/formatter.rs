@@ -5,13 +5,23 @@ pub enum OutputFormat {
     /// Pretty-printed table with borders.
     Table,
     /// Comma-separated values.
-    CSV,
+    Csv,
     /// Pretty-printed JSON.
     Json,
     /// Space-padded columns without borders.
     Plain,
 }
 
+/// How rich-text documents (issue descriptions, comments, pages) are rendered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum DocumentFormat {
+    /// Terminal-friendly plain text.
+    #[default]
+    Text,
+    /// CommonMark/GFM Markdown, suitable for docs tooling.
+    Markdown,
+}
+
 /// Helper for rendering data in various formats.
 pub struct Formatter {
     pub format: OutputFormat,
@@ -28,7 +38,7 @@ impl Formatter {
     pub fn render(&self, rows: Vec<Vec<String>>) -> String {
         match self.format {
             OutputFormat::Table => self.render_table(rows),
-            OutputFormat::CSV => self.render_csv(rows),
+            OutputFormat::Csv => self.render_csv(rows),
             OutputFormat::Json => self.render_json(rows),
             OutputFormat::Plain => self.render_plain(rows),
         }
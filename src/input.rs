@@ -0,0 +1,203 @@
+use crate::error::Error;
+use std::io::{IsTerminal, Read, Write};
+
+/// Resolve a text body given inline or as a file path. `-` in either position reads stdin.
+///
+/// Returns `None` when neither was given. clap enforces that at most one is present.
+pub fn resolve_text(inline: Option<String>, file: Option<String>) -> Result<Option<String>, Error> {
+    match (inline, file) {
+        (Some(text), _) if text == "-" => read_stdin().map(Some),
+        (Some(text), _) => Ok(Some(text)),
+        (None, Some(path)) if path == "-" => read_stdin().map(Some),
+        (None, Some(path)) => std::fs::read_to_string(&path)
+            .map(Some)
+            .map_err(|e| format!("Failed to read '{}': {}", path, e).into()),
+        (None, None) => Ok(None),
+    }
+}
+
+fn read_stdin() -> Result<String, Error> {
+    let mut text = String::new();
+    std::io::stdin()
+        .read_to_string(&mut text)
+        .map_err(|e| format!("Failed to read stdin: {}", e))?;
+    Ok(text)
+}
+
+/// Whether stdin is an interactive terminal, so prompts and editors can be used.
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal()
+}
+
+/// Print `label` and read one line from stdin, without the trailing newline.
+pub fn prompt_line(label: &str) -> Result<String, Error> {
+    print!("{}", label);
+    std::io::stdout()
+        .flush()
+        .map_err(|e| format!("Failed to write prompt: {}", e))?;
+    let mut line = String::new();
+    std::io::stdin()
+        .read_line(&mut line)
+        .map_err(|e| format!("Failed to read stdin: {}", e))?;
+    Ok(line.trim().to_string())
+}
+
+/// Like `prompt_line`, but without echoing what is typed, for tokens and passwords.
+pub fn prompt_secret(label: &str) -> Result<String, Error> {
+    rpassword::prompt_password(label)
+        .map(|line| line.trim().to_string())
+        .map_err(|e| format!("Failed to read from the terminal: {}", e).into())
+}
+
+/// Compose text in `$VISUAL`/`$EDITOR` (falling back to `vi`), the way `git commit` does.
+///
+/// The temp file is pre-filled with `initial` followed by `help`, written as `<!-- ... -->`
+/// comment lines. Comment lines are stripped from the result. Returns `None` if the saved
+/// text is empty.
+pub fn compose(name: &str, initial: &str, help: &[&str]) -> Result<Option<String>, Error> {
+    if !is_interactive() {
+        return Err(
+            "No text given and stdin is not a terminal, so no editor can be opened. \
+             Pass the text as an argument, a file, or `-` for stdin."
+                .into(),
+        );
+    }
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    run_editor(&editor, name, &template(initial, help))
+}
+
+fn template(initial: &str, help: &[&str]) -> String {
+    let mut text = initial.trim_end().to_string();
+    text.push_str("\n\n");
+    for line in help {
+        text.push_str(&format!("<!-- {} -->\n", line));
+    }
+    text
+}
+
+fn run_editor(editor: &str, name: &str, template: &str) -> Result<Option<String>, Error> {
+    let path =
+        std::env::temp_dir().join(format!("jiri-{}-{:016x}.md", name, rand::random::<u64>()));
+    write_private(&path, template)
+        .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+
+    let status = editor_command(editor).arg(&path).status();
+
+    let result: Result<String, Error> = match status {
+        Ok(status) if status.success() => std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read '{}': {}", path.display(), e).into()),
+        Ok(status) => Err(format!("Editor '{}' exited with {}", editor, status).into()),
+        Err(e) => Err(format!("Failed to start editor '{}': {}", editor, e).into()),
+    };
+    let _ = std::fs::remove_file(&path);
+
+    let text = strip_comments(&result?);
+    Ok(if text.is_empty() { None } else { Some(text) })
+}
+
+/// Create `path` readable only by the current user, failing if it already exists.
+fn write_private(path: &std::path::Path, text: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(text.as_bytes())
+}
+
+/// Like git, run the editor through the shell so it may carry arguments and quoting
+/// (e.g. `code --wait`). The file path is appended as `"$@"`.
+#[cfg(unix)]
+fn editor_command(editor: &str) -> std::process::Command {
+    let mut command = std::process::Command::new("sh");
+    command
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg(editor);
+    command
+}
+
+#[cfg(not(unix))]
+fn editor_command(editor: &str) -> std::process::Command {
+    let mut parts = editor.split_whitespace();
+    let mut command = std::process::Command::new(parts.next().unwrap_or("vi"));
+    command.args(parts);
+    command
+}
+
+/// Remove whole-line `<!-- ... -->` comments and surrounding blank lines.
+fn strip_comments(text: &str) -> String {
+    let kept: Vec<&str> = text
+        .lines()
+        .filter(|line| {
+            let line = line.trim();
+            !(line.starts_with("<!--") && line.ends_with("-->"))
+        })
+        .collect();
+    let mut text = kept.join("\n").trim().to_string();
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_text() {
+        assert_eq!(resolve_text(None, None).unwrap(), None);
+        assert_eq!(
+            resolve_text(Some("inline".to_string()), None).unwrap(),
+            Some("inline".to_string())
+        );
+
+        let path = std::env::temp_dir().join(format!("jiri-input-{}.md", std::process::id()));
+        std::fs::write(&path, "# From file\n").unwrap();
+        let text = resolve_text(None, Some(path.to_string_lossy().into_owned())).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(text, Some("# From file\n".to_string()));
+
+        let err = resolve_text(None, Some("/nonexistent/jiri.md".to_string())).unwrap_err();
+        assert!(err.to_string().contains("/nonexistent/jiri.md"));
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        let text =
+            "# Heading\n\nBody <!-- inline stays -->\n\n<!-- Lines like this are removed. -->\n";
+        assert_eq!(
+            strip_comments(text),
+            "# Heading\n\nBody <!-- inline stays -->\n"
+        );
+        assert_eq!(
+            strip_comments(&template("", &["Save empty to cancel."])),
+            ""
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_editor_keeps_prefilled_text() {
+        // `true` leaves the file untouched, like quitting the editor without changes.
+        let text = run_editor("true", "test", &template("Existing\n", &["help"])).unwrap();
+        assert_eq!(text, Some("Existing\n".to_string()));
+
+        let text = run_editor("true", "test", &template("", &["help"])).unwrap();
+        assert_eq!(text, None);
+
+        assert!(run_editor("false", "test", "x").is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_editor_passes_arguments_through_the_shell() {
+        let editor = "sed -i 's/Existing text/Edited text/'";
+        let text = run_editor(editor, "test", &template("Existing text\n", &["help"])).unwrap();
+        assert_eq!(text, Some("Edited text\n".to_string()));
+    }
+}
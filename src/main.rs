@@ -2,15 +2,24 @@ mod adf;
 mod client;
 mod commands;
 mod config;
+mod dates;
+mod error;
 mod fields;
 mod formatter;
+mod input;
+mod markup;
+mod oauth;
+mod progress;
+mod retry;
 
 use clap::builder::styling::{AnsiColor, Effects, Styles};
-use clap::{Parser, Subcommand};
+use clap::{ArgGroup, Args, Parser, Subcommand};
 use clap_complete::Shell;
 use client::AtlassianClient;
 use config::Config;
-use formatter::{Formatter, OutputFormat};
+use error::Error;
+use formatter::{DocumentFormat, Formatter, OutputFormat};
+use owo_colors::OwoColorize;
 
 fn get_styles() -> Styles {
     Styles::styled()
@@ -51,6 +60,10 @@ pub struct Cli {
     /// Verbose output (debug logging of API requests)
     #[arg(long, global = true)]
     verbose: bool,
+
+    /// Configuration profile to use (overrides JIRI_PROFILE and default_profile)
+    #[arg(long, global = true)]
+    profile: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -74,15 +87,27 @@ enum Commands {
         /// Update the issue summary
         #[arg(long)]
         summary: Option<String>,
-        /// Update the issue description
-        #[arg(long)]
-        description: Option<String>,
-        /// Comma-separated labels to set on the issue
-        #[arg(long)]
+        /// Update the issue description (Markdown; `-` reads stdin). Without a value, opens
+        /// $VISUAL/$EDITOR pre-filled with the current description.
+        #[arg(long, num_args = 0..=1, conflicts_with = "description_file")]
+        description: Option<Option<String>>,
+        /// Read the new description from a file (`-` for stdin)
+        #[arg(long, value_name = "PATH")]
+        description_file: Option<String>,
+        /// Comma-separated labels to set on the issue; +LABEL adds and -LABEL removes,
+        /// keeping the other labels
+        #[arg(long, allow_hyphen_values = true)]
         labels: Option<String>,
         /// Update the assignee using a Jira user search query
         #[arg(long)]
         assignee: Option<String>,
+        /// Set a field by name or ID, e.g. "Story Points=5" or "Components=Backend,API";
+        /// multi-value fields also take "+add,-remove" items (repeatable)
+        #[arg(long = "field", value_name = "NAME=VALUE")]
+        fields: Vec<String>,
+        /// Send the text as-is instead of converting Markdown
+        #[arg(long)]
+        no_markdown: bool,
     },
 
     /// Assign a Jira issue to a user
@@ -99,10 +124,15 @@ enum Commands {
     /// Examples:
     ///   jiri search "assignee = currentUser()"
     ///   jiri search "project = TJP" --fields "key,summary,status" --limit 20
+    ///   jiri search --sprint current "assignee = currentUser()"
     #[command(visible_alias = "s")]
     Search {
         /// The JQL query string
+        #[arg(default_value = "")]
         jql: String,
+        /// Restrict to a sprint: current, next, closed, a sprint ID, or a sprint name
+        #[arg(long)]
+        sprint: Option<String>,
         /// Comma-separated fields to display (default: key,summary)
         #[arg(short, long)]
         fields: Option<String>,
@@ -124,17 +154,57 @@ enum Commands {
     View {
         /// The issue key (e.g. PROJ-123)
         key: String,
+        /// Render the issue as plain text or Markdown
+        #[arg(long, value_enum, default_value_t = DocumentFormat::Text)]
+        format: DocumentFormat,
+    },
+
+    /// Show who changed what on a Jira issue, oldest change first
+    ///
+    /// Examples:
+    ///   jiri history PROJ-123
+    ///   jiri history PROJ-123 --field status --field assignee
+    History {
+        /// The issue key (e.g. PROJ-123)
+        key: String,
+        /// Only show changes to this field, by name or ID (repeatable)
+        #[arg(short, long = "field", value_name = "NAME")]
+        fields: Vec<String>,
     },
 
     /// Transition a Jira issue to a new status
     ///
     /// If no status is provided, it lists available transitions.
+    ///
+    /// Fields on the transition screen can be set with --resolution, --assignee and --field;
+    /// required screen fields are checked before anything is posted.
+    ///
+    /// Example: jiri transition PROJ-123 Done --resolution Fixed --comment "Shipped in 1.4"
     #[command(visible_alias = "t")]
     Transition {
         /// The issue key (e.g. PROJ-123)
         key: String,
-        /// Target status name or ID (omit to list available)
+        /// Target transition name or ID (omit to list available)
         status: Option<String>,
+        /// Resolution to set (e.g. Fixed, "Won't Do")
+        #[arg(short, long, requires = "status")]
+        resolution: Option<String>,
+        /// Comment to add with the transition (Markdown)
+        #[arg(short, long, requires = "status")]
+        comment: Option<String>,
+        /// Assign the issue as part of the transition
+        #[arg(short, long, requires = "status")]
+        assignee: Option<String>,
+        /// Set a screen field by name or ID, e.g. "Fix versions=1.4"; multi-value fields
+        /// also take "+add,-remove" items (repeatable)
+        #[arg(long = "field", value_name = "NAME=VALUE", requires = "status")]
+        fields: Vec<String>,
+        /// Treat the target as a status and walk the shortest path of transitions to it
+        #[arg(long, requires = "status")]
+        via_path: bool,
+        /// Send the comment as-is instead of converting Markdown
+        #[arg(long, requires = "comment")]
+        no_markdown: bool,
     },
 
     /// Create a new Jira issue
@@ -149,30 +219,183 @@ enum Commands {
         /// Issue type name (default: Task)
         #[arg(short = 't', long, default_value = "Task")]
         issue_type: String,
-        /// Detailed description
-        #[arg(short, long)]
+        /// Detailed description (Markdown; `-` reads stdin). When omitted in an interactive
+        /// terminal, opens $VISUAL/$EDITOR.
+        #[arg(short, long, conflicts_with = "description_file")]
         description: Option<String>,
+        /// Read the description from a file (`-` for stdin)
+        #[arg(long, value_name = "PATH")]
+        description_file: Option<String>,
+        /// Parent issue key: the epic of a story, or the issue a subtask belongs to
+        #[arg(long, value_name = "KEY")]
+        parent: Option<String>,
+        /// Set a field by name or ID, e.g. "Story Points=5" or "Components=Backend,API"
+        /// (repeatable)
+        #[arg(long = "field", value_name = "NAME=VALUE")]
+        fields: Vec<String>,
+        /// Send the text as-is instead of converting Markdown
+        #[arg(long)]
+        no_markdown: bool,
     },
 
-    /// Add a comment to a Jira issue
-    Comment {
-        /// The issue key (e.g. PROJ-123)
+    /// Show an issue and its children as a tree (epic → story → subtask)
+    ///
+    /// Example: jiri tree PROJ-10
+    Tree {
+        /// The issue key (e.g. PROJ-10)
         key: String,
-        /// Text of the comment
-        message: String,
+        /// How many levels of children to show
+        #[arg(long, default_value = "3")]
+        depth: usize,
+    },
+
+    /// Move issues under another parent
+    ///
+    /// Examples:
+    ///   jiri move-parent PROJ-12 PROJ-13 --to PROJ-10
+    ///   jiri move-parent PROJ-12 --none
+    MoveParent {
+        /// Issue keys to move
+        #[arg(required = true)]
+        keys: Vec<String>,
+        /// The new parent issue key
+        #[arg(long, value_name = "KEY", required_unless_present = "none")]
+        to: Option<String>,
+        /// Remove the parent instead
+        #[arg(long, conflicts_with = "to")]
+        none: bool,
     },
 
-    /// Add an attachment to a Jira issue
+    /// Add, list, edit or delete comments on a Jira issue
+    ///
+    /// Examples:
+    ///   jiri comment PROJ-123 "Fixed in **v1.2**"
+    ///   cargo test 2>&1 | jiri comment PROJ-123 - --no-markdown
+    ///   jiri comment PROJ-123 "Root cause below" --visibility role:Developers
+    ///   jiri comment list PROJ-123
+    ///   jiri comment edit PROJ-123 10042
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Comment {
+        #[command(subcommand)]
+        subcommand: Option<CommentCommands>,
+        #[command(flatten)]
+        add: CommentArgs,
+    },
+
+    /// Add attachments to a Jira issue
+    ///
+    /// Several files (or globs like "logs/*.log") are uploaded in one request.
     Attach {
         /// The issue key (e.g. PROJ-123)
         key: String,
-        /// Path to the file to attach
-        file: String,
-        /// Optional comment to add along with the attachment
+        /// Files or glob patterns to attach
+        #[arg(required = true)]
+        files: Vec<String>,
+        /// Optional comment to add along with the attachment (single file only)
         #[arg(short, long)]
         message: Option<String>,
     },
 
+    /// List, download or delete the attachments of a Jira issue
+    ///
+    /// Examples:
+    ///   jiri attachments PROJ-1
+    ///   jiri attachments download PROJ-1 --name "*.log" -o logs/
+    ///   jiri attachments delete PROJ-1 10001
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Attachments {
+        #[command(subcommand)]
+        subcommand: Option<AttachmentCommands>,
+        /// The issue key (e.g. PROJ-123)
+        #[arg(required = true)]
+        key: Option<String>,
+    },
+
+    /// Link Jira issues (blocks, relates to, duplicates, clones, ...)
+    ///
+    /// Examples:
+    ///   jiri link add PROJ-1 blocks PROJ-2
+    ///   jiri link list PROJ-1
+    ///   jiri link remove PROJ-1 blocks PROJ-2
+    Link {
+        #[command(subcommand)]
+        subcommand: LinkCommands,
+    },
+
+    /// Log and report time spent on Jira issues
+    ///
+    /// Examples:
+    ///   jiri worklog add PROJ-1 1h30m --started "yesterday 14:00" --comment "Code review"
+    ///   jiri worklog report --from 2024-03-01 --to 2024-03-07
+    #[command(visible_alias = "w")]
+    Worklog {
+        #[command(subcommand)]
+        subcommand: WorklogCommands,
+    },
+
+    /// List Jira Software boards
+    Board {
+        #[command(subcommand)]
+        subcommand: BoardCommands,
+    },
+
+    /// Work with Jira Software sprints
+    ///
+    /// SPRINT is a sprint ID, "active"/"current", "next"/"future", or a sprint name.
+    /// Without --board, the board of the default project is used.
+    ///
+    /// Examples:
+    ///   jiri sprint list --board "Platform"
+    ///   jiri sprint view active
+    ///   jiri sprint add PROJ-1 PROJ-2 --sprint active
+    ///   jiri sprint start next --days 14
+    Sprint {
+        #[command(subcommand)]
+        subcommand: SprintCommands,
+    },
+
+    /// Apply one change to every issue a JQL query returns
+    ///
+    /// Lists the matching issues and asks for confirmation unless --yes is given.
+    ///
+    /// Examples:
+    ///   jiri bulk --jql "project = PROJ AND labels = triage" transition Done
+    ///   jiri bulk --jql "sprint in openSprints()" edit --labels +urgent,-triage
+    ///   jiri bulk --jql "assignee = currentUser()" assign alice --yes
+    Bulk {
+        /// The JQL query selecting the issues to change
+        #[arg(long)]
+        jql: String,
+        /// Skip the confirmation prompt
+        #[arg(short, long, global = true)]
+        yes: bool,
+        /// Maximum number of issues to change; larger result sets are refused
+        #[arg(long, default_value = "1000", global = true)]
+        limit: i64,
+        /// Number of issues changed in parallel
+        #[arg(long, default_value = "5", global = true)]
+        concurrency: usize,
+        /// Send comments as-is instead of converting Markdown
+        #[arg(long, global = true)]
+        no_markdown: bool,
+        #[command(subcommand)]
+        subcommand: BulkCommands,
+    },
+
+    /// Log in or out with OAuth 2.0 (3LO)
+    ///
+    /// Requires auth.mode = oauth and the client ID of an Atlassian OAuth app whose
+    /// callback URL is http://localhost:<port>/callback.
+    ///
+    /// Examples:
+    ///   jiri auth login
+    ///   jiri auth login --port 9000 --no-browser
+    ///   jiri auth logout
+    Auth {
+        #[command(subcommand)]
+        subcommand: AuthCommands,
+    },
+
     /// Manage configuration settings
     Config {
         #[command(subcommand)]
@@ -196,9 +419,36 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum AuthCommands {
+    /// Authorize jiri in the browser and store the tokens
+    Login {
+        /// Local port for the redirect listener
+        #[arg(long, default_value = "8976")]
+        port: u16,
+        /// Space-separated scopes to request instead of the defaults
+        #[arg(long)]
+        scope: Option<String>,
+        /// Print the authorization URL without opening a browser
+        #[arg(long)]
+        no_browser: bool,
+    },
+    /// Remove the stored tokens
+    Logout,
+}
+
 #[derive(Subcommand)]
 enum ConfigCommands {
-    /// Show the current configuration
+    /// Set up credentials interactively and verify them
+    ///
+    /// Writes the global config file, or jiri.toml with --local (with --profile, that
+    /// profile's table).
+    Init {
+        /// Write the local (project) configuration file
+        #[arg(short, long)]
+        local: bool,
+    },
+    /// Show the current configuration (with --profile, the effective values of that profile)
     Show {
         /// Show global configuration file
         #[arg(short, long)]
@@ -207,7 +457,7 @@ enum ConfigCommands {
         #[arg(short, long)]
         local: bool,
     },
-    /// Set a configuration value
+    /// Set a configuration value (with --profile, in that profile's table)
     Set {
         /// The key to set (e.g., auth.username, general.default_project)
         key: String,
@@ -222,6 +472,347 @@ enum ConfigCommands {
     },
 }
 
+/// Arguments for adding a comment, shared by `jiri comment KEY ...` and `jiri comment add`.
+#[derive(Args)]
+struct CommentArgs {
+    /// The issue key (e.g. PROJ-123)
+    #[arg(required = true)]
+    key: Option<String>,
+    /// Text of the comment (Markdown; `-` reads stdin). Omit to open $VISUAL/$EDITOR.
+    #[arg(conflicts_with = "body_file")]
+    message: Option<String>,
+    /// Read the comment from a file (`-` for stdin)
+    #[arg(long, value_name = "PATH")]
+    body_file: Option<String>,
+    /// Restrict the comment to a project role or group: role:NAME or group:NAME
+    #[arg(long, value_name = "TYPE:NAME")]
+    visibility: Option<String>,
+    /// Service Management: make the comment internal (visible to agents only)
+    #[arg(long)]
+    internal: bool,
+    /// Send the text as-is instead of converting Markdown
+    #[arg(long)]
+    no_markdown: bool,
+}
+
+#[derive(Subcommand)]
+enum CommentCommands {
+    /// Add a comment (same as `jiri comment KEY MESSAGE`)
+    Add {
+        #[command(flatten)]
+        args: CommentArgs,
+    },
+    /// List the comments of an issue
+    #[command(visible_alias = "ls")]
+    List {
+        /// The issue key (e.g. PROJ-123)
+        key: String,
+    },
+    /// Replace the text of a comment (opens the editor when no text is given)
+    Edit {
+        /// The issue key (e.g. PROJ-123)
+        key: String,
+        /// The comment ID (see `jiri comment list`)
+        id: String,
+        /// New text (Markdown; `-` reads stdin)
+        #[arg(conflicts_with = "body_file")]
+        message: Option<String>,
+        /// Read the new text from a file (`-` for stdin)
+        #[arg(long, value_name = "PATH")]
+        body_file: Option<String>,
+        /// Change who can see the comment: role:NAME or group:NAME
+        #[arg(long, value_name = "TYPE:NAME")]
+        visibility: Option<String>,
+        /// Service Management: make the comment internal
+        #[arg(long)]
+        internal: bool,
+        /// Send the text as-is instead of converting Markdown
+        #[arg(long)]
+        no_markdown: bool,
+    },
+    /// Delete a comment
+    #[command(visible_alias = "rm")]
+    Delete {
+        /// The issue key (e.g. PROJ-123)
+        key: String,
+        /// The comment ID (see `jiri comment list`)
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AttachmentCommands {
+    /// List attachments (same as `jiri attachments KEY`)
+    #[command(visible_alias = "ls")]
+    List {
+        /// The issue key (e.g. PROJ-123)
+        key: String,
+    },
+    /// Download attachments (all, or those matching --name)
+    #[command(visible_alias = "get")]
+    Download {
+        /// The issue key (e.g. PROJ-123)
+        key: String,
+        /// Only attachments whose name matches this glob (e.g. "*.log")
+        #[arg(short, long, value_name = "GLOB")]
+        name: Option<String>,
+        /// Directory to save into (default: current directory)
+        #[arg(short, long, value_name = "DIR")]
+        output: Option<String>,
+        /// Overwrite files that already exist
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Delete attachments by ID or name
+    #[command(visible_alias = "rm")]
+    #[command(group(ArgGroup::new("target").required(true).multiple(true)))]
+    Delete {
+        /// The issue key (e.g. PROJ-123)
+        key: String,
+        /// Attachment IDs (see `jiri attachments KEY`)
+        #[arg(group = "target")]
+        ids: Vec<String>,
+        /// Delete attachments whose name matches this glob
+        #[arg(short, long, value_name = "GLOB", group = "target")]
+        name: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PageAttachmentCommands {
+    /// List attachments (same as `jiri confluence attachments ID`)
+    #[command(visible_alias = "ls")]
+    List {
+        /// The page ID
+        id: String,
+    },
+    /// Download attachments (all, or those matching --name)
+    #[command(visible_alias = "get")]
+    Download {
+        /// The page ID
+        id: String,
+        /// Only attachments whose name matches this glob (e.g. "*.png")
+        #[arg(short, long, value_name = "GLOB")]
+        name: Option<String>,
+        /// Directory to save into (default: current directory)
+        #[arg(short, long, value_name = "DIR")]
+        output: Option<String>,
+        /// Overwrite files that already exist
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Delete attachments by ID or name
+    #[command(visible_alias = "rm")]
+    #[command(group(ArgGroup::new("target").required(true).multiple(true)))]
+    Delete {
+        /// The page ID
+        id: String,
+        /// Attachment IDs (see `jiri confluence attachments ID`)
+        #[arg(group = "target")]
+        ids: Vec<String>,
+        /// Delete attachments whose name matches this glob
+        #[arg(short, long, value_name = "GLOB", group = "target")]
+        name: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum LinkCommands {
+    /// List available issue link types
+    Types,
+    /// Link two issues so that "KEY RELATION OTHER" reads naturally
+    Add {
+        /// The issue key (e.g. PROJ-1)
+        key: String,
+        /// Link type name or description, matched by prefix (e.g. "blocks", "is blocked by")
+        relation: String,
+        /// The other issue key (e.g. PROJ-2)
+        other: String,
+    },
+    /// List the links of an issue
+    #[command(visible_alias = "ls")]
+    List {
+        /// The issue key (e.g. PROJ-1)
+        key: String,
+    },
+    /// Remove a link by ID, or by issue key, relation and other issue key
+    #[command(visible_alias = "rm")]
+    Remove {
+        /// Link ID, or the issue key when RELATION and OTHER are given
+        target: String,
+        /// Link type name or description (e.g. "blocks")
+        #[arg(requires = "other")]
+        relation: Option<String>,
+        /// The other issue key
+        other: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum WorklogCommands {
+    /// Log time on an issue
+    Add {
+        /// The issue key (e.g. PROJ-123)
+        key: String,
+        /// Time spent in Jira syntax, e.g. 1h30m, 45m, 2d, 1w (1d = 8h, 1w = 5d)
+        time: String,
+        /// When the work started: "now", "HH:MM", "yesterday 14:00", "2024-03-01 09:30"
+        /// (local time; a date alone means 09:00)
+        #[arg(long)]
+        started: Option<String>,
+        /// Worklog comment (Markdown)
+        #[arg(short, long)]
+        comment: Option<String>,
+        /// Send the comment as-is instead of converting Markdown
+        #[arg(long, requires = "comment")]
+        no_markdown: bool,
+    },
+    /// List the worklogs of an issue
+    #[command(visible_alias = "ls")]
+    List {
+        /// The issue key (e.g. PROJ-123)
+        key: String,
+    },
+    /// Change the time, start or comment of a worklog
+    Edit {
+        /// The issue key (e.g. PROJ-123)
+        key: String,
+        /// The worklog ID (see `jiri worklog list`)
+        id: String,
+        /// New time spent, e.g. 2h
+        #[arg(long)]
+        time: Option<String>,
+        /// When the work started: "now", "HH:MM", "yesterday 14:00", "2024-03-01 09:30"
+        /// (local time; a date alone means 09:00)
+        #[arg(long)]
+        started: Option<String>,
+        /// New worklog comment (Markdown)
+        #[arg(short, long)]
+        comment: Option<String>,
+        /// Send the comment as-is instead of converting Markdown
+        #[arg(long, requires = "comment")]
+        no_markdown: bool,
+    },
+    /// Delete a worklog
+    #[command(visible_alias = "rm")]
+    Delete {
+        /// The issue key (e.g. PROJ-123)
+        key: String,
+        /// The worklog ID (see `jiri worklog list`)
+        id: String,
+    },
+    /// Sum your logged time per day and issue
+    Report {
+        /// First day to include: YYYY-MM-DD, today or yesterday (default: 6 days before --to)
+        #[arg(long)]
+        from: Option<String>,
+        /// Last day to include: YYYY-MM-DD, today or yesterday (default: today)
+        #[arg(long)]
+        to: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum BoardCommands {
+    /// List boards
+    #[command(visible_alias = "ls")]
+    List {
+        /// Only boards of this project
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SprintCommands {
+    /// List the sprints of a board
+    #[command(visible_alias = "ls")]
+    List {
+        /// Board ID or name
+        #[arg(short, long)]
+        board: Option<String>,
+        /// Only sprints in these states (comma-separated: future, active, closed)
+        #[arg(short, long)]
+        state: Option<String>,
+    },
+    /// Show a sprint and its issues grouped by status
+    View {
+        /// Sprint ID, active, next, or name
+        sprint: String,
+        /// Board ID or name
+        #[arg(short, long)]
+        board: Option<String>,
+    },
+    /// Move issues into a sprint
+    Add {
+        /// Issue keys (e.g. PROJ-1 PROJ-2)
+        #[arg(required = true)]
+        keys: Vec<String>,
+        /// Sprint ID, active, next, or name
+        #[arg(short, long)]
+        sprint: String,
+        /// Board ID or name
+        #[arg(short, long)]
+        board: Option<String>,
+    },
+    /// Start a sprint
+    Start {
+        /// Sprint ID, next, or name
+        sprint: String,
+        /// Board ID or name
+        #[arg(short, long)]
+        board: Option<String>,
+        /// Last day of the sprint: YYYY-MM-DD (default: the sprint's end date, or 14 days)
+        #[arg(long, conflicts_with = "days")]
+        end: Option<String>,
+        /// Sprint length in days from now
+        #[arg(long)]
+        days: Option<i64>,
+    },
+    /// Close a sprint
+    Close {
+        /// Sprint ID, active, or name
+        sprint: String,
+        /// Board ID or name
+        #[arg(short, long)]
+        board: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum BulkCommands {
+    /// Transition every issue
+    Transition {
+        /// Transition name or ID
+        status: String,
+        /// Resolution to set, e.g. Fixed
+        #[arg(short, long)]
+        resolution: Option<String>,
+        /// Comment to add with the transition (Markdown)
+        #[arg(short, long)]
+        comment: Option<String>,
+    },
+    /// Edit fields of every issue
+    Edit {
+        /// Comma-separated labels; +LABEL adds and -LABEL removes, plain labels replace all
+        #[arg(short, long, allow_hyphen_values = true)]
+        labels: Option<String>,
+        /// Set a field by name or ID, e.g. "Story Points=5" (repeatable)
+        #[arg(long = "field", value_name = "NAME=VALUE")]
+        fields: Vec<String>,
+    },
+    /// Assign every issue to a user
+    Assign {
+        /// User search query, display name, email, or accountId
+        user: String,
+    },
+    /// Add the same comment to every issue
+    Comment {
+        /// Comment text (Markdown)
+        message: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum ConfluenceCommands {
     /// Search for Confluence pages
@@ -264,13 +855,16 @@ enum ConfluenceCommands {
 
     /// View content of a Confluence page
     ///
-    /// Renders Atlassian Document Format (ADF) as plain text.
+    /// Renders Atlassian Document Format (ADF) as plain text or Markdown.
     View {
         /// The page ID
         id: String,
         /// Output raw ADF JSON instead of rendered text
-        #[arg(long)]
+        #[arg(long, conflicts_with = "format")]
         raw: bool,
+        /// Render the page as plain text or Markdown
+        #[arg(long, value_enum, default_value_t = DocumentFormat::Text)]
+        format: DocumentFormat,
     },
 
     /// Add an attachment to a Confluence page
@@ -284,6 +878,20 @@ enum ConfluenceCommands {
         message: Option<String>,
     },
 
+    /// List, download or delete the attachments of a Confluence page
+    ///
+    /// Examples:
+    ///   jiri confluence attachments 12345
+    ///   jiri confluence attachments download 12345 --name "*.png" -o images/
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Attachments {
+        #[command(subcommand)]
+        subcommand: Option<PageAttachmentCommands>,
+        /// The page ID
+        #[arg(required = true)]
+        id: Option<String>,
+    },
+
     /// Programmatically edit a Confluence page
     ///
     /// Performs a Fetch-Modify-PUT cycle to ensure targeted edits
@@ -343,24 +951,54 @@ enum ConfluenceCommands {
 
 #[tokio::main]
 /// Entry point for the jiri CLI.
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() {
     let cli = Cli::parse();
 
+    if let Err(e) = run(cli).await {
+        eprintln!("{} {}", "error:".red().bold(), e);
+        std::process::exit(e.exit_code());
+    }
+}
+
+/// Dispatch the parsed command line to the matching command.
+async fn run(cli: Cli) -> Result<(), Error> {
     if cli.verbose {
         std::env::set_var("JIRI_VERBOSE", "1");
     }
 
-    // Completions don't need auth
-    if let Commands::Completions { shell } = &cli.command {
-        commands::completions::run(*shell);
-        return Ok(());
+    // Completions and config management don't need auth
+    match cli.command {
+        Commands::Completions { shell } => {
+            commands::completions::run(shell);
+            return Ok(());
+        }
+        Commands::Config { subcommand } => {
+            match subcommand {
+                ConfigCommands::Init { local } => {
+                    commands::config::run_init(local, cli.profile).await?;
+                }
+                ConfigCommands::Show { global, local } => {
+                    commands::config::run_show(global, local, cli.profile).await?;
+                }
+                ConfigCommands::Set {
+                    key,
+                    value,
+                    global,
+                    local,
+                } => {
+                    commands::config::run_set(key, value, global, local, cli.profile).await?;
+                }
+            }
+            return Ok(());
+        }
+        _ => {}
     }
 
-    let config = Config::load()?;
+    let config = Config::load(cli.profile.as_deref())?;
     let client = AtlassianClient::new(config);
 
     let format = if cli.csv {
-        OutputFormat::CSV
+        OutputFormat::Csv
     } else if cli.json {
         OutputFormat::Json
     } else if cli.plain {
@@ -382,16 +1020,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             key,
             summary,
             description,
+            description_file,
             labels,
             assignee,
+            fields,
+            no_markdown,
         } => {
-            commands::edit::run(&client, key, summary, description, labels, assignee).await?;
+            // `--description` without a value opens the editor
+            let description = match description {
+                Some(None) => Some(None),
+                other => input::resolve_text(other.flatten(), description_file)?.map(Some),
+            };
+            commands::edit::run(
+                &client,
+                key,
+                summary,
+                description,
+                labels,
+                assignee,
+                fields,
+                no_markdown,
+            )
+            .await?;
         }
         Commands::Assign { key, user } => {
             commands::assign::run(&client, key, user).await?;
         }
         Commands::Search {
             jql,
+            sprint,
             fields,
             get_fields,
             limit,
@@ -401,6 +1058,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 &client,
                 &formatter,
                 jql,
+                sprint,
                 fields,
                 get_fields,
                 limit,
@@ -408,42 +1066,264 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             )
             .await?;
         }
-        Commands::View { key } => {
-            commands::view::run(&client, key).await?;
+        Commands::View { key, format } => {
+            commands::view::run(&client, key, format).await?;
         }
-        Commands::Transition { key, status } => {
-            commands::transition::run(&client, key, status).await?;
+        Commands::History { key, fields } => {
+            commands::history::run(&client, &formatter, key, fields).await?;
+        }
+        Commands::Transition {
+            key,
+            status,
+            resolution,
+            comment,
+            assignee,
+            fields,
+            via_path,
+            no_markdown,
+        } => {
+            commands::transition::run(
+                &client,
+                key,
+                status,
+                resolution,
+                comment,
+                assignee,
+                fields,
+                via_path,
+                no_markdown,
+            )
+            .await?;
         }
         Commands::Create {
             project,
             summary,
             issue_type,
             description,
+            description_file,
+            parent,
+            fields,
+            no_markdown,
         } => {
+            let description = input::resolve_text(description, description_file)?;
+            // A child issue lives in its parent's project unless told otherwise
+            let parent_project = parent
+                .as_deref()
+                .and_then(|p| p.rsplit_once('-'))
+                .map(|(project, _)| project.to_string());
             let project_key = project
+                .or(parent_project)
                 .or_else(|| client.config().default_project.clone())
                 .ok_or(
                     "Project key is required. Use --project or set default_project in config.",
                 )?;
-            commands::create::run(&client, project_key, summary, issue_type, description).await?;
+            commands::create::run(
+                &client,
+                project_key,
+                summary,
+                issue_type,
+                description,
+                parent,
+                fields,
+                no_markdown,
+            )
+            .await?;
         }
-        Commands::Comment { key, message } => {
-            commands::comment::run(&client, key, message).await?;
+        Commands::Tree { key, depth } => {
+            commands::hierarchy::run_tree(&client, &formatter, key, depth).await?;
         }
-        Commands::Attach { key, file, message } => {
-            commands::attach::run(&client, key, file, message).await?;
+        Commands::MoveParent { keys, to, none: _ } => {
+            commands::hierarchy::run_move_parent(&client, keys, to).await?;
+        }
+        Commands::Comment { subcommand, add } => match subcommand {
+            None => add_comment(&client, add).await?,
+            Some(CommentCommands::Add { args }) => add_comment(&client, args).await?,
+            Some(CommentCommands::List { key }) => {
+                commands::comment::run_list(&client, &formatter, key).await?;
+            }
+            Some(CommentCommands::Edit {
+                key,
+                id,
+                message,
+                body_file,
+                visibility,
+                internal,
+                no_markdown,
+            }) => {
+                let message = input::resolve_text(message, body_file)?;
+                commands::comment::run_edit(
+                    &client,
+                    key,
+                    id,
+                    message,
+                    visibility,
+                    internal,
+                    no_markdown,
+                )
+                .await?;
+            }
+            Some(CommentCommands::Delete { key, id }) => {
+                commands::comment::run_delete(&client, key, id).await?;
+            }
+        },
+        Commands::Attach {
+            key,
+            files,
+            message,
+        } => {
+            commands::attach::run(&client, key, files, message).await?;
         }
-        Commands::Config { subcommand } => match subcommand {
-            ConfigCommands::Show { global, local } => {
-                commands::config::run_show(global, local).await?;
+        Commands::Attachments { subcommand, key } => match subcommand {
+            None => {
+                let key = key.ok_or("The issue key is required")?;
+                commands::attachments::run_list(&client, &formatter, key).await?;
+            }
+            Some(AttachmentCommands::List { key }) => {
+                commands::attachments::run_list(&client, &formatter, key).await?;
+            }
+            Some(AttachmentCommands::Download {
+                key,
+                name,
+                output,
+                force,
+            }) => {
+                commands::attachments::run_download(&client, key, name, output, force).await?;
+            }
+            Some(AttachmentCommands::Delete { key, ids, name }) => {
+                commands::attachments::run_delete(&client, key, ids, name).await?;
+            }
+        },
+        Commands::Link { subcommand } => match subcommand {
+            LinkCommands::Types => {
+                commands::link::run_types(&client, &formatter).await?;
+            }
+            LinkCommands::Add {
+                key,
+                relation,
+                other,
+            } => {
+                commands::link::run_add(&client, key, relation, other).await?;
+            }
+            LinkCommands::List { key } => {
+                commands::link::run_list(&client, &formatter, key).await?;
+            }
+            LinkCommands::Remove {
+                target,
+                relation,
+                other,
+            } => {
+                commands::link::run_remove(&client, target, relation, other).await?;
             }
-            ConfigCommands::Set {
+        },
+        Commands::Worklog { subcommand } => match subcommand {
+            WorklogCommands::Add {
+                key,
+                time,
+                started,
+                comment,
+                no_markdown,
+            } => {
+                commands::worklog::run_add(&client, key, time, started, comment, no_markdown)
+                    .await?;
+            }
+            WorklogCommands::List { key } => {
+                commands::worklog::run_list(&client, &formatter, key).await?;
+            }
+            WorklogCommands::Edit {
                 key,
-                value,
-                global,
-                local,
+                id,
+                time,
+                started,
+                comment,
+                no_markdown,
+            } => {
+                commands::worklog::run_edit(&client, key, id, time, started, comment, no_markdown)
+                    .await?;
+            }
+            WorklogCommands::Delete { key, id } => {
+                commands::worklog::run_delete(&client, key, id).await?;
+            }
+            WorklogCommands::Report { from, to } => {
+                commands::worklog::run_report(&client, &formatter, from, to).await?;
+            }
+        },
+        Commands::Board { subcommand } => match subcommand {
+            BoardCommands::List { project } => {
+                commands::board::run_list(&client, &formatter, project).await?;
+            }
+        },
+        Commands::Sprint { subcommand } => match subcommand {
+            SprintCommands::List { board, state } => {
+                commands::sprint::run_list(&client, &formatter, board, state).await?;
+            }
+            SprintCommands::View { sprint, board } => {
+                commands::sprint::run_view(&client, &formatter, sprint, board).await?;
+            }
+            SprintCommands::Add {
+                keys,
+                sprint,
+                board,
+            } => {
+                commands::sprint::run_add(&client, keys, sprint, board).await?;
+            }
+            SprintCommands::Start {
+                sprint,
+                board,
+                end,
+                days,
             } => {
-                commands::config::run_set(key, value, global, local).await?;
+                commands::sprint::run_start(&client, sprint, board, end, days).await?;
+            }
+            SprintCommands::Close { sprint, board } => {
+                commands::sprint::run_close(&client, sprint, board).await?;
+            }
+        },
+        Commands::Bulk {
+            jql,
+            yes,
+            limit,
+            concurrency,
+            no_markdown,
+            subcommand,
+        } => {
+            let action = match subcommand {
+                BulkCommands::Transition {
+                    status,
+                    resolution,
+                    comment,
+                } => commands::bulk::Action::Transition {
+                    status,
+                    resolution,
+                    comment,
+                },
+                BulkCommands::Edit { labels, fields } => {
+                    commands::bulk::Action::Edit { labels, fields }
+                }
+                BulkCommands::Assign { user } => commands::bulk::Action::Assign { user },
+                BulkCommands::Comment { message } => commands::bulk::Action::Comment { message },
+            };
+            commands::bulk::run(
+                &client,
+                &formatter,
+                jql,
+                action,
+                limit,
+                concurrency,
+                yes,
+                no_markdown,
+            )
+            .await?;
+        }
+        Commands::Auth { subcommand } => match subcommand {
+            AuthCommands::Login {
+                port,
+                scope,
+                no_browser,
+            } => {
+                commands::auth::run_login(&client, port, scope, no_browser).await?;
+            }
+            AuthCommands::Logout => {
+                commands::auth::run_logout(&client).await?;
             }
         },
         Commands::Doctor => {
@@ -469,12 +1349,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 commands::confluence::run_create(&client, title, space, parent, content, adf)
                     .await?;
             }
-            ConfluenceCommands::View { id, raw } => {
-                commands::confluence::run_view(&client, id, raw).await?;
+            ConfluenceCommands::View { id, raw, format } => {
+                commands::confluence::run_view(&client, id, raw, format).await?;
             }
             ConfluenceCommands::Attach { id, file, message } => {
                 commands::confluence::run_attach(&client, id, file, message).await?;
             }
+            ConfluenceCommands::Attachments { subcommand, id } => match subcommand {
+                None => {
+                    let id = id.ok_or("The page ID is required")?;
+                    commands::confluence::run_attachments_list(&client, &formatter, id).await?;
+                }
+                Some(PageAttachmentCommands::List { id }) => {
+                    commands::confluence::run_attachments_list(&client, &formatter, id).await?;
+                }
+                Some(PageAttachmentCommands::Download {
+                    id,
+                    name,
+                    output,
+                    force,
+                }) => {
+                    commands::confluence::run_attachments_download(
+                        &client, id, name, output, force,
+                    )
+                    .await?;
+                }
+                Some(PageAttachmentCommands::Delete { id, ids, name }) => {
+                    commands::confluence::run_attachments_delete(&client, id, ids, name).await?;
+                }
+            },
             ConfluenceCommands::Edit {
                 id,
                 full,
@@ -507,8 +1410,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .await?;
             }
         },
-        Commands::Completions { .. } => unreachable!(),
+        Commands::Completions { .. } | Commands::Config { .. } => unreachable!(),
     }
 
     Ok(())
 }
+
+async fn add_comment(client: &AtlassianClient, args: CommentArgs) -> Result<(), Error> {
+    let key = args.key.ok_or("The issue key is required")?;
+    let message = input::resolve_text(args.message, args.body_file)?;
+    commands::comment::run_add(
+        client,
+        key,
+        message,
+        args.visibility,
+        args.internal,
+        args.no_markdown,
+    )
+    .await
+}
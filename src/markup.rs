@@ -0,0 +1,556 @@
+//! Renderers from Atlassian Document Format (ADF) to the markup Data Center products accept:
+//! Jira wiki markup (Jira REST API v2) and the Confluence storage format (XHTML).
+
+use serde_json::Value;
+
+fn children(node: &Value) -> &[Value] {
+    node.get("content")
+        .and_then(|c| c.as_array())
+        .map(|a| a.as_slice())
+        .unwrap_or(&[])
+}
+
+fn attr<'a>(node: &'a Value, name: &str) -> &'a Value {
+    &node["attrs"][name]
+}
+
+fn text_of(node: &Value) -> String {
+    crate::adf::get_node_text(node)
+}
+
+// --- Jira wiki markup ---
+
+/// Render an ADF document as Jira wiki markup.
+pub fn to_wiki(node: &Value) -> String {
+    let rendered = if node["type"] == "doc" {
+        wiki_blocks(children(node))
+    } else {
+        wiki_block(node, "")
+    };
+    rendered.trim_end().to_string()
+}
+
+fn wiki_blocks(nodes: &[Value]) -> String {
+    nodes
+        .iter()
+        .map(|n| wiki_block(n, ""))
+        .filter(|b| !b.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// `list_prefix` is the run of `*`/`#` markers of the enclosing lists.
+fn wiki_block(node: &Value, list_prefix: &str) -> String {
+    match node["type"].as_str().unwrap_or("") {
+        "paragraph" => wiki_inlines(children(node)),
+        "heading" => {
+            let level = attr(node, "level").as_u64().unwrap_or(1).clamp(1, 6);
+            format!("h{}. {}", level, wiki_inlines(children(node)))
+        }
+        "bulletList" | "taskList" => wiki_list(node, &format!("{}*", list_prefix)),
+        "orderedList" => wiki_list(node, &format!("{}#", list_prefix)),
+        "codeBlock" => {
+            let code = text_of(node);
+            match attr(node, "language").as_str().filter(|l| !l.is_empty()) {
+                Some(language) => format!("{{code:{}}}\n{}\n{{code}}", language, code),
+                None => format!("{{code}}\n{}\n{{code}}", code),
+            }
+        }
+        "blockquote" => format!("{{quote}}\n{}\n{{quote}}", wiki_blocks(children(node))),
+        "panel" => {
+            let macro_name = match attr(node, "panelType").as_str().unwrap_or("info") {
+                "success" => "tip",
+                "warning" | "error" => "warning",
+                "note" => "note",
+                _ => "info",
+            };
+            format!(
+                "{{{0}}}\n{1}\n{{{0}}}",
+                macro_name,
+                wiki_blocks(children(node))
+            )
+        }
+        "rule" => "----".to_string(),
+        "table" => children(node)
+            .iter()
+            .map(|row| {
+                let mut line = String::new();
+                for cell in children(row) {
+                    let sep = if cell["type"] == "tableHeader" {
+                        "||"
+                    } else {
+                        "|"
+                    };
+                    let body = wiki_blocks(children(cell)).replace('\n', " ");
+                    line.push_str(&format!("{}{}", sep, body));
+                }
+                let last = if children(row)
+                    .last()
+                    .is_some_and(|c| c["type"] == "tableHeader")
+                {
+                    "||"
+                } else {
+                    "|"
+                };
+                format!("{}{}", line, last)
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        "mediaSingle" | "mediaGroup" => children(node)
+            .iter()
+            .map(wiki_media)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        "expand" | "nestedExpand" => {
+            let title = attr(node, "title").as_str().unwrap_or("");
+            let body = wiki_blocks(children(node));
+            if title.is_empty() {
+                body
+            } else {
+                format!("*{}*\n\n{}", escape_wiki(title), body)
+            }
+        }
+        "blockCard" | "embedCard" => format!("[{}]", attr(node, "url").as_str().unwrap_or("")),
+        _ => {
+            if node.get("text").is_some() {
+                wiki_inline(node)
+            } else {
+                wiki_blocks(children(node))
+            }
+        }
+    }
+}
+
+fn wiki_list(node: &Value, prefix: &str) -> String {
+    let mut lines = Vec::new();
+    for item in children(node) {
+        let mut text = Vec::new();
+        let mut nested = Vec::new();
+        if item["type"] == "taskItem" {
+            let done = attr(item, "state").as_str() == Some("DONE");
+            text.push(format!(
+                "{} {}",
+                if done { "(/)" } else { "(x)" },
+                wiki_inlines(children(item))
+            ));
+        }
+        for child in children(item) {
+            match child["type"].as_str().unwrap_or("") {
+                "bulletList" | "orderedList" | "taskList" => nested.push(wiki_block(child, prefix)),
+                _ if item["type"] == "taskItem" => {}
+                _ => text.push(wiki_block(child, prefix)),
+            }
+        }
+        lines.push(format!("{} {}", prefix, text.join(" ")));
+        lines.extend(nested);
+    }
+    lines.join("\n")
+}
+
+fn wiki_media(node: &Value) -> String {
+    match attr(node, "type").as_str() {
+        Some("external") => format!("!{}!", attr(node, "url").as_str().unwrap_or("")),
+        _ => {
+            let name = attr(node, "alt")
+                .as_str()
+                .filter(|a| !a.is_empty())
+                .or_else(|| attr(node, "id").as_str())
+                .unwrap_or("file");
+            format!("[^{}]", name)
+        }
+    }
+}
+
+fn wiki_inlines(nodes: &[Value]) -> String {
+    nodes.iter().map(wiki_inline).collect::<Vec<_>>().join("")
+}
+
+fn wiki_inline(node: &Value) -> String {
+    match node["type"].as_str().unwrap_or("") {
+        "text" => wiki_text(node),
+        "hardBreak" => "\n".to_string(),
+        "mention" => format!("[~{}]", attr(node, "id").as_str().unwrap_or("")),
+        "emoji" => attr(node, "text")
+            .as_str()
+            .or_else(|| attr(node, "shortName").as_str())
+            .unwrap_or("")
+            .to_string(),
+        "inlineCard" => format!("[{}]", attr(node, "url").as_str().unwrap_or("")),
+        "status" => format!("{{{{{}}}}}", attr(node, "text").as_str().unwrap_or("")),
+        "media" | "mediaInline" => wiki_media(node),
+        _ => wiki_inlines(children(node)),
+    }
+}
+
+fn wiki_text(node: &Value) -> String {
+    let text = node["text"].as_str().unwrap_or("");
+    let marks = node["marks"].as_array().cloned().unwrap_or_default();
+    let mut out = if marks.iter().any(|m| m["type"] == "code") {
+        format!("{{{{{}}}}}", text)
+    } else {
+        escape_wiki(text)
+    };
+    for mark in &marks {
+        out = match mark["type"].as_str().unwrap_or("") {
+            "strong" => format!("*{}*", out),
+            "em" => format!("_{}_", out),
+            "strike" => format!("-{}-", out),
+            "underline" => format!("+{}+", out),
+            "link" => format!("[{}|{}]", out, attr(mark, "href").as_str().unwrap_or("")),
+            _ => out,
+        };
+    }
+    out
+}
+
+/// Escape characters that start wiki markup effects or macros.
+fn escape_wiki(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '*' | '_' | '{' | '}' | '[' | ']' | '|' | '!' | '^' | '~'
+        ) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+// --- Confluence storage format ---
+
+/// Render an ADF document in the Confluence storage format (XHTML with `ac:` macros).
+pub fn to_storage(node: &Value) -> String {
+    if node["type"] == "doc" {
+        storage_blocks(children(node))
+    } else {
+        storage_block(node)
+    }
+}
+
+fn storage_blocks(nodes: &[Value]) -> String {
+    nodes.iter().map(storage_block).collect::<String>()
+}
+
+fn storage_block(node: &Value) -> String {
+    match node["type"].as_str().unwrap_or("") {
+        "paragraph" => format!("<p>{}</p>", storage_inlines(children(node))),
+        "heading" => {
+            let level = attr(node, "level").as_u64().unwrap_or(1).clamp(1, 6);
+            format!("<h{0}>{1}</h{0}>", level, storage_inlines(children(node)))
+        }
+        "bulletList" => format!("<ul>{}</ul>", storage_blocks(children(node))),
+        "orderedList" => match attr(node, "order").as_u64().filter(|n| *n != 1) {
+            Some(start) => format!(
+                "<ol start=\"{}\">{}</ol>",
+                start,
+                storage_blocks(children(node))
+            ),
+            None => format!("<ol>{}</ol>", storage_blocks(children(node))),
+        },
+        "listItem" => format!("<li>{}</li>", storage_blocks(children(node))),
+        "taskList" => format!(
+            "<ac:task-list>{}</ac:task-list>",
+            storage_blocks(children(node))
+        ),
+        "taskItem" => {
+            let status = if attr(node, "state").as_str() == Some("DONE") {
+                "complete"
+            } else {
+                "incomplete"
+            };
+            format!(
+                "<ac:task><ac:task-status>{}</ac:task-status><ac:task-body>{}</ac:task-body></ac:task>",
+                status,
+                storage_inlines(children(node))
+            )
+        }
+        "codeBlock" => {
+            let language = attr(node, "language")
+                .as_str()
+                .filter(|l| !l.is_empty())
+                .map(|l| {
+                    format!(
+                        "<ac:parameter ac:name=\"language\">{}</ac:parameter>",
+                        escape_xml(l)
+                    )
+                })
+                .unwrap_or_default();
+            format!(
+                "<ac:structured-macro ac:name=\"code\">{}<ac:plain-text-body><![CDATA[{}]]></ac:plain-text-body></ac:structured-macro>",
+                language,
+                text_of(node).replace("]]>", "]]]]><![CDATA[>")
+            )
+        }
+        "blockquote" => format!(
+            "<blockquote>{}</blockquote>",
+            storage_blocks(children(node))
+        ),
+        "panel" => {
+            let macro_name = match attr(node, "panelType").as_str().unwrap_or("info") {
+                "success" => "tip",
+                "warning" | "error" => "warning",
+                "note" => "note",
+                _ => "info",
+            };
+            storage_macro(macro_name, None, &storage_blocks(children(node)))
+        }
+        "expand" | "nestedExpand" => storage_macro(
+            "expand",
+            attr(node, "title").as_str(),
+            &storage_blocks(children(node)),
+        ),
+        "rule" => "<hr />".to_string(),
+        "table" => format!(
+            "<table><tbody>{}</tbody></table>",
+            children(node)
+                .iter()
+                .map(|row| format!("<tr>{}</tr>", storage_blocks(children(row))))
+                .collect::<String>()
+        ),
+        "tableHeader" => format!("<th>{}</th>", storage_blocks(children(node))),
+        "tableCell" => format!("<td>{}</td>", storage_blocks(children(node))),
+        "mediaSingle" | "mediaGroup" => children(node).iter().map(storage_media).collect(),
+        "blockCard" | "embedCard" => {
+            let url = escape_xml(attr(node, "url").as_str().unwrap_or(""));
+            format!("<p><a href=\"{0}\">{0}</a></p>", url)
+        }
+        _ => {
+            if node.get("text").is_some() {
+                storage_inline(node)
+            } else {
+                storage_blocks(children(node))
+            }
+        }
+    }
+}
+
+fn storage_macro(name: &str, title: Option<&str>, body: &str) -> String {
+    let title = title
+        .filter(|t| !t.is_empty())
+        .map(|t| {
+            format!(
+                "<ac:parameter ac:name=\"title\">{}</ac:parameter>",
+                escape_xml(t)
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        "<ac:structured-macro ac:name=\"{}\">{}<ac:rich-text-body>{}</ac:rich-text-body></ac:structured-macro>",
+        name, title, body
+    )
+}
+
+fn storage_media(node: &Value) -> String {
+    match attr(node, "type").as_str() {
+        Some("external") => format!(
+            "<ac:image><ri:url ri:value=\"{}\" /></ac:image>",
+            escape_xml(attr(node, "url").as_str().unwrap_or(""))
+        ),
+        _ => {
+            let name = attr(node, "alt")
+                .as_str()
+                .filter(|a| !a.is_empty())
+                .or_else(|| attr(node, "id").as_str())
+                .unwrap_or("file");
+            format!(
+                "<ac:image><ri:attachment ri:filename=\"{}\" /></ac:image>",
+                escape_xml(name)
+            )
+        }
+    }
+}
+
+fn storage_inlines(nodes: &[Value]) -> String {
+    nodes.iter().map(storage_inline).collect()
+}
+
+fn storage_inline(node: &Value) -> String {
+    match node["type"].as_str().unwrap_or("") {
+        "text" => storage_text(node),
+        "hardBreak" => "<br />".to_string(),
+        "mention" => format!(
+            "<ac:link><ri:user ri:username=\"{}\" /></ac:link>",
+            escape_xml(attr(node, "id").as_str().unwrap_or(""))
+        ),
+        "emoji" => escape_xml(
+            attr(node, "text")
+                .as_str()
+                .or_else(|| attr(node, "shortName").as_str())
+                .unwrap_or(""),
+        ),
+        "inlineCard" => {
+            let url = escape_xml(attr(node, "url").as_str().unwrap_or(""));
+            format!("<a href=\"{0}\">{0}</a>", url)
+        }
+        "status" => format!(
+            "<code>{}</code>",
+            escape_xml(attr(node, "text").as_str().unwrap_or(""))
+        ),
+        "media" | "mediaInline" => storage_media(node),
+        _ => storage_inlines(children(node)),
+    }
+}
+
+fn storage_text(node: &Value) -> String {
+    let mut out = escape_xml(node["text"].as_str().unwrap_or(""));
+    for mark in node["marks"].as_array().into_iter().flatten() {
+        out = match mark["type"].as_str().unwrap_or("") {
+            "strong" => format!("<strong>{}</strong>", out),
+            "em" => format!("<em>{}</em>", out),
+            "strike" => format!("<s>{}</s>", out),
+            "underline" => format!("<u>{}</u>", out),
+            "code" => format!("<code>{}</code>", out),
+            "link" => format!(
+                "<a href=\"{}\">{}</a>",
+                escape_xml(attr(mark, "href").as_str().unwrap_or("")),
+                out
+            ),
+            _ => out,
+        };
+    }
+    out
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Replace `old` with `new` in the text of a storage format body. Tags, their attributes
+/// and CDATA sections (code macros) are left alone, and both strings are XML-escaped so
+/// they match and produce text rather than markup.
+pub fn replace_text(storage: &str, old: &str, new: &str) -> String {
+    let (old, new) = (escape_xml(old), escape_xml(new));
+    let mut out = String::new();
+    let mut rest = storage;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start].replace(&old, &new));
+        let end = if rest[start..].starts_with("<![CDATA[") {
+            rest[start..].find("]]>").map(|i| i + 3)
+        } else {
+            rest[start..].find('>').map(|i| i + 1)
+        };
+        let end = start + end.unwrap_or(rest.len() - start);
+        out.push_str(&rest[start..end]);
+        rest = &rest[end..];
+    }
+    out.push_str(&rest.replace(&old, &new));
+    out
+}
+
+/// Plain text of a storage format body, for display: tags are dropped, block ends become
+/// line breaks and common entities are decoded.
+pub fn storage_to_text(storage: &str) -> String {
+    let mut out = String::new();
+    let mut rest = storage;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("");
+        let closing = tag.starts_with('/');
+        match name {
+            "p" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "tr" | "li" | "ac:task"
+                if closing && !out.ends_with('\n') =>
+            {
+                out.push('\n')
+            }
+            "br" | "hr" => out.push('\n'),
+            "li" | "ac:task" if !closing => out.push_str("• "),
+            "td" | "th" if closing => out.push('\t'),
+            _ if tag.starts_with("![CDATA[") => {
+                // Keep code block contents verbatim
+                let cdata = &rest[start + 9..];
+                let cdata_end = cdata.find("]]>").unwrap_or(cdata.len());
+                out.push_str(&cdata[..cdata_end]);
+                out.push('\n');
+                rest = &cdata[(cdata_end + 3).min(cdata.len())..];
+                continue;
+            }
+            _ => {}
+        }
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    out.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adf;
+    use serde_json::json;
+
+    #[test]
+    fn test_to_wiki() {
+        let doc = adf::from_text(
+            "## Plan\n\nShip **1.4** with `--flag` and [docs](https://x.io).\n\n- one\n  - nested\n- two\n\n1. first\n\n```rust\nfn main() {}\n```\n\n> quoted",
+            false,
+        );
+        assert_eq!(
+            to_wiki(&doc),
+            "h2. Plan\n\n\
+             Ship *1.4* with {{--flag}} and [docs|https://x.io].\n\n\
+             * one\n** nested\n* two\n\n\
+             # first\n\n\
+             {code:rust}\nfn main() {}\n{code}\n\n\
+             {quote}\nquoted\n{quote}"
+        );
+
+        let table = json!({ "type": "table", "content": [
+            { "type": "tableRow", "content": [
+                { "type": "tableHeader", "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": "A" }] }] },
+                { "type": "tableHeader", "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": "B" }] }] }
+            ]},
+            { "type": "tableRow", "content": [
+                { "type": "tableCell", "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": "1" }] }] },
+                { "type": "tableCell", "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": "2|3" }] }] }
+            ]}
+        ]});
+        assert_eq!(to_wiki(&table), "||A||B||\n|1|2\\|3|");
+    }
+
+    #[test]
+    fn test_replace_text_skips_markup() {
+        let storage = "<p class=\"old\"><a href=\"https://old.example\">old &amp; new</a></p>\
+                       <ac:structured-macro ac:name=\"code\"><ac:plain-text-body>\
+                       <![CDATA[old > x]]></ac:plain-text-body></ac:structured-macro>";
+        assert_eq!(
+            replace_text(storage, "old & new", "<b>"),
+            "<p class=\"old\"><a href=\"https://old.example\">&lt;b&gt;</a></p>\
+             <ac:structured-macro ac:name=\"code\"><ac:plain-text-body>\
+             <![CDATA[old > x]]></ac:plain-text-body></ac:structured-macro>"
+        );
+        assert_eq!(replace_text("old <br/> old", "old", "new"), "new <br/> new");
+    }
+
+    #[test]
+    fn test_to_storage() {
+        let doc = adf::from_text("# Title\n\nA & B *em*\n\n- item\n\n```\nx < y\n```", false);
+        assert_eq!(
+            to_storage(&doc),
+            "<h1>Title</h1>\
+             <p>A &amp; B <em>em</em></p>\
+             <ul><li><p>item</p></li></ul>\
+             <ac:structured-macro ac:name=\"code\"><ac:plain-text-body><![CDATA[x < y]]></ac:plain-text-body></ac:structured-macro>"
+        );
+        assert_eq!(
+            storage_to_text(&to_storage(&doc)),
+            "Title\nA & B em\n• item\nx < y\n"
+        );
+    }
+}
@@ -0,0 +1,413 @@
+//! OAuth 2.0 (3LO) for Atlassian Cloud: the authorization-code flow with PKCE, the
+//! refresh grant, and the on-disk token store.
+
+use crate::error::Error;
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+pub const DEFAULT_OAUTH_URL: &str = "https://auth.atlassian.com";
+pub const DEFAULT_API_URL: &str = "https://api.atlassian.com";
+
+/// Scopes requested by `jiri auth login` unless overridden. `offline_access` yields the
+/// refresh token.
+pub const DEFAULT_SCOPES: &str = "read:jira-work write:jira-work read:jira-user \
+    read:confluence-content.all write:confluence-content read:confluence-space.summary \
+    search:confluence offline_access";
+
+/// Refresh this long before the access token expires, so it does not lapse mid-request.
+const EXPIRY_MARGIN_SECS: u64 = 60;
+
+/// How long `login` waits for the browser to come back to the redirect listener.
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The OAuth app and endpoints of the active configuration.
+#[derive(Debug, Clone)]
+pub struct OAuthSettings {
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    /// Authorization server, `https://auth.atlassian.com` unless overridden.
+    pub oauth_url: String,
+    /// API gateway requests are routed through, `https://api.atlassian.com` unless overridden.
+    pub api_url: String,
+    /// Where the session of this profile is stored.
+    pub store: PathBuf,
+}
+
+/// Tokens and the site they were granted for, as stored between runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    /// Unix time in seconds at which the access token expires.
+    pub expires_at: u64,
+    pub cloud_id: String,
+    pub site: String,
+}
+
+impl Session {
+    /// Load the stored session, or `None` if not logged in.
+    pub fn load(path: &Path) -> Result<Option<Session>, String> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map(Some)
+                .map_err(|e| format!("Invalid OAuth session in {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Write the session readable by the owner only, since it holds live credentials.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let io_error = |e: std::io::Error| format!("Failed to write {}: {}", path.display(), e);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        let contents = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+            // The mode only applies to new files; tighten an existing one as well
+            if path.exists() {
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+                    .map_err(io_error)?;
+            }
+        }
+        let mut file = options.open(path).map_err(io_error)?;
+        std::io::Write::write_all(&mut file, contents.as_bytes()).map_err(io_error)
+    }
+
+    /// Whether the access token has expired or is about to.
+    pub fn is_expired(&self) -> bool {
+        unix_now() + EXPIRY_MARGIN_SECS >= self.expires_at
+    }
+
+    /// Take over the tokens of a refresh response. Atlassian rotates refresh tokens, but
+    /// keeps the old one valid when none is returned.
+    pub fn update(&mut self, tokens: TokenResponse) {
+        self.access_token = tokens.access_token;
+        if tokens.refresh_token.is_some() {
+            self.refresh_token = tokens.refresh_token;
+        }
+        self.expires_at = unix_now() + tokens.expires_in;
+    }
+}
+
+/// The fields jiri uses from a token endpoint response.
+#[derive(Debug, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    #[serde(default = "default_expires_in")]
+    pub expires_in: u64,
+}
+
+fn default_expires_in() -> u64 {
+    3600
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Run the authorization-code flow with PKCE.
+///
+/// `listener` receives the redirect; `open` is handed the authorization URL to show the user.
+/// Returns the tokens; the caller picks the site with [`accessible_resources`].
+pub async fn authorize(
+    http: &reqwest::Client,
+    settings: &OAuthSettings,
+    listener: TcpListener,
+    scopes: &str,
+    open: impl FnOnce(&str),
+) -> Result<TokenResponse, Error> {
+    let port = listener
+        .local_addr()
+        .map_err(|e| Error::Other(format!("Failed to start redirect listener: {}", e)))?
+        .port();
+    let redirect_uri = format!("http://localhost:{}/callback", port);
+    let verifier = random_token(32);
+    let challenge = pkce_challenge(&verifier);
+    let state = random_token(16);
+
+    let url = format!(
+        "{}/authorize?audience=api.atlassian.com&client_id={}&scope={}&redirect_uri={}\
+         &state={}&response_type=code&prompt=consent&code_challenge={}\
+         &code_challenge_method=S256",
+        settings.oauth_url.trim_end_matches('/'),
+        urlencoding::encode(&settings.client_id),
+        urlencoding::encode(scopes),
+        urlencoding::encode(&redirect_uri),
+        state,
+        challenge
+    );
+    open(&url);
+
+    let code = tokio::time::timeout(LOGIN_TIMEOUT, receive_code(listener, &state))
+        .await
+        .map_err(|_| "Timed out waiting for the browser to complete the login")??;
+
+    let mut body = serde_json::json!({
+        "grant_type": "authorization_code",
+        "client_id": settings.client_id,
+        "code": code,
+        "redirect_uri": redirect_uri,
+        "code_verifier": verifier,
+    });
+    if let Some(secret) = &settings.client_secret {
+        body["client_secret"] = Value::String(secret.clone());
+    }
+    token_request(http, settings, body).await
+}
+
+/// Exchange a refresh token for a new access token.
+pub async fn refresh(
+    http: &reqwest::Client,
+    settings: &OAuthSettings,
+    refresh_token: &str,
+) -> Result<TokenResponse, Error> {
+    let mut body = serde_json::json!({
+        "grant_type": "refresh_token",
+        "client_id": settings.client_id,
+        "refresh_token": refresh_token,
+    });
+    if let Some(secret) = &settings.client_secret {
+        body["client_secret"] = Value::String(secret.clone());
+    }
+    token_request(http, settings, body).await
+}
+
+async fn token_request(
+    http: &reqwest::Client,
+    settings: &OAuthSettings,
+    body: Value,
+) -> Result<TokenResponse, Error> {
+    let method = reqwest::Method::POST;
+    let url = format!("{}/oauth/token", settings.oauth_url.trim_end_matches('/'));
+    let response = http
+        .post(&url)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| Error::transport(&method, &url, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(crate::error::ApiError::new(status, method, url, text).into());
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| Error::transport(&method, &url, e))
+}
+
+/// List the sites (`id` is the cloud ID, plus `url` and `name`) the token grants access to.
+pub async fn accessible_resources(
+    http: &reqwest::Client,
+    settings: &OAuthSettings,
+    access_token: &str,
+) -> Result<Vec<Value>, Error> {
+    let method = reqwest::Method::GET;
+    let url = format!(
+        "{}/oauth/token/accessible-resources",
+        settings.api_url.trim_end_matches('/')
+    );
+    let response = http
+        .get(&url)
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .map_err(|e| Error::transport(&method, &url, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(crate::error::ApiError::new(status, method, url, text).into());
+    }
+    let data: Value = response
+        .json()
+        .await
+        .map_err(|e| Error::transport(&method, &url, e))?;
+    Ok(data.as_array().cloned().unwrap_or_default())
+}
+
+/// Serve the redirect listener until the browser arrives with the authorization code.
+/// Other requests (e.g. `/favicon.ico`) get a 404 and are otherwise ignored.
+async fn receive_code(listener: TcpListener, state: &str) -> Result<String, Error> {
+    let io_error = |e: std::io::Error| Error::Other(format!("Redirect listener failed: {}", e));
+    loop {
+        let (mut stream, _) = listener.accept().await.map_err(io_error)?;
+        let mut request = Vec::new();
+        let mut buf = [0; 4096];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 64 * 1024 {
+            let read = stream.read(&mut buf).await.map_err(io_error)?;
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..read]);
+        }
+
+        let request = String::from_utf8_lossy(&request);
+        let target = request.split_whitespace().nth(1).unwrap_or("");
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let params: Vec<(String, String)> = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(k, v)| {
+                let decode = |s: &str| {
+                    urlencoding::decode(&s.replace('+', " "))
+                        .map(|d| d.into_owned())
+                        .unwrap_or_default()
+                };
+                (decode(k), decode(v))
+            })
+            .collect();
+        let param = |name: &str| {
+            params
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.as_str())
+        };
+
+        let result = if path != "/callback" {
+            None
+        } else if let Some(error) = param("error") {
+            let description = param("error_description").unwrap_or(error);
+            Some(Err(format!("Authorization was denied: {}", description)))
+        } else if param("state") != Some(state) {
+            Some(Err(
+                "The redirect carried an unexpected state; try logging in again".to_string(),
+            ))
+        } else {
+            param("code").map(|code| Ok(code.to_string()))
+        };
+
+        let (status, message) = match &result {
+            None => ("404 Not Found", "Not found"),
+            Some(Ok(_)) => ("200 OK", "Logged in to jiri. You can close this window."),
+            Some(Err(_)) => ("400 Bad Request", "jiri login failed. See the terminal."),
+        };
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{}",
+            status,
+            message.len(),
+            message
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+        let _ = stream.shutdown().await;
+
+        if let Some(result) = result {
+            return result.map_err(Error::Other);
+        }
+    }
+}
+
+/// A URL-safe random string of `bytes` random bytes.
+fn random_token(bytes: usize) -> String {
+    let data: Vec<u8> = (0..bytes).map(|_| rand::random::<u8>()).collect();
+    general_purpose::URL_SAFE_NO_PAD.encode(data)
+}
+
+/// The PKCE S256 code challenge for `verifier` (RFC 7636 section 4.2).
+fn pkce_challenge(verifier: &str) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_pkce_challenge() {
+        // RFC 7636 appendix B
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_authorize_against_stand_in_server() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/oauth/token"))
+            .and(body_partial_json(json!({
+                "grant_type": "authorization_code",
+                "client_id": "app",
+                "code": "the-code",
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "access_token": "access-1",
+                "refresh_token": "refresh-1",
+                "expires_in": 3600
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/oauth/token/accessible-resources"))
+            .and(header("authorization", "Bearer access-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "id": "cloud-1", "url": "https://acme.atlassian.net", "name": "acme" }
+            ])))
+            .mount(&server)
+            .await;
+
+        let settings = OAuthSettings {
+            client_id: "app".to_string(),
+            client_secret: None,
+            oauth_url: server.uri(),
+            api_url: server.uri(),
+            store: PathBuf::new(),
+        };
+        let http = reqwest::Client::new();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+        // Play the browser: follow the authorization URL back to the redirect URI
+        let tokens = authorize(&http, &settings, listener, DEFAULT_SCOPES, |url| {
+            let query = url.split_once('?').unwrap().1;
+            let param = |name: &str| {
+                query
+                    .split('&')
+                    .find_map(|p| p.strip_prefix(&format!("{}=", name)))
+                    .map(|v| urlencoding::decode(v).unwrap().into_owned())
+                    .unwrap()
+            };
+            assert_eq!(param("code_challenge_method"), "S256");
+            let callback = format!(
+                "{}?code=the-code&state={}",
+                param("redirect_uri"),
+                param("state")
+            );
+            tokio::spawn(async move {
+                reqwest::get(callback.replace("localhost", "127.0.0.1"))
+                    .await
+                    .unwrap();
+            });
+        })
+        .await
+        .unwrap();
+        assert_eq!(tokens.access_token, "access-1");
+        assert_eq!(tokens.refresh_token.as_deref(), Some("refresh-1"));
+
+        let sites = accessible_resources(&http, &settings, &tokens.access_token)
+            .await
+            .unwrap();
+        assert_eq!(sites[0]["id"], "cloud-1");
+    }
+}
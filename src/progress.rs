@@ -0,0 +1,101 @@
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const BAR_WIDTH: usize = 30;
+
+/// A byte-count progress bar drawn on stderr. Does nothing when stderr is not a terminal,
+/// so piped and scripted output stays clean.
+pub struct Progress {
+    label: String,
+    total: u64,
+    done: AtomicU64,
+    /// Last drawn position in tenths of a percent, to avoid redrawing on every chunk.
+    drawn: AtomicU64,
+    enabled: bool,
+}
+
+impl Progress {
+    pub fn new(label: impl Into<String>, total: u64) -> Self {
+        Self {
+            label: label.into(),
+            total,
+            done: AtomicU64::new(0),
+            drawn: AtomicU64::new(u64::MAX),
+            enabled: total > 0 && std::io::stderr().is_terminal(),
+        }
+    }
+
+    /// Record `bytes` more bytes and redraw if the bar moved.
+    pub fn add(&self, bytes: u64) {
+        let done = self.done.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        if !self.enabled {
+            return;
+        }
+        let permille = done.min(self.total) * 1000 / self.total;
+        if self.drawn.swap(permille, Ordering::Relaxed) != permille {
+            eprint!("\r{}", render(&self.label, done, self.total));
+            std::io::stderr().flush().ok();
+        }
+    }
+
+    /// Start over, e.g. when a request is retried.
+    pub fn reset(&self) {
+        self.done.store(0, Ordering::Relaxed);
+        self.drawn.store(u64::MAX, Ordering::Relaxed);
+    }
+
+    /// Clear the bar from the terminal.
+    pub fn finish(&self) {
+        if self.enabled {
+            eprint!("\r\x1b[2K");
+            std::io::stderr().flush().ok();
+        }
+    }
+}
+
+/// e.g. `Uploading [==========>         ]  50%  1.0 MB / 2.0 MB`
+fn render(label: &str, done: u64, total: u64) -> String {
+    let done = done.min(total);
+    let filled = if total == 0 {
+        BAR_WIDTH
+    } else {
+        (done as u128 * BAR_WIDTH as u128 / total as u128) as usize
+    };
+    let bar = if filled >= BAR_WIDTH {
+        "=".repeat(BAR_WIDTH)
+    } else {
+        format!(
+            "{}>{}",
+            "=".repeat(filled),
+            " ".repeat(BAR_WIDTH - filled - 1)
+        )
+    };
+    let percent = (done * 100).checked_div(total).unwrap_or(100);
+    format!(
+        "{} [{}] {:>3}%  {} / {}",
+        label,
+        bar,
+        percent,
+        crate::commands::attachments::format_size(done),
+        crate::commands::attachments::format_size(total)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render("Uploading", 1024 * 1024, 2 * 1024 * 1024),
+            format!(
+                "Uploading [{}>{}]  50%  1.0 MB / 2.0 MB",
+                "=".repeat(15),
+                " ".repeat(14)
+            )
+        );
+        assert!(render("Uploading", 0, 10).starts_with("Uploading [>"));
+        assert!(render("Uploading", 10, 10).contains(&format!("[{}] 100%", "=".repeat(30))));
+    }
+}
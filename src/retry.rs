@@ -0,0 +1,143 @@
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// Policy for retrying rate-limited (429) and temporarily unavailable (503) responses.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the initial attempt.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled on every subsequent attempt.
+    pub base_delay: Duration,
+    /// Upper bound for a single computed backoff delay.
+    pub max_delay: Duration,
+    /// Upper bound for the total time spent waiting across all retries of one request.
+    pub max_total_wait: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            max_total_wait: Duration::from_secs(120),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether a response with this status should be retried.
+    pub fn is_retryable(status: StatusCode) -> bool {
+        status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE
+    }
+
+    /// Exponential backoff for the given (zero-based) retry attempt, with "equal jitter":
+    /// half of the delay is fixed and the other half is random.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.min(16)));
+        let capped = exp.min(self.max_delay);
+        let half = capped / 2;
+        let jitter_ms = rand::rng().random_range(0..=half.as_millis() as u64);
+        half + Duration::from_millis(jitter_ms)
+    }
+
+    /// Compute the delay before the next retry, or `None` if the request should not be retried.
+    ///
+    /// A server-provided `Retry-After` takes precedence over the computed backoff. Retrying stops
+    /// once `max_retries` is reached or the delay would push the total wait past `max_total_wait`.
+    pub fn next_delay(
+        &self,
+        attempt: u32,
+        retry_after: Option<Duration>,
+        waited: Duration,
+    ) -> Option<Duration> {
+        if attempt >= self.max_retries {
+            return None;
+        }
+        let delay = retry_after.unwrap_or_else(|| self.backoff(attempt));
+        if waited + delay > self.max_total_wait {
+            return None;
+        }
+        Some(delay)
+    }
+}
+
+/// Parse a `Retry-After` header given in delta-seconds (the form Atlassian Cloud sends).
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_backoff_is_bounded() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            max_total_wait: Duration::from_secs(60),
+        };
+
+        for attempt in 0..10 {
+            let cap = (Duration::from_millis(100) * 2u32.pow(attempt)).min(policy.max_delay);
+            let delay = policy.backoff(attempt);
+            assert!(
+                delay >= cap / 2,
+                "attempt {}: {:?} < {:?}",
+                attempt,
+                delay,
+                cap / 2
+            );
+            assert!(delay <= cap, "attempt {}: {:?} > {:?}", attempt, delay, cap);
+        }
+    }
+
+    #[test]
+    fn test_next_delay_limits() {
+        let policy = RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+            max_total_wait: Duration::from_secs(5),
+        };
+
+        let retry_after = Some(Duration::from_secs(3));
+        assert_eq!(
+            policy.next_delay(0, retry_after, Duration::ZERO),
+            Some(Duration::from_secs(3))
+        );
+        // Would exceed the total wait cap
+        assert_eq!(
+            policy.next_delay(1, retry_after, Duration::from_secs(3)),
+            None
+        );
+        // Out of retries
+        assert_eq!(policy.next_delay(2, None, Duration::ZERO), None);
+    }
+
+    #[test]
+    fn test_retry_after_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), None);
+    }
+}